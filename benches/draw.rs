@@ -0,0 +1,79 @@
+//! Benchmarks of the layout/draw hot path
+//!
+//! A real `StatusBar` needs an X server, so the full-bar benchmark
+//! draws N widgets on an image surface instead; the layout cost is
+//! dominated by the per-widget `size` queries benched below
+
+use barust::{
+    utils::Rectangle,
+    widgets::{Text, Widget, WidgetConfig},
+};
+use cairo::{Context, Format, ImageSurface};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+
+fn text_widget(content: &str) -> Box<Text> {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    runtime.block_on(async {
+        let mut text = Text::new("", &WidgetConfig::default()).await;
+        text.set_text(content);
+        text
+    })
+}
+
+fn bench_text_draw(c: &mut Criterion) {
+    let text = text_widget("benchmark");
+    let surface = ImageSurface::create(Format::ARgb32, 400, 21).unwrap();
+    let rectangle = Rectangle {
+        x: 0,
+        y: 0,
+        width: 400,
+        height: 21,
+    };
+    c.bench_function("text_draw", |b| {
+        b.iter(|| {
+            let context = Context::new(&surface).unwrap();
+            text.draw(context, &rectangle).unwrap();
+        })
+    });
+}
+
+fn bench_text_size(c: &mut Criterion) {
+    let text = text_widget("benchmark");
+    let surface = ImageSurface::create(Format::ARgb32, 400, 21).unwrap();
+    let context = Context::new(&surface).unwrap();
+    c.bench_function("text_size", |b| {
+        b.iter(|| text.size(&context).unwrap())
+    });
+}
+
+fn bench_bar_draw(c: &mut Criterion) {
+    let mut group = c.benchmark_group("bar_draw");
+    for n in [1usize, 10, 50] {
+        let widgets: Vec<_> = (0..n)
+            .map(|i| text_widget(&format!("widget {i}")))
+            .collect();
+        let surface = ImageSurface::create(Format::ARgb32, 1920, 21).unwrap();
+        group.bench_with_input(BenchmarkId::from_parameter(n), &n, |b, _| {
+            b.iter(|| {
+                let context = Context::new(&surface).unwrap();
+                context.set_operator(cairo::Operator::Clear);
+                context.paint().unwrap();
+                for (i, widget) in widgets.iter().enumerate() {
+                    let rectangle = Rectangle {
+                        x: (i * 38) as u32,
+                        y: 0,
+                        width: 38,
+                        height: 21,
+                    };
+                    let context = Context::new(&surface).unwrap();
+                    context.translate(f64::from(rectangle.x), 0.0);
+                    widget.draw(context, &rectangle).unwrap();
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_text_draw, bench_text_size, bench_bar_draw);
+criterion_main!(benches);
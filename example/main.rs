@@ -1,7 +1,5 @@
 mod elite;
-mod qtile;
 
-use crate::qtile::QtileStatusProvider;
 use barust::{
     statusbar::StatusBar,
     utils::{Color, Position},
@@ -83,7 +81,14 @@ async fn main() -> Result<()> {
         Disk::new("💾 %f", "/", &wd_config).await,
         Wlan::new("📡 %e", "wlp1s0".to_string(), &wd_config).await,
         Cpu::new("💻 %p󱉸", &wd_config).await?,
-        Battery::new("%i %c󱉸", None, &wd_config, NotifySend::default()).await?,
+        Battery::new(
+            "%i %c󱉸",
+            Box::new(PowerSupplyProvider::new().await?),
+            None,
+            &wd_config,
+            NotifySend::default(),
+        )
+        .await?,
         Volume::new(
             "%i %p",
             Box::new(PulseaudioProvider::new().await.unwrap()),
@@ -91,7 +96,13 @@ async fn main() -> Result<()> {
             &wd_config,
         )
         .await,
-        Brightness::new("%i %p󱉸", None, None, &wd_config).await?,
+        Brightness::new(
+            "%i %p󱉸",
+            None,
+            Box::new(SysfsProvider::new(None).await?),
+            &wd_config,
+        )
+        .await?,
         Clock::new("🕓 %H:%M %d/%m/%Y", &wd_config).await,
     ];
     StatusBar::create()
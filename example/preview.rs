@@ -0,0 +1,24 @@
+use barust::{
+    utils::{headless, Background, Color},
+    widgets::{Clock, Text, Widget, WidgetConfig},
+};
+
+#[tokio::main]
+async fn main() -> barust::Result<()> {
+    let config = WidgetConfig::default();
+    let mut widgets: Vec<Box<dyn Widget>> = vec![
+        Text::new("hello barust", &config).await,
+        Clock::new("%H:%M %d/%m", &config).await,
+    ];
+    headless::render_png(
+        &mut widgets,
+        &Background::Flat(Color::new(0.1, 0.1, 0.1, 1.0)),
+        600,
+        21,
+        10,
+        "preview.png",
+    )
+    .await?;
+    println!("rendered preview.png");
+    Ok(())
+}
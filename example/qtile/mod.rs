@@ -1,3 +0,0 @@
-mod workspace;
-
-pub use workspace::*;
@@ -0,0 +1,48 @@
+//! Sends IPC commands to a running bar
+//!
+//! The bar must have been built with
+//! [ipc_socket](barust::statusbar::StatusBarBuilder::ipc_socket),
+//! the socket path is taken from `BARUST_SOCKET` when set
+
+use barust::utils::ipc::default_socket_path;
+use std::{env, io::Write, os::unix::net::UnixStream, path::PathBuf, process::exit};
+
+fn usage() -> ! {
+    eprintln!("usage: barust-ctl update <name>");
+    eprintln!("       barust-ctl insert <index> <widget> [options-json]");
+    eprintln!("       barust-ctl remove <index>");
+    eprintln!("       barust-ctl hide <group>");
+    eprintln!("       barust-ctl show <group>");
+    exit(1)
+}
+
+fn main() {
+    let socket = env::var("BARUST_SOCKET")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| default_socket_path());
+    let args: Vec<String> = env::args().skip(1).collect();
+    let command = match args.iter().map(String::as_str).collect::<Vec<_>>()[..] {
+        ["update", name] => format!(r#"{{"command": "update_widget", "name": "{name}"}}"#),
+        ["insert", index, widget] => {
+            format!(r#"{{"command": "insert_widget", "index": {index}, "widget": "{widget}"}}"#)
+        }
+        ["insert", index, widget, options] => format!(
+            r#"{{"command": "insert_widget", "index": {index}, "widget": "{widget}", "options": {options}}}"#
+        ),
+        ["remove", index] => format!(r#"{{"command": "remove_widget", "index": {index}}}"#),
+        ["hide", group] => format!(r#"{{"command": "hide_group", "group": "{group}"}}"#),
+        ["show", group] => format!(r#"{{"command": "show_group", "group": "{group}"}}"#),
+        _ => usage(),
+    };
+    let mut stream = match UnixStream::connect(&socket) {
+        Ok(stream) => stream,
+        Err(e) => {
+            eprintln!("cannot connect to {}: {e}", socket.display());
+            exit(1)
+        }
+    };
+    if let Err(e) = writeln!(stream, "{command}") {
+        eprintln!("cannot send the command: {e}");
+        exit(1)
+    }
+}
@@ -1,32 +1,81 @@
 use crate::{
     utils::{
-        screen_true_height, screen_true_width, set_source_rgba, Atoms, Color, HookSender, Position,
-        Rectangle, StatusBarInfo, TimedHooks, WidgetIndex,
+        atoms::intern_atom, ipc, metrics, power, screen_dpi, screen_true_height,
+        screen_true_width, shared_connection, sun,
+        Atoms, Background, Color, HookEvent, HookKind, HookSender, IpcCommand, PersistentState,
+        Position, Rectangle, StatusBarInfo, Theme, TimedHooks, WidgetIndex,
+    },
+    widgets::{
+        ClickEvent, ClickModifiers, MouseButton, OnError, ReplaceableWidget, Size, Widget,
+        WidgetConfig, WidgetRegistry,
     },
-    widgets::{ReplaceableWidget, Size, Widget},
     BarustError, Result,
 };
-use async_channel::{bounded, Receiver};
+use async_channel::{bounded, Receiver, Sender};
 use cairo::{Context, Operator, XCBConnection, XCBDrawable, XCBSurface, XCBVisualType};
 use futures::future::join_all;
 use log::{debug, error, warn};
-use std::{sync::Arc, thread};
+use std::{
+    path::PathBuf,
+    sync::Arc,
+    thread,
+    time::{Duration, Instant},
+};
 use tokio::{
     select,
     signal::unix::{signal, SignalKind},
     spawn,
+    time::sleep,
 };
 use xcb::{
     x::{
-        Colormap, ColormapAlloc, CreateColormap, CreateWindow, Cw, EventMask, MapWindow, Pixmap,
-        VisualClass, Visualtype, Window, WindowClass,
+        Colormap, ColormapAlloc, ConfigWindow, ConfigureWindow, CreateColormap, CreateWindow, Cw,
+        EventMask, GrabKey, GrabMode, MapWindow, ModMask, Pixmap, UnmapWindow, VisualClass,
+        Visualtype, Window, WindowClass,
     },
     Connection, Event, Xid,
 };
 
+/// How long updates are coalesced before a redraw (~60fps)
+const FRAME_BUDGET: Duration = Duration::from_millis(16);
+
+/// The frame budget while [low_power](power::low_power) is active (~10fps)
+const LOW_POWER_FRAME_BUDGET: Duration = Duration::from_millis(100);
+
+/// How many frames a [FrameTimes] histogram spans
+const FRAME_LOG_INTERVAL: u32 = 600;
+
+/// Histogram of full redraw durations, logged at debug level
+/// every [FRAME_LOG_INTERVAL] frames so layout/draw regressions
+/// show up in the logs
+#[derive(Debug, Default)]
+struct FrameTimes {
+    /// power of two millisecond buckets: <1ms, <2ms, ... >=64ms
+    buckets: [u32; 8],
+    frames: u32,
+}
+
+impl FrameTimes {
+    fn record(&mut self, elapsed: Duration) {
+        let ms = elapsed.as_millis() as u32;
+        let mut index = 0;
+        let mut limit = 1;
+        while ms >= limit && index < self.buckets.len() - 1 {
+            limit *= 2;
+            index += 1;
+        }
+        self.buckets[index] += 1;
+        self.frames += 1;
+        if self.frames >= FRAME_LOG_INTERVAL {
+            debug!("frame times (ms buckets <1 to >=64): {:?}", self.buckets);
+            *self = Self::default();
+        }
+    }
+}
+
 /// Represents the Bar displayed on the screen
 pub struct StatusBar {
-    background: Color,
+    background: Background,
     connection: Arc<Connection>,
     regions: Vec<Rectangle>,
     widgets: Vec<ReplaceableWidget>,
@@ -35,6 +84,136 @@ pub struct StatusBar {
     width: u32,
     window: Window,
     position: Position,
+    theme: Theme,
+    theme_loader: Option<ThemeLoader>,
+    // widget index ranges of each named page
+    pages: Vec<(String, std::ops::Range<usize>)>,
+    active_page: usize,
+    screen_id: i32,
+    xoff: u16,
+    yoff: u16,
+    // the width follows the screen when none was requested
+    auto_width: bool,
+    spacing: u32,
+    margins: Margins,
+    on_before_draw: Option<DrawHook>,
+    on_after_layout: Option<LayoutHook>,
+    // (modifiers, keycode, action) of every grabbed key
+    hotkeys: Vec<(ModMask, u8, HotkeyAction)>,
+    hidden: bool,
+    frame_times: FrameTimes,
+    overflow: OverflowPolicy,
+    on_error: OnError,
+    sigusr1: SignalAction,
+    sigusr2: SignalAction,
+    // user-assigned widget names, by position, for IPC addressing
+    names: Vec<Option<String>>,
+    // user-assigned widget groups, by position, for group show/hide
+    groups: Vec<Option<String>>,
+    // groups currently hidden over IPC
+    hidden_groups: Vec<String>,
+    // hook id of each widget, by position; hook senders keep the id
+    // they were created with while insertions and removals shift positions
+    hook_ids: Vec<WidgetIndex>,
+    next_hook_id: WidgetIndex,
+    // clone of the hook channel, to hook widgets inserted at runtime
+    widget_channel: Option<Sender<HookEvent>>,
+    ipc_commands: Option<Receiver<IpcCommand>>,
+    registry: WidgetRegistry,
+    day_night: Option<DayNightThemes>,
+    // which palette is active, None before the scheduler first fires
+    day_active: Option<bool>,
+}
+
+type ThemeLoader = Box<dyn Fn() -> Option<Theme> + Send>;
+type DrawHook = Box<dyn Fn(&Context, &[Rectangle]) + Send>;
+type LayoutHook = Box<dyn Fn(&[Rectangle]) + Send>;
+
+/// What happens when the static widgets alone are wider than the bar
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// flex widgets shrink to nothing and the static widgets
+    /// overflow past the right edge
+    #[default]
+    Shrink,
+    /// the lowest [priority](crate::widgets::Widget::priority) widgets
+    /// give up their region until the remaining ones fit; they keep
+    /// running and come back as soon as there is space again
+    Hide,
+}
+
+/// A day and a night [Theme] switched at sunrise and sunset, see
+/// [day_night_themes](StatusBarBuilder::day_night_themes)
+#[derive(Debug)]
+pub struct DayNightThemes {
+    pub day: Theme,
+    pub night: Theme,
+    /// coordinates fed to the sunrise equation, the ones the
+    /// Weather widget geolocates work well
+    pub latitude: f64,
+    /// positive going east
+    pub longitude: f64,
+}
+
+/// What a user signal triggers, see
+/// [on_sigusr1](StatusBarBuilder::on_sigusr1)
+pub enum SignalAction {
+    /// update every widget and repaint the bar
+    RefreshAll,
+    /// reload the theme through the configured loader
+    ReloadTheme,
+    /// cycle to the next page
+    NextPage,
+    /// map/unmap the bar window
+    ToggleVisibility,
+    /// arbitrary user callback
+    Callback(Box<dyn FnMut() + Send>),
+}
+
+/// What a hotkey registered with
+/// [hotkey](StatusBarBuilder::hotkey) triggers
+pub enum HotkeyAction {
+    /// cycle to the next carousel page
+    NextPage,
+    /// map/unmap the bar window
+    ToggleVisibility,
+    /// arbitrary user callback
+    Callback(Box<dyn FnMut() + Send>),
+}
+
+/// Resolves an X keysym to a keycode via the core keyboard mapping
+fn keysym_to_keycode(connection: &Connection, keysym: u32) -> Result<Option<u8>> {
+    let setup = connection.get_setup();
+    let min = setup.min_keycode();
+    let max = setup.max_keycode();
+    let cookie = connection.send_request(&xcb::x::GetKeyboardMapping {
+        first_keycode: min,
+        count: max - min + 1,
+    });
+    let reply = connection.wait_for_reply(cookie)?;
+    let per_keycode = reply.keysyms_per_keycode() as usize;
+    for (i, keysyms) in reply.keysyms().chunks(per_keycode).enumerate() {
+        if keysyms.contains(&keysym) {
+            return Ok(Some(min + i as u8));
+        }
+    }
+    Ok(None)
+}
+
+/// Outer gaps between the bar window and the screen edges
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Margins {
+    pub left: u16,
+    pub right: u16,
+    pub top: u16,
+    pub bottom: u16,
+}
+
+/// Stacking order requested through `_NET_WM_STATE`
+#[derive(Clone, Copy, Debug)]
+pub enum StackLayer {
+    Above,
+    Below,
 }
 
 impl StatusBar {
@@ -47,18 +226,12 @@ impl StatusBar {
     /// Starts the [StatusBar] drawing and event loop
     pub async fn start(mut self) -> Result<()> {
         debug!("Starting loop");
-        let (tx, widgets_events) = bounded::<WidgetIndex>(10);
+        let (tx, widgets_events) = bounded::<HookEvent>(10);
 
         debug!("Widget setup");
-        let info = StatusBarInfo {
-            background: self.background,
-            regions: self.regions.clone(),
-            height: self.height,
-            width: self.width,
-            position: self.position,
-            window: self.window,
-        };
+        let info = self.info();
         let mut pool = TimedHooks::default();
+        self.widget_channel = Some(tx.clone());
 
         let setup_futures = self
             .widgets
@@ -67,10 +240,20 @@ impl StatusBar {
             .collect::<Vec<_>>();
         join_all(setup_futures).await;
 
+        let state = PersistentState::load();
+        for (index, wd) in self.widgets.iter_mut().enumerate() {
+            let key = format!("{index}:{wd}");
+            if let Some(value) = state.get(&key) {
+                wd.load_state(value);
+            }
+        }
+
         for (index, wd) in self.widgets.iter_mut().enumerate() {
             wd.hook_or_replace(HookSender::new(tx.clone(), index), &mut pool)
                 .await;
         }
+        self.hook_ids = (0..self.widgets.len()).collect();
+        self.next_hook_id = self.widgets.len();
 
         let update_futures = self
             .widgets
@@ -80,7 +263,48 @@ impl StatusBar {
         join_all(update_futures).await;
 
         let signal = stop_on_signal()?;
+        let sigusr1_events = user_signal(SignalKind::user_defined1(), "SIGUSR1")?;
+        let sigusr2_events = user_signal(SignalKind::user_defined2(), "SIGUSR2")?;
         let bar_events = bar_event_listener(Arc::clone(&self.connection))?;
+        // a dummy channel when no socket was requested, so the
+        // select arm never fires instead of spinning on a closed one
+        let (_ipc_keepalive, ipc_commands) = match self.ipc_commands.take() {
+            Some(receiver) => (None, receiver),
+            None => {
+                let (sender, receiver) = bounded(1);
+                (Some(sender), receiver)
+            }
+        };
+        // refresh everything on resume, widgets would otherwise show
+        // stale data until their next interval
+        #[cfg(feature = "logind")]
+        let resume_events = crate::utils::logind::resume_listener();
+        #[cfg(not(feature = "logind"))]
+        let (_resume_keepalive, resume_events) = bounded::<()>(1);
+        let (_sun_keepalive, sun_events) = match &self.day_night {
+            Some(themes) => {
+                let (sender, receiver) = bounded::<bool>(1);
+                let (latitude, longitude) = (themes.latitude, themes.longitude);
+                spawn(async move {
+                    loop {
+                        let now = sun::unix_now();
+                        let times = sun::sun_times(latitude, longitude, now);
+                        if sender.send(times.is_day(now)).await.is_err() {
+                            break;
+                        }
+                        // wake a minute past the transition so the new
+                        // period is unambiguous
+                        let pause = times.next_transition(now) + 60.0;
+                        sleep(Duration::from_secs_f64(pause)).await;
+                    }
+                });
+                (None, receiver)
+            }
+            None => {
+                let (sender, receiver) = bounded(1);
+                (Some(sender), receiver)
+            }
+        };
 
         self.generate_regions().await?;
         self.show()?;
@@ -93,42 +317,495 @@ impl StatusBar {
         self.connection.flush()?;
 
         loop {
-            let mut to_update: Option<WidgetIndex> = None;
+            let mut to_update: Vec<WidgetIndex> = Vec::new();
+            let mut force_layout = false;
 
             select!(
-                id = widgets_events.recv() => {
-                    to_update = id.ok();
+                event = widgets_events.recv() => {
+                    if let Ok(event) = event {
+                        if let Some(index) = self.widget_position(event.index) {
+                            self.handle_hook_kind(&event, &mut force_layout);
+                            to_update.push(index);
+                        }
+                    }
+                }
+                command = ipc_commands.recv() => {
+                    if let Ok(command) = command {
+                        self.handle_ipc(command, &mut to_update, &mut force_layout).await;
+                    }
+                }
+                event = bar_events.recv() => {
+                    match event {
+                        Ok(BarEvent::Click(x, button, modifiers)) => {
+                            to_update.extend(self.click(x, button, modifiers).await);
+                        }
+                        Ok(BarEvent::KeyPress(keycode, state)) => {
+                            if self.handle_hotkey(keycode, state)? {
+                                force_layout = true;
+                            }
+                        }
+                        Ok(BarEvent::ScreenChanged) => {
+                            self.handle_screen_change()?;
+                            self.generate_regions().await?;
+                            self.draw_all().await?;
+                        }
+                        _ => {}
+                    }
+                }
+                _ = resume_events.recv() => {
+                    debug!("refreshing every widget after resume");
+                    to_update.extend(0..self.widgets.len());
+                    force_layout = true;
+                }
+                is_day = sun_events.recv() => {
+                    if let Ok(is_day) = is_day {
+                        if self.apply_day_night(is_day) {
+                            // repaint everything under the new palette
+                            to_update.extend(0..self.widgets.len());
+                            force_layout = true;
+                        }
+                    }
+                }
+                _ = sigusr1_events.recv() => {
+                    self.handle_user_signal(1, &mut to_update, &mut force_layout)?;
+                }
+                _ = sigusr2_events.recv() => {
+                    self.handle_user_signal(2, &mut to_update, &mut force_layout)?;
                 }
-                _ = bar_events.recv() => {/* just redraw? */ }
                 _ = signal.recv() => {
                     // shutdown
+                    self.teardown().await;
                     return Ok(())
                 },
             );
 
-            if let Some(to_update) = to_update {
-                self.update(to_update).await?;
+            if to_update.is_empty() && !force_layout {
+                // nothing to draw, go back to sleep
+                continue;
             }
 
-            let need_relayout = self.generate_regions().await?;
+            // coalesce updates landing within one frame into a single redraw
+            if !to_update.is_empty() {
+                let budget = if power::low_power() {
+                    LOW_POWER_FRAME_BUDGET
+                } else {
+                    FRAME_BUDGET
+                };
+                sleep(budget).await;
+                while let Ok(event) = widgets_events.try_recv() {
+                    if let Some(index) = self.widget_position(event.index) {
+                        self.handle_hook_kind(&event, &mut force_layout);
+                        to_update.push(index);
+                    }
+                }
+                to_update.sort_unstable();
+                to_update.dedup();
+            }
+
+            for index in &to_update {
+                self.update(*index).await?;
+            }
+
+            let need_relayout = self.generate_regions().await? || force_layout;
             if need_relayout {
                 self.draw_all().await?;
-            } else if let Some(to_update) = to_update {
-                self.targeted_draw(to_update).await?;
+            } else {
+                for index in to_update {
+                    self.targeted_draw(index).await?;
+                }
             }
         }
     }
 
+    /// The widget indices of the currently displayed page
+    fn active_range(&self) -> std::ops::Range<usize> {
+        self.pages[self.active_page].1.clone()
+    }
+
+    fn info(&self) -> StatusBarInfo {
+        StatusBarInfo {
+            background: self.background.clone(),
+            regions: self.regions.clone(),
+            height: self.height,
+            width: self.width,
+            position: self.position,
+            window: self.window,
+            theme: self.theme.clone(),
+            connection: Arc::clone(&self.connection),
+            screen_id: self.screen_id,
+            screen_width: u32::from(screen_true_width(&self.connection, self.screen_id)),
+            screen_height: u32::from(screen_true_height(&self.connection, self.screen_id)),
+            dpi: screen_dpi(&self.connection, self.screen_id),
+        }
+    }
+
+    /// The current position of the widget hooked with `id`
+    fn widget_position(&self, id: WidgetIndex) -> Option<usize> {
+        self.hook_ids.iter().position(|i| *i == id)
+    }
+
+    /// Adds a widget while the bar is running
+    ///
+    /// The widget is set up, hooked and updated before it joins the
+    /// bar, `index` is clamped to the end
+    pub async fn insert_widget(&mut self, index: usize, mut widget: Box<dyn Widget>) -> Result<()> {
+        let index = index.min(self.widgets.len());
+        let info = self.info();
+        widget.setup(&info).await?;
+        let id = self.next_hook_id;
+        self.next_hook_id += 1;
+        if let Some(tx) = &self.widget_channel {
+            // the shared pool is already running, the new widget gets its own
+            let mut pool = TimedHooks::default();
+            widget
+                .hook(HookSender::new(tx.clone(), id), &mut pool)
+                .await?;
+            pool.start().await;
+        }
+        widget.update().await?;
+        self.widgets.insert(
+            index,
+            ReplaceableWidget::new(widget).with_on_error(self.on_error),
+        );
+        self.regions.insert(index, Rectangle::default());
+        self.hook_ids.insert(index, id);
+        self.names.insert(index, None);
+        self.groups.insert(index, None);
+        let mut placed = false;
+        for (_, range) in &mut self.pages {
+            if placed {
+                range.start += 1;
+                range.end += 1;
+            } else if range.contains(&index) || range.end == index {
+                range.end += 1;
+                placed = true;
+            }
+        }
+        Ok(())
+    }
+
+    /// Tears down and drops the widget at `index` while the bar is running
+    pub async fn remove_widget(&mut self, index: usize) -> Result<()> {
+        if index >= self.widgets.len() {
+            return Ok(());
+        }
+        let mut widget = self.widgets.remove(index);
+        widget.teardown_or_log().await;
+        self.regions.remove(index);
+        self.hook_ids.remove(index);
+        self.names.remove(index);
+        self.groups.remove(index);
+        for (_, range) in &mut self.pages {
+            if range.contains(&index) {
+                range.end -= 1;
+            } else if range.start > index {
+                range.start -= 1;
+                range.end -= 1;
+            }
+        }
+        Ok(())
+    }
+
+    async fn handle_ipc(
+        &mut self,
+        command: IpcCommand,
+        to_update: &mut Vec<WidgetIndex>,
+        force_layout: &mut bool,
+    ) {
+        match command {
+            IpcCommand::InsertWidget {
+                index,
+                widget,
+                options,
+            } => {
+                match self
+                    .registry
+                    .build(&widget, options, &WidgetConfig::default())
+                    .await
+                {
+                    Ok(wd) => {
+                        if let Err(e) = self.insert_widget(index, wd).await {
+                            warn!("failed to insert widget {widget}: {e}");
+                        }
+                        *force_layout = true;
+                    }
+                    Err(e) => warn!("failed to build widget {widget}: {e}"),
+                }
+            }
+            IpcCommand::RemoveWidget { index } => {
+                if let Err(e) = self.remove_widget(index).await {
+                    warn!("failed to remove widget {index}: {e}");
+                }
+                *force_layout = true;
+            }
+            IpcCommand::UpdateWidget { name } => {
+                let position = self.names.iter().position(|n| n.as_deref() == Some(&*name));
+                match position {
+                    Some(index) => to_update.push(index),
+                    None => warn!("no widget named {name}"),
+                }
+            }
+            IpcCommand::HideGroup { group } => {
+                if !self.groups.iter().flatten().any(|g| *g == group) {
+                    warn!("no widget in group {group}");
+                } else if !self.hidden_groups.contains(&group) {
+                    self.hidden_groups.push(group);
+                    *force_layout = true;
+                }
+            }
+            IpcCommand::ShowGroup { group } => {
+                if let Some(position) = self.hidden_groups.iter().position(|g| *g == group) {
+                    self.hidden_groups.remove(position);
+                    *force_layout = true;
+                }
+            }
+        }
+    }
+
+    /// Decides how much redrawing a hook event requires
+    fn handle_hook_kind(&self, event: &HookEvent, force_layout: &mut bool) {
+        match &event.kind {
+            HookKind::DataChanged => {}
+            // urgent events skip the targeted draw so accents repaint
+            HookKind::SizeChanged | HookKind::UrgentChange => *force_layout = true,
+            HookKind::Custom(payload) => {
+                debug!("custom hook event from widget {}: {payload}", event.index);
+            }
+        }
+    }
+
+    /// Resizes and repositions the bar after a RandR screen change
+    fn handle_screen_change(&mut self) -> Result<()> {
+        if self.auto_width {
+            self.width = u32::from(
+                screen_true_width(&self.connection, self.screen_id)
+                    - self.margins.left
+                    - self.margins.right,
+            );
+        }
+        let y = match self.position {
+            Position::Top => u32::from(self.yoff + self.margins.top),
+            Position::Bottom => {
+                u32::from(screen_true_height(&self.connection, self.screen_id))
+                    - self.height
+                    - u32::from(self.margins.bottom)
+            }
+        };
+        self.connection
+            .send_and_check_request(&ConfigureWindow {
+                window: self.window,
+                value_list: &[
+                    ConfigWindow::X(i32::from(self.xoff + self.margins.left)),
+                    ConfigWindow::Y(y as i32),
+                    ConfigWindow::Width(self.width),
+                ],
+            })?;
+        self.surface.set_size(self.width as i32, self.height as i32)?;
+
+        let atoms = Atoms::new(&self.connection)?;
+        let strut_data = [0, 0, self.height, 0, 0, 0, 0, 0, 0, self.width, 0, 0];
+        self.connection
+            .send_and_check_request(&xcb::x::ChangeProperty {
+                mode: xcb::x::PropMode::Replace,
+                window: self.window,
+                property: atoms._NET_WM_STRUT,
+                r#type: xcb::x::ATOM_CARDINAL,
+                data: &strut_data[0..4],
+            })?;
+        self.connection
+            .send_and_check_request(&xcb::x::ChangeProperty {
+                mode: xcb::x::PropMode::Replace,
+                window: self.window,
+                property: atoms._NET_WM_STRUT_PARTIAL,
+                r#type: xcb::x::ATOM_CARDINAL,
+                data: &strut_data,
+            })?;
+        self.connection.flush()?;
+        debug!("resized bar to {}x{}", self.width, self.height);
+        Ok(())
+    }
+
+    /// Runs the action bound to a grabbed key press,
+    /// true when a redraw is needed
+    fn handle_hotkey(&mut self, keycode: u8, state: xcb::x::KeyButMask) -> Result<bool> {
+        let mut hotkeys = std::mem::take(&mut self.hotkeys);
+        let mut redraw = false;
+        for (modifiers, key, action) in &mut hotkeys {
+            let modifiers = xcb::x::KeyButMask::from_bits_truncate(modifiers.bits());
+            if *key != keycode || !state.contains(modifiers) {
+                continue;
+            }
+            match action {
+                HotkeyAction::NextPage => {
+                    self.next_page();
+                    redraw = true;
+                }
+                HotkeyAction::ToggleVisibility => {
+                    redraw = self.toggle_visibility()?;
+                }
+                HotkeyAction::Callback(callback) => callback(),
+            }
+        }
+        self.hotkeys = hotkeys;
+        Ok(redraw)
+    }
+
+    /// Maps or unmaps the bar window
+    /// returns true when the bar became visible
+    fn toggle_visibility(&mut self) -> Result<bool> {
+        if self.hidden {
+            self.connection.send_and_check_request(&MapWindow {
+                window: self.window,
+            })?;
+        } else {
+            self.connection.send_and_check_request(&UnmapWindow {
+                window: self.window,
+            })?;
+        }
+        self.hidden = !self.hidden;
+        Ok(!self.hidden)
+    }
+
+    /// Runs the action configured for SIGUSR`number`
+    fn handle_user_signal(
+        &mut self,
+        number: u8,
+        to_update: &mut Vec<WidgetIndex>,
+        force_layout: &mut bool,
+    ) -> Result<()> {
+        // take the action out so a callback can borrow the bar
+        let field = if number == 1 {
+            &mut self.sigusr1
+        } else {
+            &mut self.sigusr2
+        };
+        let mut action = std::mem::replace(field, SignalAction::RefreshAll);
+        match &mut action {
+            SignalAction::RefreshAll => {
+                to_update.extend(0..self.widgets.len());
+                *force_layout = true;
+            }
+            SignalAction::ReloadTheme => {
+                if self.reload_theme() {
+                    *force_layout = true;
+                }
+            }
+            SignalAction::NextPage => {
+                self.next_page();
+                *force_layout = true;
+            }
+            SignalAction::ToggleVisibility => {
+                if self.toggle_visibility()? {
+                    *force_layout = true;
+                }
+            }
+            SignalAction::Callback(callback) => callback(),
+        }
+        *(if number == 1 {
+            &mut self.sigusr1
+        } else {
+            &mut self.sigusr2
+        }) = action;
+        Ok(())
+    }
+
+    /// Displays the next page, wrapping around
+    fn next_page(&mut self) {
+        self.active_page = (self.active_page + 1) % self.pages.len();
+        debug!("switched to page `{}`", self.pages[self.active_page].0);
+    }
+
+    /// Applies the day or night palette, true when it changed
+    fn apply_day_night(&mut self, is_day: bool) -> bool {
+        if self.day_active == Some(is_day) {
+            return false;
+        }
+        let Some(themes) = &self.day_night else {
+            return false;
+        };
+        debug!(
+            "switching to the {} theme",
+            if is_day { "day" } else { "night" }
+        );
+        let theme = if is_day {
+            themes.day.clone()
+        } else {
+            themes.night.clone()
+        };
+        self.day_active = Some(is_day);
+        self.background = theme.background.into();
+        self.theme = theme;
+        true
+    }
+
+    /// Reloads the theme from the configured loader
+    /// returns true if the theme changed
+    fn reload_theme(&mut self) -> bool {
+        let Some(loader) = &self.theme_loader else {
+            return false;
+        };
+        let Some(theme) = loader() else {
+            warn!("theme loader failed, keeping the current theme");
+            return false;
+        };
+        self.background = theme.background.into();
+        self.theme = theme;
+        true
+    }
+
+    /// Gives every widget a chance to clean up before the bar exits
+    async fn teardown(&mut self) {
+        debug!("Widget teardown");
+        let mut state = PersistentState::load();
+        for (index, wd) in self.widgets.iter().enumerate() {
+            if let Some(value) = wd.save_state() {
+                state.set(format!("{index}:{wd}"), value);
+            }
+        }
+        if let Err(e) = state.save() {
+            error!("failed to save widget states: {e}");
+        }
+        let teardown_futures = self
+            .widgets
+            .iter_mut()
+            .map(|w| w.teardown_or_log())
+            .collect::<Vec<_>>();
+        join_all(teardown_futures).await;
+        let _ = self.connection.flush();
+    }
+
     async fn update(&mut self, index: WidgetIndex) -> Result<()> {
         let wd = &mut self.widgets[index];
         wd.update_or_replace().await;
         Ok(())
     }
 
+    /// Dispatches a mouse event to the widget under the pointer
+    /// returns the widget index so it can be updated and redrawn
+    async fn click(
+        &mut self,
+        x: i16,
+        button: MouseButton,
+        modifiers: ClickModifiers,
+    ) -> Option<WidgetIndex> {
+        let x = x.max(0) as u32;
+        let index = self
+            .active_range()
+            .find(|i| (self.regions[*i].x..self.regions[*i].x + self.regions[*i].width).contains(&x))?;
+        let event = ClickEvent {
+            button,
+            modifiers,
+            x: x - self.regions[index].x,
+        };
+        self.widgets[index].on_click_or_replace(event).await;
+        Some(index)
+    }
+
     /// Regenerate the regions for the widgets
     /// return true if the regions have changed
     async fn generate_regions(&mut self) -> Result<bool> {
         let context = Context::new(&self.surface)?;
+        let range = self.active_range();
         let mut rectangle = Rectangle {
             x: 0,
             y: 0,
@@ -136,57 +813,149 @@ impl StatusBar {
             height: self.height,
         };
 
-        let static_size: u32 = self
-            .widgets
-            .iter_mut()
-            .map(|wd| {
-                if let Ok(Size::Static(width)) = wd.size(&context) {
-                    width + 2 * wd.padding()
-                } else {
-                    2 * wd.padding()
-                }
-            })
-            .sum();
-
-        let flex_widgets = self
-            .widgets
-            .iter_mut()
-            .flat_map(|wd| wd.size(&context))
-            .filter(|wd| wd.is_flex())
-            .count();
+        let mut sizes = Vec::with_capacity(range.len());
+        for wd in self.widgets[range.clone()].iter_mut() {
+            sizes.push(wd.size_or_replace(&context).await);
+        }
+        let paddings: Vec<u32> = self.widgets[range.clone()]
+            .iter()
+            .map(|wd| wd.padding())
+            .collect();
+        let priorities: Vec<u32> = self.widgets[range.clone()]
+            .iter()
+            .map(|wd| wd.priority())
+            .collect();
 
-        let flex_size = (self.width - static_size)
-            .checked_div(flex_widgets as u32)
+        // widgets in a hidden group or hidden by the overflow
+        // policy take no space at all
+        let mut hidden: Vec<bool> = range
+            .clone()
+            .map(|index| {
+                self.groups[index]
+                    .as_ref()
+                    .is_some_and(|group| self.hidden_groups.contains(group))
+            })
+            .collect();
+        let (static_size, total_spacing) = loop {
+            let static_size: u32 = sizes
+                .iter()
+                .zip(&paddings)
+                .zip(&hidden)
+                .map(|((size, padding), hidden)| match size {
+                    _ if *hidden => 0,
+                    Size::Static(width) => width + 2 * padding,
+                    _ => 2 * padding,
+                })
+                .sum();
+            let visible = hidden.iter().filter(|hidden| !**hidden).count();
+            let total_spacing = self.spacing * visible.saturating_sub(1) as u32;
+            if static_size + total_spacing <= self.width
+                || !matches!(self.overflow, OverflowPolicy::Hide)
+            {
+                break (static_size, total_spacing);
+            }
+            // drop the lowest priority widget still visible and retry
+            let lowest = (0..sizes.len())
+                .filter(|index| !hidden[*index])
+                .min_by_key(|index| priorities[*index]);
+            let Some(lowest) = lowest else {
+                break (static_size, total_spacing);
+            };
+            hidden[lowest] = true;
+        };
+        let available = self.width.saturating_sub(static_size + total_spacing);
+
+        // percentage widgets take their share of the leftover first,
+        // the rest is split between the flex widgets by weight
+        let mut weight_total: u32 = 0;
+        let mut percent_size: u32 = 0;
+        for (size, hidden) in sizes.iter().zip(&hidden) {
+            if *hidden {
+                continue;
+            }
+            match size {
+                Size::Flex => weight_total += 1,
+                Size::Weighted { weight, .. } => weight_total += weight,
+                Size::Percent(percent) => {
+                    percent_size += (f64::from(available) * percent.clamp(0.0, 1.0)) as u32;
+                }
+                Size::Static(_) => {}
+            }
+        }
+        let flex_space = available.saturating_sub(percent_size);
+        let flex_unit = flex_space
+            .checked_div(weight_total)
             // if there are no flex widgets, use the full width
-            .unwrap_or(self.width - static_size);
+            .unwrap_or(flex_space);
 
         let mut need_relayout = false;
+        let spacing = self.spacing;
 
-        let left = self.widgets.iter_mut().zip(self.regions.iter_mut());
+        // widgets on other pages keep running but take no space
+        for (index, region) in self.regions.iter_mut().enumerate() {
+            if !range.contains(&index) && *region != Rectangle::default() {
+                need_relayout = true;
+                *region = Rectangle::default();
+            }
+        }
 
-        for (wd, region) in left {
-            rectangle.x += wd.padding();
-            let widget_width = wd.size_or_replace(&context).await.unwrap_or(flex_size);
+        let left = sizes.iter().zip(self.regions[range].iter_mut());
+
+        let mut first = true;
+        for (index, (size, region)) in left.enumerate() {
+            if hidden[index] {
+                if *region != Rectangle::default() {
+                    need_relayout = true;
+                    *region = Rectangle::default();
+                }
+                continue;
+            }
+            let padding = paddings[index];
+            if !first {
+                rectangle.x += spacing;
+            }
+            first = false;
+            rectangle.x += padding;
+            let widget_width = match *size {
+                Size::Static(width) => width,
+                Size::Flex => flex_unit,
+                // the clamps win over the weighted share, the bar may
+                // under or overfill when they kick in
+                Size::Weighted { weight, min, max } => {
+                    let width = flex_unit.saturating_mul(weight);
+                    let width = min.map_or(width, |min| width.max(min));
+                    max.map_or(width, |max| width.min(max))
+                }
+                Size::Percent(percent) => (f64::from(available) * percent.clamp(0.0, 1.0)) as u32,
+            };
             rectangle.width = widget_width;
             if !need_relayout && *region != rectangle {
                 need_relayout = true;
             }
             *region = rectangle;
-            rectangle.x += widget_width + wd.padding();
+            rectangle.x += widget_width + padding;
+        }
+
+        if need_relayout {
+            if let Some(hook) = &self.on_after_layout {
+                hook(&self.regions);
+            }
         }
 
         Ok(need_relayout)
     }
 
     async fn draw_all(&mut self) -> Result<()> {
+        let started = Instant::now();
         assert!(
             self.regions.len() == self.widgets.len(),
             "Regions and widgets length mismatch"
         );
 
-        let widgets = self.widgets.iter_mut();
+        let range = self.active_range();
+        let widgets = self.widgets[range.clone()].iter_mut();
 
-        let regions: Vec<&Rectangle> = self.regions.iter().collect();
+        let regions: Vec<&Rectangle> = self.regions[range].iter().collect();
 
         let context = Context::new(&self.surface)?;
         // clear surface
@@ -194,8 +963,12 @@ impl StatusBar {
         context.paint()?;
         // paint background
         context.set_operator(Operator::Over);
-        set_source_rgba(&context, self.background);
-        context.paint()?;
+        self.background
+            .apply(&context, self.width as f64, self.height as f64)?;
+
+        if let Some(hook) = &self.on_before_draw {
+            hook(&context, &self.regions);
+        }
 
         for (wd, rectangle) in widgets.zip(regions) {
             let cairo_rectangle: cairo::Rectangle = (*rectangle).into();
@@ -206,10 +979,21 @@ impl StatusBar {
 
         self.surface.flush();
         self.connection.flush()?;
+        self.frame_times.record(started.elapsed());
         Ok(())
     }
 
     async fn targeted_draw(&mut self, index: WidgetIndex) -> Result<()> {
+        if !self.active_range().contains(&index) {
+            // the widget is on another page
+            return Ok(());
+        }
+        if !matches!(self.background, Background::Flat(_)) || self.on_before_draw.is_some() {
+            // a region-local repaint would misalign gradients,
+            // images and user drawn decorations
+            return self.draw_all().await;
+        }
+
         let wd = &mut self.widgets[index];
         let region = self.regions[index];
 
@@ -220,8 +1004,8 @@ impl StatusBar {
         context.set_operator(Operator::Clear);
         context.paint()?;
         context.set_operator(Operator::Over);
-        set_source_rgba(&context, self.background);
-        context.paint()?;
+        self.background
+            .apply(&context, region.width as f64, region.height as f64)?;
 
         wd.draw_or_replace(context, &region).await;
 
@@ -245,8 +1029,34 @@ pub struct StatusBarBuilder {
     width: Option<u16>,
     height: u16,
     position: Position,
-    background: Color,
+    background: Background,
     widgets: Vec<Box<dyn Widget>>,
+    widget_names: Vec<Option<String>>,
+    widget_groups: Vec<Option<String>>,
+    pages: Vec<(String, Vec<Box<dyn Widget>>)>,
+    theme: Theme,
+    theme_loader: Option<ThemeLoader>,
+    opacity: f64,
+    blur: bool,
+    click_through: bool,
+    override_redirect: bool,
+    sticky: bool,
+    stack_layer: Option<StackLayer>,
+    window_class: String,
+    window_title: String,
+    spacing: u32,
+    margins: Margins,
+    on_before_draw: Option<DrawHook>,
+    on_after_layout: Option<LayoutHook>,
+    metrics_address: Option<String>,
+    hotkeys: Vec<(ModMask, u32, HotkeyAction)>,
+    ipc_path: Option<PathBuf>,
+    registry: WidgetRegistry,
+    overflow: OverflowPolicy,
+    on_error: OnError,
+    sigusr1: SignalAction,
+    sigusr2: SignalAction,
+    day_night: Option<DayNightThemes>,
 }
 
 impl Default for StatusBarBuilder {
@@ -257,8 +1067,34 @@ impl Default for StatusBarBuilder {
             width: None,
             height: 21,
             position: Position::Top,
-            background: Color::new(0.0, 0.0, 0.0, 1.0),
+            background: Background::Flat(Color::new(0.0, 0.0, 0.0, 1.0)),
             widgets: Vec::new(),
+            widget_names: Vec::new(),
+            widget_groups: Vec::new(),
+            pages: Vec::new(),
+            theme: Theme::default(),
+            theme_loader: None,
+            opacity: 1.0,
+            blur: false,
+            click_through: false,
+            override_redirect: false,
+            sticky: false,
+            stack_layer: None,
+            window_class: String::from("barust"),
+            window_title: String::from("barust"),
+            spacing: 0,
+            margins: Margins::default(),
+            on_before_draw: None,
+            on_after_layout: None,
+            metrics_address: None,
+            hotkeys: Vec::new(),
+            ipc_path: None,
+            registry: WidgetRegistry::with_builtins(),
+            overflow: OverflowPolicy::default(),
+            on_error: OnError::default(),
+            sigusr1: SignalAction::ReloadTheme,
+            sigusr2: SignalAction::NextPage,
+            day_night: None,
         }
     }
 }
@@ -294,15 +1130,191 @@ impl StatusBarBuilder {
         self
     }
 
-    ///Set the `StatusBar` background color
-    pub fn background(mut self, background: Color) -> Self {
-        self.background = background;
+    ///Set the `StatusBar` background (flat color, gradient or image)
+    pub fn background(mut self, background: impl Into<Background>) -> Self {
+        self.background = background.into();
+        self
+    }
+
+    ///Set the `StatusBar` [Theme] (also sets the background color)
+    pub fn theme(mut self, theme: Theme) -> Self {
+        self.background = theme.background.into();
+        self.theme = theme;
+        self
+    }
+
+    ///Set a loader (e.g. [Theme::from_pywal]) used to read the [Theme]
+    ///at startup and every time the bar receives SIGUSR1
+    pub fn theme_loader(mut self, loader: impl Fn() -> Option<Theme> + Send + 'static) -> Self {
+        if let Some(theme) = loader() {
+            self = self.theme(theme);
+        }
+        self.theme_loader = Some(Box::new(loader));
+        self
+    }
+
+    ///Switch between a day and a night [Theme] at sunrise and sunset,
+    ///computed from the coordinates with the sunrise equation
+    ///(see [sun](crate::utils::sun)); the whole bar is repainted and
+    ///every widget refreshed when the palette flips
+    pub fn day_night_themes(mut self, themes: DayNightThemes) -> Self {
+        self.day_night = Some(themes);
+        self
+    }
+
+    ///Set the whole bar opacity (from 0 to 1) via _NET_WM_WINDOW_OPACITY
+    ///so compositors fade the bar and everything drawn on it
+    pub fn opacity(mut self, opacity: f64) -> Self {
+        self.opacity = opacity.clamp(0.0, 1.0);
+        self
+    }
+
+    ///Ask the compositor to blur what is behind the bar
+    ///(KWin and picom honor _KDE_NET_WM_BLUR_BEHIND_REGION)
+    pub fn blur(mut self, blur: bool) -> Self {
+        self.blur = blur;
+        self
+    }
+
+    ///Let every click pass through the bar to the desktop below
+    ///by clearing its XShape input region
+    ///
+    ///Widget click handlers will never fire on a click-through bar
+    pub fn click_through(mut self, click_through: bool) -> Self {
+        self.click_through = click_through;
+        self
+    }
+
+    ///Bypass the window manager entirely (override-redirect)
+    ///
+    ///The bar keeps its position but loses struts and dock handling
+    pub fn override_redirect(mut self, override_redirect: bool) -> Self {
+        self.override_redirect = override_redirect;
+        self
+    }
+
+    ///Ask the window manager to show the bar on every workspace
+    pub fn sticky(mut self, sticky: bool) -> Self {
+        self.sticky = sticky;
+        self
+    }
+
+    ///Ask the window manager to keep the bar above or below other windows
+    pub fn stack_layer(mut self, layer: StackLayer) -> Self {
+        self.stack_layer = Some(layer);
+        self
+    }
+
+    ///Set the WM_CLASS used by window manager and compositor rules
+    pub fn window_class(mut self, class: impl ToString) -> Self {
+        self.window_class = class.to_string();
+        self
+    }
+
+    ///Set the window title (WM_NAME and _NET_WM_NAME)
+    pub fn window_title(mut self, title: impl ToString) -> Self {
+        self.window_title = title.to_string();
+        self
+    }
+
+    ///Space inserted between adjacent widget regions
+    pub fn spacing(mut self, spacing: u32) -> Self {
+        self.spacing = spacing;
+        self
+    }
+
+    ///Gap between the bar and every screen edge, floating the bar
+    pub fn margin(mut self, margin: u16) -> Self {
+        self.margins = Margins {
+            left: margin,
+            right: margin,
+            top: margin,
+            bottom: margin,
+        };
+        self
+    }
+
+    ///Per-side version of [margin](StatusBarBuilder::margin)
+    pub fn margins(mut self, margins: Margins) -> Self {
+        self.margins = margins;
+        self
+    }
+
+    ///Run a callback on the bar context after the background is painted
+    ///and before any widget draws, to paint custom decorations
+    ///(e.g. separators between the widget regions)
+    pub fn on_before_draw(
+        mut self,
+        hook: impl Fn(&Context, &[Rectangle]) + Send + 'static,
+    ) -> Self {
+        self.on_before_draw = Some(Box::new(hook));
+        self
+    }
+
+    ///Run a callback every time the widget regions change
+    pub fn on_after_layout(mut self, hook: impl Fn(&[Rectangle]) + Send + 'static) -> Self {
+        self.on_after_layout = Some(Box::new(hook));
+        self
+    }
+
+    ///Serve the widget metrics in Prometheus text format
+    ///on the given address (e.g. "127.0.0.1:9631")
+    pub fn metrics_endpoint(mut self, address: impl ToString) -> Self {
+        self.metrics_address = Some(address.to_string());
+        self
+    }
+
+    ///Accept json-line commands on a unix socket at `path`,
+    ///e.g. `{"command": "insert_widget", "index": 0, "widget": "clock", "options": {}}`
+    ///or `{"command": "remove_widget", "index": 0}`
+    pub fn ipc_socket(mut self, path: impl Into<PathBuf>) -> Self {
+        self.ipc_path = Some(path.into());
+        self
+    }
+
+    ///Replace the [WidgetRegistry] used to build widgets requested over IPC
+    pub fn widget_registry(mut self, registry: WidgetRegistry) -> Self {
+        self.registry = registry;
+        self
+    }
+
+    ///Set what happens when the widgets are wider than the bar
+    pub fn overflow(mut self, policy: OverflowPolicy) -> Self {
+        self.overflow = policy;
+        self
+    }
+
+    ///Set how a failing widget is handled, by default it is
+    ///replaced with a static crash message
+    pub fn on_widget_error(mut self, on_error: OnError) -> Self {
+        self.on_error = on_error;
+        self
+    }
+
+    ///Set what SIGUSR1 triggers, a theme reload by default
+    pub fn on_sigusr1(mut self, action: SignalAction) -> Self {
+        self.sigusr1 = action;
+        self
+    }
+
+    ///Set what SIGUSR2 triggers, a page cycle by default
+    pub fn on_sigusr2(mut self, action: SignalAction) -> Self {
+        self.sigusr2 = action;
+        self
+    }
+
+    ///Register a global hotkey, `keysym` is an X keysym
+    ///(e.g. 0x0062 for `b`)
+    pub fn hotkey(mut self, modifiers: ModMask, keysym: u32, action: HotkeyAction) -> Self {
+        self.hotkeys.push((modifiers, keysym, action));
         self
     }
 
     ///Add a widget to the `StatusBar`
     pub fn widget(mut self, widget: Box<dyn Widget>) -> Self {
         self.widgets.push(widget);
+        self.widget_names.push(None);
+        self.widget_groups.push(None);
         self
     }
 
@@ -310,18 +1322,57 @@ impl StatusBarBuilder {
     pub fn widgets(mut self, widgets: Vec<Box<dyn Widget>>) -> Self {
         for wd in widgets {
             self.widgets.push(wd);
+            self.widget_names.push(None);
+            self.widget_groups.push(None);
         }
         self
     }
 
+    ///Add a widget addressable by name, e.g. for
+    ///`barust-ctl update <name>`
+    pub fn named(mut self, name: impl ToString, widget: Box<dyn Widget>) -> Self {
+        self.widgets.push(widget);
+        self.widget_names.push(Some(name.to_string()));
+        self.widget_groups.push(None);
+        self
+    }
+
+    ///Add a widget to a named group, every widget in a group can be
+    ///hidden and shown at once with `barust-ctl hide <group>` and
+    ///`barust-ctl show <group>` (e.g. to redact private info while
+    ///sharing the screen)
+    pub fn grouped(mut self, group: impl ToString, widget: Box<dyn Widget>) -> Self {
+        self.widgets.push(widget);
+        self.widget_names.push(None);
+        self.widget_groups.push(Some(group.to_string()));
+        self
+    }
+
+    ///Add a named page of widgets
+    ///
+    ///All widgets stay alive but only the active page is laid out,
+    ///SIGUSR2 flips to the next page
+    pub fn page(mut self, name: impl ToString, widgets: Vec<Box<dyn Widget>>) -> Self {
+        self.pages.push((name.to_string(), widgets));
+        self
+    }
+
     ///Build the `StatusBar` with the previously selected options
     pub async fn build(self) -> Result<StatusBar> {
-        let (connection, screen_id) = Connection::connect(None)?;
-        let connection = Arc::new(connection);
+        let (connection, screen_id) = shared_connection()?;
+
+        if let Some(address) = &self.metrics_address {
+            metrics::serve(address).await?;
+        }
+
+        let ipc_commands = match &self.ipc_path {
+            Some(path) => Some(ipc::ipc_listener(path)?),
+            None => None,
+        };
 
-        let width = self
-            .width
-            .unwrap_or_else(|| screen_true_width(&connection, screen_id));
+        let width = self.width.unwrap_or_else(|| {
+            screen_true_width(&connection, screen_id) - self.margins.left - self.margins.right
+        });
 
         let window: Window = connection.generate_id();
         let colormap: Colormap = connection.generate_id();
@@ -351,26 +1402,34 @@ impl StatusBarBuilder {
             visual: visual_type.visual_id(),
         })?;
 
+        // Cw values must stay in value-mask order
+        let mut value_list = vec![
+            Cw::BackPixmap(Pixmap::none()),
+            Cw::BorderPixel(screen.black_pixel()),
+        ];
+        if self.override_redirect {
+            value_list.push(Cw::OverrideRedirect(true));
+        }
+        value_list.push(Cw::EventMask(EventMask::all()));
+        value_list.push(Cw::Colormap(colormap));
+
         connection.send_and_check_request(&CreateWindow {
             depth: depth.depth(),
             wid: window,
             parent: screen.root(),
-            x: self.xoff as _,
+            x: (self.xoff + self.margins.left) as _,
             y: match self.position {
-                Position::Top => self.yoff,
-                Position::Bottom => screen_true_height(&connection, screen_id) - self.height,
+                Position::Top => self.yoff + self.margins.top,
+                Position::Bottom => {
+                    screen_true_height(&connection, screen_id) - self.height - self.margins.bottom
+                }
             } as _,
             width,
             height: self.height,
             border_width: 0,
             class: WindowClass::InputOutput,
             visual: visual_type.visual_id(),
-            value_list: &[
-                Cw::BackPixmap(Pixmap::none()),
-                Cw::BorderPixel(screen.black_pixel()),
-                Cw::EventMask(EventMask::all()),
-                Cw::Colormap(colormap),
-            ],
+            value_list: &value_list,
         })?;
 
         let atoms = Atoms::new(&connection)?;
@@ -382,7 +1441,42 @@ impl StatusBarBuilder {
             data: &[atoms._NET_WM_WINDOW_TYPE_DOCK],
         })?;
 
-        let bar_size = self.height as u32; // MUST USE u32
+        let mut states = Vec::new();
+        if self.sticky {
+            states.push(intern_atom(&connection, "_NET_WM_STATE_STICKY")?);
+        }
+        match self.stack_layer {
+            Some(StackLayer::Above) => {
+                states.push(intern_atom(&connection, "_NET_WM_STATE_ABOVE")?)
+            }
+            Some(StackLayer::Below) => {
+                states.push(intern_atom(&connection, "_NET_WM_STATE_BELOW")?)
+            }
+            None => {}
+        }
+        if !states.is_empty() {
+            connection.send_and_check_request(&xcb::x::ChangeProperty {
+                mode: xcb::x::PropMode::Replace,
+                window,
+                property: intern_atom(&connection, "_NET_WM_STATE")?,
+                r#type: xcb::x::ATOM_ATOM,
+                data: &states,
+            })?;
+        }
+
+        // instance and class, both null terminated
+        let class = format!("{0}\0{0}\0", self.window_class);
+        connection.send_and_check_request(&xcb::x::ChangeProperty {
+            mode: xcb::x::PropMode::Replace,
+            window,
+            property: xcb::x::ATOM_WM_CLASS,
+            r#type: xcb::x::ATOM_STRING,
+            data: class.as_bytes(),
+        })?;
+
+        // MUST USE u32, the margins keep windows out of the gap
+        let bar_size =
+            u32::from(self.height) + u32::from(self.margins.top) + u32::from(self.margins.bottom);
         let strut_data = [0, 0, bar_size, 0, 0, 0, 0, 0, 0, width as u32, 0, 0];
 
         connection.send_and_check_request(&xcb::x::ChangeProperty {
@@ -401,7 +1495,49 @@ impl StatusBarBuilder {
             data: &strut_data,
         })?;
 
-        set_window_title(connection.clone(), window, "barust")?;
+        if self.opacity < 1.0 {
+            let opacity_atom = intern_atom(&connection, "_NET_WM_WINDOW_OPACITY")?;
+            connection.send_and_check_request(&xcb::x::ChangeProperty {
+                mode: xcb::x::PropMode::Replace,
+                window,
+                property: opacity_atom,
+                r#type: xcb::x::ATOM_CARDINAL,
+                data: &[(self.opacity * f64::from(u32::MAX)) as u32],
+            })?;
+        }
+
+        if self.click_through {
+            // an empty input region makes the window invisible to the pointer
+            connection.send_and_check_request(&xcb::shape::Rectangles {
+                operation: xcb::shape::So::Set,
+                destination_kind: xcb::shape::Sk::Input,
+                ordering: xcb::x::ClipOrdering::Unsorted,
+                destination_window: window,
+                x_offset: 0,
+                y_offset: 0,
+                rectangles: &[],
+            })?;
+        }
+
+        if self.blur {
+            let blur_atom = intern_atom(&connection, "_KDE_NET_WM_BLUR_BEHIND_REGION")?;
+            // an empty region means the whole window
+            connection.send_and_check_request(&xcb::x::ChangeProperty {
+                mode: xcb::x::PropMode::Replace,
+                window,
+                property: blur_atom,
+                r#type: xcb::x::ATOM_CARDINAL,
+                data: &[] as &[u32],
+            })?;
+        }
+
+        set_window_title(connection.clone(), window, &self.window_title)?;
+
+        // follow resolution changes and monitor hotplug
+        connection.send_and_check_request(&xcb::randr::SelectInput {
+            window: screen.root(),
+            enable: xcb::randr::NotifyMask::SCREEN_CHANGE,
+        })?;
 
         let surface = unsafe {
             let conn_ptr = connection.get_raw_conn() as _;
@@ -416,11 +1552,46 @@ impl StatusBarBuilder {
 
         connection.flush()?;
 
-        let widgets: Vec<ReplaceableWidget> = self
+        let mut hotkeys = Vec::new();
+        for (modifiers, keysym, action) in self.hotkeys {
+            let Some(keycode) = keysym_to_keycode(&connection, keysym)? else {
+                warn!("no keycode for keysym {keysym:#x}");
+                continue;
+            };
+            connection.send_and_check_request(&GrabKey {
+                owner_events: false,
+                grab_window: screen.root(),
+                modifiers,
+                key: keycode,
+                pointer_mode: GrabMode::Async,
+                keyboard_mode: GrabMode::Async,
+            })?;
+            hotkeys.push((modifiers, keycode, action));
+        }
+
+        let on_error = self.on_error;
+        let mut widgets: Vec<ReplaceableWidget> = self
             .widgets
             .into_iter()
-            .map(ReplaceableWidget::new)
+            .map(|wd| ReplaceableWidget::new(wd).with_on_error(on_error))
             .collect();
+        let mut names = self.widget_names;
+        let mut groups = self.widget_groups;
+        let mut pages = Vec::new();
+        if !widgets.is_empty() || self.pages.is_empty() {
+            pages.push((String::from("default"), 0..widgets.len()));
+        }
+        for (name, page_widgets) in self.pages {
+            let start = widgets.len();
+            widgets.extend(
+                page_widgets
+                    .into_iter()
+                    .map(|wd| ReplaceableWidget::new(wd).with_on_error(on_error)),
+            );
+            pages.push((name, start..widgets.len()));
+        }
+        names.resize(widgets.len(), None);
+        groups.resize(widgets.len(), None);
         let regions = vec![Rectangle::default(); widgets.len()];
 
         Ok(StatusBar {
@@ -433,6 +1604,35 @@ impl StatusBarBuilder {
             width: u32::from(width),
             window,
             position: self.position,
+            theme: self.theme,
+            theme_loader: self.theme_loader,
+            pages,
+            active_page: 0,
+            screen_id,
+            xoff: self.xoff,
+            yoff: self.yoff,
+            auto_width: self.width.is_none(),
+            spacing: self.spacing,
+            margins: self.margins,
+            on_before_draw: self.on_before_draw,
+            on_after_layout: self.on_after_layout,
+            hotkeys,
+            hidden: false,
+            frame_times: FrameTimes::default(),
+            overflow: self.overflow,
+            on_error,
+            sigusr1: self.sigusr1,
+            sigusr2: self.sigusr2,
+            names,
+            groups,
+            hidden_groups: Vec::new(),
+            hook_ids: Vec::new(),
+            next_hook_id: 0,
+            widget_channel: None,
+            ipc_commands,
+            registry: self.registry,
+            day_night: self.day_night,
+            day_active: None,
         })
     }
 }
@@ -456,10 +1656,44 @@ pub(crate) fn set_window_title(
     Ok(())
 }
 
-fn bar_event_listener(connection: Arc<Connection>) -> Result<Receiver<()>> {
+enum BarEvent {
+    Click(i16, MouseButton, ClickModifiers),
+    KeyPress(u8, xcb::x::KeyButMask),
+    ScreenChanged,
+    Redraw,
+}
+
+fn bar_event_listener(connection: Arc<Connection>) -> Result<Receiver<BarEvent>> {
     let (tx, rx) = bounded(10);
     thread::spawn(move || loop {
-        if matches!(connection.wait_for_event(), Ok(Event::X(_))) && tx.send_blocking(()).is_err() {
+        let event = match connection.wait_for_event() {
+            Ok(Event::X(xcb::x::Event::ButtonPress(press))) => {
+                let button = match press.detail() {
+                    1 => MouseButton::Left,
+                    2 => MouseButton::Middle,
+                    3 => MouseButton::Right,
+                    4 => MouseButton::ScrollUp,
+                    5 => MouseButton::ScrollDown,
+                    _ => continue,
+                };
+                let state = press.state();
+                let modifiers = ClickModifiers {
+                    shift: state.contains(xcb::x::KeyButMask::SHIFT),
+                    ctrl: state.contains(xcb::x::KeyButMask::CONTROL),
+                    alt: state.contains(xcb::x::KeyButMask::MOD1),
+                    logo: state.contains(xcb::x::KeyButMask::MOD4),
+                };
+                BarEvent::Click(press.event_x(), button, modifiers)
+            }
+            // grabbed hotkeys are the only key events we receive
+            Ok(Event::X(xcb::x::Event::KeyPress(press))) => {
+                BarEvent::KeyPress(press.detail(), press.state())
+            }
+            Ok(Event::RandR(xcb::randr::Event::ScreenChangeNotify(_))) => BarEvent::ScreenChanged,
+            Ok(Event::X(_)) => BarEvent::Redraw,
+            _ => continue,
+        };
+        if tx.send_blocking(event).is_err() {
             error!("bar_event_listener channel closed");
             break;
         }
@@ -467,6 +1701,25 @@ fn bar_event_listener(connection: Arc<Connection>) -> Result<Receiver<()>> {
     Ok(rx)
 }
 
+fn user_signal(
+    kind: SignalKind,
+    name: &'static str,
+) -> std::result::Result<Receiver<()>, BarustError> {
+    let (s, r) = bounded(10);
+    spawn(async move {
+        let mut stream = signal(kind).unwrap();
+        loop {
+            stream.recv().await;
+            warn!("Receive {name}");
+            if s.send(()).await.is_err() {
+                error!("signal channel closed");
+                break;
+            }
+        }
+    });
+    Ok(r)
+}
+
 fn stop_on_signal() -> std::result::Result<Receiver<()>, BarustError> {
     let (s, r) = bounded(10);
     spawn(async move {
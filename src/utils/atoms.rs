@@ -1,10 +1,14 @@
 #![allow(non_snake_case)]
 
 use crate::atoms;
-use std::sync::OnceLock;
+use std::{
+    collections::HashMap,
+    sync::{Mutex, OnceLock},
+};
 use xcb::{Connection, Xid};
 
 static ATOMS: OnceLock<Atoms> = OnceLock::new();
+static EXTRA_ATOMS: Mutex<Option<HashMap<String, xcb::x::Atom>>> = Mutex::new(None);
 
 atoms!(
      struct Atoms {
@@ -12,12 +16,15 @@ atoms!(
         UTF8_STRING,
         WM_NAME,
         _NET_ACTIVE_WINDOW,
+        _NET_CLIENT_LIST,
+        _NET_CLOSE_WINDOW,
         _NET_CURRENT_DESKTOP,
         _NET_DESKTOP_NAMES,
         _NET_SYSTEM_TRAY_OPCODE,
         _NET_SYSTEM_TRAY_ORIENTATION,
         _NET_SYSTEM_TRAY_S0,
         _NET_SYSTEM_TRAY_VISUAL,
+        _NET_WM_DESKTOP,
         _NET_WM_NAME,
         _NET_WM_WINDOW_TYPE,
         _NET_WM_WINDOW_TYPE_DOCK,
@@ -59,6 +66,7 @@ macro_rules! atoms {
 }
 
 impl Atoms {
+    /// Interns every atom on the first call, later calls are free
     pub fn new(connection: &Connection) -> xcb::Result<&'static Atoms> {
         if ATOMS.get().is_none() {
             let inner = Atoms::intern_all(connection)?;
@@ -67,3 +75,21 @@ impl Atoms {
         Ok(ATOMS.get().unwrap())
     }
 }
+
+/// Interns an atom outside the [Atoms] list, caching it by name
+///
+/// Unlike [Atoms] the atom is created when it does not exist
+pub(crate) fn intern_atom(connection: &Connection, name: &str) -> xcb::Result<xcb::x::Atom> {
+    let mut cache = EXTRA_ATOMS.lock().unwrap();
+    let cache = cache.get_or_insert_with(HashMap::new);
+    if let Some(atom) = cache.get(name) {
+        return Ok(*atom);
+    }
+    let cookie = connection.send_request(&xcb::x::InternAtom {
+        only_if_exists: false,
+        name: name.as_bytes(),
+    });
+    let atom = connection.wait_for_reply(cookie)?.atom();
+    cache.insert(name.to_string(), atom);
+    Ok(atom)
+}
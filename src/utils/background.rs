@@ -0,0 +1,62 @@
+use super::{set_source_rgba, Color};
+use cairo::{Context, ImageSurface, LinearGradient};
+use log::error;
+use std::{fs::File, path::PathBuf};
+
+/// How a region of the bar is filled before drawing on top of it
+#[derive(Debug, Clone)]
+pub enum Background {
+    Flat(Color),
+    ///color stops (offset from 0 to 1) interpolated left to right
+    LinearGradient(Vec<(f64, Color)>),
+    ///path to a png image, stretched to the target region
+    Image(PathBuf),
+}
+
+impl Background {
+    /// Paints the background over a `width` x `height` region
+    pub fn apply(&self, context: &Context, width: f64, height: f64) -> Result<(), cairo::Error> {
+        match self {
+            Background::Flat(color) => {
+                set_source_rgba(context, *color);
+                context.paint()?;
+            }
+            Background::LinearGradient(stops) => {
+                let gradient = LinearGradient::new(0.0, 0.0, width, 0.0);
+                for (offset, color) in stops {
+                    gradient.add_color_stop_rgba(*offset, color.r, color.g, color.b, color.a);
+                }
+                context.set_source(&gradient)?;
+                context.paint()?;
+            }
+            Background::Image(path) => {
+                let surface = match File::open(path)
+                    .map_err(|e| e.to_string())
+                    .and_then(|mut f| {
+                        ImageSurface::create_from_png(&mut f).map_err(|e| e.to_string())
+                    }) {
+                    Ok(surface) => surface,
+                    Err(e) => {
+                        error!("failed to load background image {}: {}", path.display(), e);
+                        return Ok(());
+                    }
+                };
+                context.save()?;
+                context.scale(
+                    width / f64::from(surface.width()),
+                    height / f64::from(surface.height()),
+                );
+                context.set_source_surface(&surface, 0.0, 0.0)?;
+                context.paint()?;
+                context.restore()?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl From<Color> for Background {
+    fn from(color: Color) -> Self {
+        Background::Flat(color)
+    }
+}
@@ -12,6 +12,33 @@ impl Color {
     pub const fn new(r: f64, g: f64, b: f64, a: f64) -> Self {
         Self { r, g, b, a }
     }
+
+    /// Parses a `#RRGGBB` or `#RRGGBBAA` string
+    pub fn from_hex(hex: &str) -> Option<Self> {
+        let hex = hex.strip_prefix('#').unwrap_or(hex);
+        if hex.len() != 6 && hex.len() != 8 {
+            return None;
+        }
+        let component = |i: usize| {
+            u8::from_str_radix(hex.get(i..i + 2)?, 16)
+                .ok()
+                .map(|v| f64::from(v) / 255.0)
+        };
+        Some(Self {
+            r: component(0)?,
+            g: component(2)?,
+            b: component(4)?,
+            a: if hex.len() == 8 { component(6)? } else { 1.0 },
+        })
+    }
+}
+
+impl std::str::FromStr for Color {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_hex(s).ok_or_else(|| format!("invalid hex color: {}", s))
+    }
 }
 
 pub fn set_source_rgba(context: &Context, color: Color) {
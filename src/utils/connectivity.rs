@@ -0,0 +1,66 @@
+use log::debug;
+use std::{fs, sync::OnceLock, time::Duration};
+use tokio::{sync::watch, time::sleep};
+
+/// Watches the default route so network widgets can delay their
+/// first fetch and refetch as soon as the connection returns
+pub struct Connectivity {
+    receiver: watch::Receiver<bool>,
+}
+
+/// The watcher shared by every widget, started on first use
+pub fn connectivity() -> &'static Connectivity {
+    static WATCHER: OnceLock<Connectivity> = OnceLock::new();
+    WATCHER.get_or_init(Connectivity::new)
+}
+
+/// True when a default route is present
+fn has_default_route() -> bool {
+    let Ok(routes) = fs::read_to_string("/proc/net/route") else {
+        return false;
+    };
+    routes
+        .lines()
+        .skip(1)
+        .any(|line| line.split_whitespace().nth(1) == Some("00000000"))
+}
+
+impl Connectivity {
+    fn new() -> Self {
+        let (sender, receiver) = watch::channel(has_default_route());
+        tokio::spawn(async move {
+            loop {
+                sleep(Duration::from_secs(2)).await;
+                let online = has_default_route();
+                sender.send_if_modified(|previous| {
+                    if *previous == online {
+                        return false;
+                    }
+                    debug!("connectivity changed: online={online}");
+                    *previous = online;
+                    true
+                });
+            }
+        });
+        Self { receiver }
+    }
+
+    pub fn is_online(&self) -> bool {
+        *self.receiver.borrow()
+    }
+
+    /// Completes immediately if online, otherwise when the connection returns
+    pub async fn wait_until_online(&self) {
+        let mut receiver = self.receiver.clone();
+        let _ = receiver.wait_for(|online| *online).await;
+    }
+
+    /// Completes the next time the connection comes back after a drop
+    pub async fn wait_for_reconnect(&self) {
+        let mut receiver = self.receiver.clone();
+        if receiver.wait_for(|online| !*online).await.is_err() {
+            return;
+        }
+        let _ = receiver.wait_for(|online| *online).await;
+    }
+}
@@ -0,0 +1,206 @@
+//! Format string mini-language shared by the widgets
+//!
+//! Beside the plain `%x` specifiers every widget documents,
+//! templates can pad, round and convert values:
+//! * `%{p:5.1}` renders `p` with one decimal, padded to 5 characters
+//! * a trailing letter converts the unit (`B` raw bytes, `F` fahrenheit)
+//! * `%%` is a literal `%`
+//!
+//! Unknown specifiers are kept as-is
+//!
+//! Numbers follow the locale decimal separator read from the environment
+
+use std::{fmt::Write, sync::OnceLock};
+
+/// Decimal separator taken from the process locale
+/// (`LC_NUMERIC`/`LC_ALL`/`LANG`), a comma for most of Europe
+fn locale_decimal_separator() -> char {
+    static SEPARATOR: OnceLock<char> = OnceLock::new();
+    *SEPARATOR.get_or_init(|| {
+        let locale = std::env::var("LC_NUMERIC")
+            .or_else(|_| std::env::var("LC_ALL"))
+            .or_else(|_| std::env::var("LANG"))
+            .unwrap_or_default();
+        let comma = [
+            "cs", "da", "de", "es", "fi", "fr", "it", "nb", "nl", "pl", "pt", "ru", "sv", "tr",
+        ];
+        if comma.iter().any(|prefix| locale.starts_with(prefix)) {
+            ','
+        } else {
+            '.'
+        }
+    })
+}
+
+/// A value a widget exposes to its format string
+#[derive(Debug, Clone)]
+pub enum FormatValue {
+    Text(String),
+    /// rendered with one decimal by default
+    Number(f64),
+    /// rendered human readable (`12MB`), `B` renders the raw count
+    Bytes(u64),
+    /// degrees celsius, `F` converts to fahrenheit
+    Celsius(f64),
+}
+
+/// True when the template references `key`
+pub fn uses(template: &str, key: char) -> bool {
+    template.contains(&format!("%{key}")) || template.contains(&format!("%{{{key}"))
+}
+
+fn humanize_bytes(out: &mut String, bytes: u64, precision: usize) {
+    const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value > 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    let _ = write!(out, "{value:.precision$}{}", UNITS[unit]);
+}
+
+/// Swaps the first `.` after `start` for the locale separator,
+/// in place since both are one byte
+fn localize_decimal(out: &mut String, start: usize) {
+    let separator = locale_decimal_separator();
+    if separator == '.' {
+        return;
+    }
+    if let Some(position) = out[start..].find('.') {
+        let position = start + position;
+        out.replace_range(position..=position, separator.encode_utf8(&mut [0; 4]));
+    }
+}
+
+fn render(
+    out: &mut String,
+    value: &FormatValue,
+    width: usize,
+    precision: Option<usize>,
+    unit: Option<char>,
+) {
+    let start = out.len();
+    match value {
+        FormatValue::Text(text) => out.push_str(text),
+        FormatValue::Number(n) => {
+            let _ = write!(out, "{n:.*}", precision.unwrap_or(1));
+            localize_decimal(out, start);
+        }
+        FormatValue::Bytes(bytes) => match unit {
+            Some('B') => {
+                let _ = write!(out, "{bytes}");
+            }
+            _ => humanize_bytes(out, *bytes, precision.unwrap_or(0)),
+        },
+        FormatValue::Celsius(celsius) => {
+            let degrees = match unit {
+                Some('F') => celsius * 9.0 / 5.0 + 32.0,
+                _ => *celsius,
+            };
+            let _ = write!(out, "{degrees:.*}", precision.unwrap_or(0));
+            localize_decimal(out, start);
+        }
+    }
+    let rendered = out.len() - start;
+    for _ in rendered..width {
+        match value {
+            FormatValue::Text(_) => out.push(' '),
+            _ => out.insert(start, ' '),
+        }
+    }
+}
+
+/// Parses "x", "x:5", "x:5.1" or "x:.1F" and renders the value of `x`,
+/// `out` is only touched when the whole token is valid
+fn render_token(out: &mut String, token: &str, values: &[(char, FormatValue)]) -> Option<()> {
+    let mut chars = token.chars();
+    let key = chars.next()?;
+    let value = value_for(values, key)?;
+    let spec = chars.as_str();
+    if !spec.is_empty() && !spec.starts_with(':') {
+        return None;
+    }
+    let spec = spec.strip_prefix(':').unwrap_or(spec);
+
+    let width_digits: String = spec.chars().take_while(char::is_ascii_digit).collect();
+    let width = width_digits.parse().unwrap_or(0);
+    let mut rest = &spec[width_digits.len()..];
+
+    let mut precision = None;
+    if let Some(stripped) = rest.strip_prefix('.') {
+        let digits: String = stripped.chars().take_while(char::is_ascii_digit).collect();
+        precision = digits.parse().ok();
+        rest = &stripped[digits.len()..];
+    }
+
+    let mut unit = None;
+    let mut rest_chars = rest.chars();
+    if let Some(c) = rest_chars.next() {
+        if !c.is_ascii_alphabetic() || rest_chars.next().is_some() {
+            return None;
+        }
+        unit = Some(c);
+    }
+
+    render(out, value, width, precision, unit);
+    Some(())
+}
+
+fn value_for<'a>(values: &'a [(char, FormatValue)], key: char) -> Option<&'a FormatValue> {
+    values.iter().find(|(k, _)| *k == key).map(|(_, v)| v)
+}
+
+/// Replaces the `%` specifiers in `template` with `values`
+pub fn format(template: &str, values: &[(char, FormatValue)]) -> String {
+    let mut out = String::with_capacity(template.len());
+    format_into(template, values, &mut out);
+    out
+}
+
+/// Like [format] but appends to `out`, so widgets updating on
+/// every tick can reuse the same buffer
+pub fn format_into(template: &str, values: &[(char, FormatValue)], out: &mut String) {
+    let mut chars = template.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            out.push(c);
+            continue;
+        }
+        match chars.peek() {
+            Some('%') => {
+                chars.next();
+                out.push('%');
+            }
+            Some('{') => {
+                chars.next();
+                let mut token = String::new();
+                let mut closed = false;
+                for c in chars.by_ref() {
+                    if c == '}' {
+                        closed = true;
+                        break;
+                    }
+                    token.push(c);
+                }
+                if !(closed && render_token(out, &token, values).is_some()) {
+                    // keep malformed tokens visible
+                    out.push_str("%{");
+                    out.push_str(&token);
+                    if closed {
+                        out.push('}');
+                    }
+                }
+            }
+            Some(&key) => {
+                if let Some(value) = value_for(values, key) {
+                    chars.next();
+                    render(out, value, 0, None, None);
+                } else {
+                    out.push('%');
+                }
+            }
+            None => out.push('%'),
+        }
+    }
+}
@@ -0,0 +1,86 @@
+//! Renders widgets into an image without an X server
+//!
+//! Useful for golden-image tests of widgets and for previewing
+//! themes, see the `preview` example
+
+use crate::{
+    utils::{Background, Rectangle},
+    widgets::Widget,
+    BarustError, Result,
+};
+use cairo::{Context, Format, ImageSurface};
+use std::path::Path;
+
+/// Draws `widgets` side by side onto an [ImageSurface],
+/// mirroring the bar layout rules (static sizes first,
+/// the leftover split between flex widgets)
+pub async fn render(
+    widgets: &mut [Box<dyn Widget>],
+    background: &Background,
+    width: u32,
+    height: u32,
+    spacing: u32,
+) -> Result<ImageSurface> {
+    let surface = ImageSurface::create(Format::ARgb32, width as i32, height as i32)?;
+
+    // let the widgets fill in their content first
+    for widget in widgets.iter_mut() {
+        widget.update().await?;
+    }
+
+    let context = Context::new(&surface)?;
+    background.apply(&context, f64::from(width), f64::from(height))?;
+
+    let mut sizes = Vec::new();
+    let mut flex_count: u32 = 0;
+    let mut used: u32 = 0;
+    for widget in widgets.iter() {
+        let size = widget.size(&context)?;
+        if size.is_flex() {
+            flex_count += 1;
+        } else {
+            used += size.unwrap_or(0);
+        }
+        sizes.push(size);
+    }
+    let total_spacing = spacing * widgets.len().saturating_sub(1) as u32;
+    let leftover = width.saturating_sub(used + total_spacing);
+    let flex_size = if flex_count > 0 {
+        leftover / flex_count
+    } else {
+        0
+    };
+
+    let mut x: u32 = 0;
+    for (widget, size) in widgets.iter().zip(&sizes) {
+        let rectangle = Rectangle {
+            x,
+            y: 0,
+            width: size.unwrap_or(flex_size),
+            height,
+        };
+        let sub_surface = surface.create_for_rectangle(rectangle.into())?;
+        let context = Context::new(&sub_surface)?;
+        widget.draw(context, &rectangle)?;
+        x += rectangle.width + spacing;
+    }
+    surface.flush();
+    Ok(surface)
+}
+
+/// Renders to a png file, for golden-image tests and previews
+pub async fn render_png(
+    widgets: &mut [Box<dyn Widget>],
+    background: &Background,
+    width: u32,
+    height: u32,
+    spacing: u32,
+    path: impl AsRef<Path>,
+) -> Result<()> {
+    let surface = render(widgets, background, width, height, spacing).await?;
+    let mut file = std::fs::File::create(path)?;
+    surface.write_to_png(&mut file).map_err(|e| match e {
+        cairo::IoError::Cairo(e) => BarustError::from(e),
+        cairo::IoError::Io(e) => BarustError::from(e),
+    })
+}
@@ -2,22 +2,77 @@ use async_channel::{SendError, Sender};
 
 pub type WidgetIndex = usize;
 
+/// What a widget hook is reporting
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HookKind {
+    /// the content changed, a redraw is enough
+    DataChanged,
+    /// the size changed, the bar must relayout
+    SizeChanged,
+    /// the widget wants attention, the whole bar is repainted
+    UrgentChange,
+    /// free-form event, treated like [DataChanged](HookKind::DataChanged)
+    Custom(String),
+}
+
+/// An event sent from a widget hook to the bar
+#[derive(Debug, Clone)]
+pub struct HookEvent {
+    pub index: WidgetIndex,
+    pub kind: HookKind,
+}
+
 #[derive(Debug, Clone)]
 pub struct HookSender {
-    sender: Sender<WidgetIndex>,
+    sender: Sender<HookEvent>,
     id: WidgetIndex,
 }
 
 impl HookSender {
-    pub fn new(sender: Sender<WidgetIndex>, id: WidgetIndex) -> Self {
+    pub fn new(sender: Sender<HookEvent>, id: WidgetIndex) -> Self {
         Self { sender, id }
     }
 
-    pub async fn send(&self) -> Result<(), SendError<WidgetIndex>> {
-        self.sender.send(self.id).await
+    fn event(&self, kind: HookKind) -> HookEvent {
+        HookEvent {
+            index: self.id,
+            kind,
+        }
+    }
+
+    pub async fn send(&self) -> Result<(), SendError<HookEvent>> {
+        self.sender.send(self.event(HookKind::DataChanged)).await
+    }
+
+    pub fn send_blocking(&self) -> Result<(), SendError<HookEvent>> {
+        self.sender.send_blocking(self.event(HookKind::DataChanged))
+    }
+
+    /// Signals that the widget size changed and the bar must relayout
+    pub async fn send_size_changed(&self) -> Result<(), SendError<HookEvent>> {
+        self.sender.send(self.event(HookKind::SizeChanged)).await
+    }
+
+    pub fn send_size_changed_blocking(&self) -> Result<(), SendError<HookEvent>> {
+        self.sender.send_blocking(self.event(HookKind::SizeChanged))
+    }
+
+    /// Signals that the widget needs attention right away
+    pub async fn send_urgent(&self) -> Result<(), SendError<HookEvent>> {
+        self.sender.send(self.event(HookKind::UrgentChange)).await
+    }
+
+    pub fn send_urgent_blocking(&self) -> Result<(), SendError<HookEvent>> {
+        self.sender.send_blocking(self.event(HookKind::UrgentChange))
     }
 
-    pub fn send_blocking(&self) -> Result<(), SendError<WidgetIndex>> {
-        self.sender.send_blocking(self.id)
+    /// Sends a free-form event, useful for custom widgets
+    pub async fn send_custom(
+        &self,
+        payload: impl ToString,
+    ) -> Result<(), SendError<HookEvent>> {
+        self.sender
+            .send(self.event(HookKind::Custom(payload.to_string())))
+            .await
     }
 }
@@ -0,0 +1,111 @@
+use log::{debug, warn};
+use reqwest::Client;
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex, OnceLock,
+    },
+    time::{Duration, Instant},
+};
+use tokio::time::sleep;
+
+struct CacheEntry {
+    body: String,
+    at: Instant,
+}
+
+/// A shared http client with retry, response caching and an
+/// offline flag so widgets do not hammer APIs while the network is down
+pub struct HttpClient {
+    client: Client,
+    cache: Mutex<HashMap<String, CacheEntry>>,
+    ttl: Duration,
+    retries: u32,
+    offline: AtomicBool,
+}
+
+/// The client shared by every widget
+pub fn http_client() -> &'static HttpClient {
+    static CLIENT: OnceLock<HttpClient> = OnceLock::new();
+    CLIENT.get_or_init(HttpClient::new)
+}
+
+impl HttpClient {
+    fn new() -> Self {
+        Self {
+            client: Client::new(),
+            cache: Mutex::new(HashMap::new()),
+            ttl: Duration::from_secs(60),
+            retries: 3,
+            offline: AtomicBool::new(false),
+        }
+    }
+
+    /// True while the last request could not connect
+    pub fn is_offline(&self) -> bool {
+        self.offline.load(Ordering::Relaxed)
+    }
+
+    fn cached(&self, url: &str) -> Option<String> {
+        let cache = self.cache.lock().unwrap();
+        let entry = cache.get(url)?;
+        (entry.at.elapsed() < self.ttl).then(|| entry.body.clone())
+    }
+
+    async fn try_get(&self, url: &str) -> reqwest::Result<String> {
+        self.client
+            .get(url)
+            .send()
+            .await?
+            .error_for_status()?
+            .text()
+            .await
+    }
+
+    /// Fetches a url, from the cache when fresh enough,
+    /// retrying with exponential backoff on failure
+    pub async fn get_text(&self, url: &str) -> reqwest::Result<String> {
+        if let Some(body) = self.cached(url) {
+            debug!("http cache hit for {url}");
+            return Ok(body);
+        }
+        let mut backoff = Duration::from_secs(1);
+        let mut attempt = 0;
+        loop {
+            match self.try_get(url).await {
+                Ok(body) => {
+                    self.offline.store(false, Ordering::Relaxed);
+                    self.cache.lock().unwrap().insert(
+                        url.to_string(),
+                        CacheEntry {
+                            body: body.clone(),
+                            at: Instant::now(),
+                        },
+                    );
+                    return Ok(body);
+                }
+                Err(e) => {
+                    if e.is_connect() {
+                        // no point in retrying until the network is back
+                        self.offline.store(true, Ordering::Relaxed);
+                        return Err(e);
+                    }
+                    attempt += 1;
+                    if attempt > self.retries {
+                        return Err(e);
+                    }
+                    warn!("http request to {url} failed (attempt {attempt}): {e}");
+                    sleep(backoff).await;
+                    backoff *= 2;
+                }
+            }
+        }
+    }
+
+    /// Like [get_text](HttpClient::get_text) but parses the body as json
+    pub async fn get_json(&self, url: &str) -> reqwest::Result<Option<serde_json::Value>> {
+        let body = self.get_text(url).await?;
+        Ok(serde_json::from_str(&body).ok())
+    }
+}
@@ -0,0 +1,52 @@
+//! Icon packs and glyph coverage checks
+//!
+//! Every `*Icons` struct implements [IconSet], listing the glyphs it
+//! may draw so the widget can verify at setup that the configured
+//! font actually shapes them, instead of silently rendering tofu.
+//! [IconSet::for_theme] builds a whole set from a named [IconTheme],
+//! switching every icon of a widget in one place
+
+use log::warn;
+use pango::{prelude::FontMapExt, FontDescription, Layout};
+
+/// A named icon pack
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum IconTheme {
+    /// glyphs from the nerd-font private range, needs a patched font
+    #[default]
+    NerdFont,
+    /// color emoji
+    Emoji,
+    /// plain text, works with any font
+    Ascii,
+}
+
+/// The icons a widget can draw, see [IconTheme]
+pub trait IconSet {
+    /// every glyph the set may draw
+    fn glyphs(&self) -> Vec<&str>;
+
+    /// The set from a named pack, packs without a dedicated
+    /// variant fall back to the default icons
+    fn for_theme(_theme: IconTheme) -> Self
+    where
+        Self: Sized + Default,
+    {
+        Self::default()
+    }
+}
+
+/// Warns about every glyph in `set` that `font` cannot shape,
+/// called by the widgets at setup
+pub fn warn_missing_glyphs(set: &dyn IconSet, font: &str, widget: &str) {
+    let font_map = pangocairo::FontMap::new();
+    let context = font_map.create_context();
+    let layout = Layout::new(&context);
+    layout.set_font_description(Some(&FontDescription::from_string(font)));
+    for glyph in set.glyphs() {
+        layout.set_text(glyph);
+        if layout.unknown_glyphs_count() > 0 {
+            warn!("{widget}: the font `{font}` is missing the glyph `{glyph}`");
+        }
+    }
+}
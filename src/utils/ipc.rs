@@ -0,0 +1,109 @@
+//! Json-line commands over a unix socket
+//!
+//! Enabled with [ipc_socket](crate::statusbar::StatusBarBuilder::ipc_socket),
+//! every line sent to the socket is one json object, e.g.
+//! `{"command": "insert_widget", "index": 0, "widget": "clock", "options": {"format": "%H:%M"}}`,
+//! `{"command": "remove_widget", "index": 0}` or
+//! `{"command": "update_widget", "name": "cpu"}`
+//!
+//! The `barust-ctl` binary wraps these commands for scripts
+
+use async_channel::{bounded, Receiver};
+use log::{debug, warn};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use tokio::{
+    io::{AsyncBufReadExt, BufReader},
+    net::UnixListener,
+    spawn,
+};
+
+/// A command received on the IPC socket
+#[derive(Debug)]
+pub enum IpcCommand {
+    /// insert a widget built by the
+    /// [WidgetRegistry](crate::widgets::WidgetRegistry) at `index`
+    InsertWidget {
+        index: usize,
+        widget: String,
+        options: Value,
+    },
+    /// teardown and drop the widget at `index`
+    RemoveWidget { index: usize },
+    /// update the widget registered with
+    /// [named](crate::statusbar::StatusBarBuilder::named) right away
+    UpdateWidget { name: String },
+    /// hide every widget registered with
+    /// [grouped](crate::statusbar::StatusBarBuilder::grouped) under `group`
+    HideGroup { group: String },
+    /// undo a [HideGroup](IpcCommand::HideGroup)
+    ShowGroup { group: String },
+}
+
+/// The socket path `barust-ctl` falls back to
+pub fn default_socket_path() -> PathBuf {
+    let runtime = std::env::var("XDG_RUNTIME_DIR").unwrap_or_else(|_| String::from("/tmp"));
+    Path::new(&runtime).join("barust.sock")
+}
+
+impl IpcCommand {
+    fn parse(line: &str) -> Option<Self> {
+        let value: Value = serde_json::from_str(line).ok()?;
+        let index = value.get("index").and_then(Value::as_u64).unwrap_or(0) as usize;
+        match value.get("command").and_then(Value::as_str)? {
+            "insert_widget" => Some(Self::InsertWidget {
+                index,
+                widget: value.get("widget").and_then(Value::as_str)?.to_string(),
+                options: value.get("options").cloned().unwrap_or(Value::Null),
+            }),
+            "remove_widget" => Some(Self::RemoveWidget { index }),
+            "update_widget" => Some(Self::UpdateWidget {
+                name: value.get("name").and_then(Value::as_str)?.to_string(),
+            }),
+            "hide_group" => Some(Self::HideGroup {
+                group: value.get("group").and_then(Value::as_str)?.to_string(),
+            }),
+            "show_group" => Some(Self::ShowGroup {
+                group: value.get("group").and_then(Value::as_str)?.to_string(),
+            }),
+            command => {
+                warn!("unknown ipc command: {command}");
+                None
+            }
+        }
+    }
+}
+
+/// Binds `path` and forwards the parsed commands
+pub(crate) fn ipc_listener(path: &Path) -> std::io::Result<Receiver<IpcCommand>> {
+    if path.exists() {
+        // a previous bar may have left the socket behind
+        std::fs::remove_file(path)?;
+    }
+    let listener = UnixListener::bind(path)?;
+    let (tx, rx) = bounded(10);
+    spawn(async move {
+        loop {
+            let stream = match listener.accept().await {
+                Ok((stream, _)) => stream,
+                Err(e) => {
+                    warn!("ipc accept failed: {e}");
+                    return;
+                }
+            };
+            let tx = tx.clone();
+            spawn(async move {
+                let mut lines = BufReader::new(stream).lines();
+                while let Ok(Some(line)) = lines.next_line().await {
+                    debug!("ipc command: {line}");
+                    if let Some(command) = IpcCommand::parse(&line) {
+                        if tx.send(command).await.is_err() {
+                            return;
+                        }
+                    }
+                }
+            });
+        }
+    });
+    Ok(rx)
+}
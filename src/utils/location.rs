@@ -0,0 +1,109 @@
+//! Geographic location sources
+//!
+//! The Weather widget geolocates through the machine's public ip by
+//! default, which is inaccurate and rate limited. A [LocationProvider]
+//! replaces that lookup, [geoclue::GeoclueProvider] asks the GeoClue2
+//! D-Bus service instead
+
+use async_trait::async_trait;
+
+/// A position on the globe
+#[derive(Debug, Clone)]
+pub struct Location {
+    pub latitude: f64,
+    /// positive going east
+    pub longitude: f64,
+    /// a human readable place name, when the source knows one
+    pub city: Option<String>,
+}
+
+/// A source of geographic coordinates
+#[async_trait]
+pub trait LocationProvider: Send + Sync + std::fmt::Debug {
+    async fn location(&self) -> Option<Location>;
+}
+
+#[cfg(feature = "geoclue")]
+pub mod geoclue {
+    use super::{Location, LocationProvider};
+    use async_trait::async_trait;
+    use futures::StreamExt;
+    use log::warn;
+    use std::time::Duration;
+    use tokio::time::timeout;
+    use zbus::zvariant::OwnedObjectPath;
+
+    const GEOCLUE_DEST: &str = "org.freedesktop.GeoClue2";
+    const GEOCLUE_MANAGER_PATH: &str = "/org/freedesktop/GeoClue2/Manager";
+
+    /// Reads the position from the GeoClue2 D-Bus service
+    #[derive(Debug)]
+    pub struct GeoclueProvider;
+
+    impl GeoclueProvider {
+        pub fn new() -> Box<Self> {
+            Box::new(Self)
+        }
+
+        async fn query(&self) -> zbus::Result<Location> {
+            let connection = zbus::Connection::system().await?;
+            let manager = zbus::Proxy::new(
+                &connection,
+                GEOCLUE_DEST,
+                GEOCLUE_MANAGER_PATH,
+                "org.freedesktop.GeoClue2.Manager",
+            )
+            .await?;
+            let client_path: OwnedObjectPath = manager.call("GetClient", &()).await?;
+            let client = zbus::Proxy::new(
+                &connection,
+                GEOCLUE_DEST,
+                client_path,
+                "org.freedesktop.GeoClue2.Client",
+            )
+            .await?;
+            client.set_property("DesktopId", "barust").await?;
+            let mut updates = client.receive_signal("LocationUpdated").await?;
+            client.call::<_, _, ()>("Start", &()).await?;
+            let message = updates
+                .next()
+                .await
+                .ok_or(zbus::Error::InvalidReply)?;
+            let (_old, path): (OwnedObjectPath, OwnedObjectPath) =
+                message.body().deserialize()?;
+            let location = zbus::Proxy::new(
+                &connection,
+                GEOCLUE_DEST,
+                path,
+                "org.freedesktop.GeoClue2.Location",
+            )
+            .await?;
+            let latitude: f64 = location.get_property("Latitude").await?;
+            let longitude: f64 = location.get_property("Longitude").await?;
+            client.call::<_, _, ()>("Stop", &()).await?;
+            Ok(Location {
+                latitude,
+                longitude,
+                city: None,
+            })
+        }
+    }
+
+    #[async_trait]
+    impl LocationProvider for GeoclueProvider {
+        async fn location(&self) -> Option<Location> {
+            // geoclue may take a while to produce a first fix
+            match timeout(Duration::from_secs(30), self.query()).await {
+                Ok(Ok(location)) => Some(location),
+                Ok(Err(e)) => {
+                    warn!("geoclue failed: {e}");
+                    None
+                }
+                Err(_) => {
+                    warn!("geoclue timed out");
+                    None
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,62 @@
+//! Resume notifications from systemd-logind
+//!
+//! Subscribes to the `PrepareForSleep` signal on the system bus so
+//! the bar can refresh every widget on wake, instead of showing
+//! stale data until the next scheduled update
+
+use async_channel::{bounded, Receiver};
+use futures::StreamExt;
+use log::{debug, warn};
+use tokio::spawn;
+
+const LOGIND_DEST: &str = "org.freedesktop.login1";
+const LOGIND_PATH: &str = "/org/freedesktop/login1";
+
+/// Sends `()` every time the machine resumes from sleep
+pub(crate) fn resume_listener() -> Receiver<()> {
+    let (tx, rx) = bounded(1);
+    spawn(async move {
+        let connection = match zbus::Connection::system().await {
+            Ok(connection) => connection,
+            Err(e) => {
+                warn!("cannot reach the system bus: {e}");
+                return;
+            }
+        };
+        let proxy = match zbus::Proxy::new(
+            &connection,
+            LOGIND_DEST,
+            LOGIND_PATH,
+            "org.freedesktop.login1.Manager",
+        )
+        .await
+        {
+            Ok(proxy) => proxy,
+            Err(e) => {
+                warn!("cannot reach logind: {e}");
+                return;
+            }
+        };
+        let mut stream = match proxy.receive_signal("PrepareForSleep").await {
+            Ok(stream) => stream,
+            Err(e) => {
+                warn!("cannot subscribe to PrepareForSleep: {e}");
+                return;
+            }
+        };
+        while let Some(message) = stream.next().await {
+            // fires with true before sleeping and false on resume
+            let Ok(entering_sleep) = message.body().deserialize::<bool>() else {
+                continue;
+            };
+            if entering_sleep {
+                continue;
+            }
+            debug!("resumed from sleep");
+            if tx.send(()).await.is_err() {
+                break;
+            }
+        }
+    });
+    rx
+}
@@ -0,0 +1,67 @@
+//! Prometheus text-format export of widget values
+//!
+//! Widgets publish gauges with [gauge] during their update, the
+//! bar serves them over HTTP when
+//! [metrics_endpoint](crate::statusbar::StatusBarBuilder::metrics_endpoint)
+//! is set
+
+use log::debug;
+use std::{collections::BTreeMap, sync::Mutex};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpListener,
+    spawn,
+};
+
+static METRICS: Mutex<BTreeMap<String, f64>> = Mutex::new(BTreeMap::new());
+
+/// Publishes a gauge, exported as `barust_<name>`
+pub fn gauge(name: impl ToString, value: f64) {
+    METRICS
+        .lock()
+        .expect("metrics mutex is poisoned")
+        .insert(name.to_string(), value);
+}
+
+/// Every published gauge in Prometheus text format
+fn render() -> String {
+    let metrics = METRICS.lock().expect("metrics mutex is poisoned");
+    let mut out = String::new();
+    for (name, value) in metrics.iter() {
+        out.push_str(&format!(
+            "# TYPE barust_{name} gauge\nbarust_{name} {value}\n"
+        ));
+    }
+    out
+}
+
+/// Serves the metrics over HTTP, one request per connection
+pub async fn serve(address: &str) -> std::io::Result<()> {
+    let listener = TcpListener::bind(address).await?;
+    spawn(async move {
+        loop {
+            let Ok((mut stream, _)) = listener.accept().await else {
+                continue;
+            };
+            spawn(async move {
+                // the request itself does not matter, every path
+                // serves the same document
+                let mut request = [0; 1024];
+                let _ = stream.read(&mut request).await;
+                let body = render();
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\n\
+                     Content-Type: text/plain; version=0.0.4\r\n\
+                     Content-Length: {}\r\n\
+                     Connection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                );
+                if let Err(e) = stream.write_all(response.as_bytes()).await {
+                    debug!("failed to serve metrics: {e}");
+                }
+            });
+        }
+    });
+    Ok(())
+}
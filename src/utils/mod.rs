@@ -1,30 +1,125 @@
 #[cfg(feature = "psutil")]
 use psutil::Bytes;
-use std::fmt::Debug;
+use std::{
+    fmt::Debug,
+    sync::{Arc, OnceLock},
+};
 use xcb::Connection;
 
 pub mod atoms;
+pub mod background;
 pub mod color;
+pub mod connectivity;
+pub mod format;
+#[cfg(feature = "headless")]
+pub mod headless;
 pub mod hook_sender;
+#[cfg(any(feature = "json", feature = "rss", feature = "ticker"))]
+pub mod http;
+pub mod icons;
 pub mod image_surface;
+pub mod ipc;
+pub mod location;
+#[cfg(feature = "logind")]
+pub(crate) mod logind;
+pub mod metrics;
+pub mod notify;
+pub mod persistence;
+pub mod popup;
+pub mod power;
 pub mod resettable_timer;
+pub mod spawn;
+pub mod sun;
+#[cfg(feature = "testing")]
+pub mod testing;
+pub mod theme;
 pub mod timed_hooks;
 
 pub use atoms::Atoms;
+pub use background::Background;
 pub use color::{set_source_rgba, Color};
-pub use hook_sender::{HookSender, WidgetIndex};
+pub use connectivity::{connectivity, Connectivity};
+pub use format::FormatValue;
+pub use hook_sender::{HookEvent, HookKind, HookSender, WidgetIndex};
+#[cfg(any(feature = "json", feature = "rss", feature = "ticker"))]
+pub use http::{http_client, HttpClient};
+pub use icons::{IconSet, IconTheme};
 pub use image_surface::OwnedImageSurface;
+pub use ipc::IpcCommand;
+#[cfg(feature = "geoclue")]
+pub use location::geoclue::GeoclueProvider;
+pub use location::{Location, LocationProvider};
+pub use persistence::PersistentState;
+pub use popup::Popup;
 pub use resettable_timer::ResettableTimer;
+pub use sun::SunTimes;
+pub use theme::Theme;
 pub use timed_hooks::TimedHooks;
 
-#[derive(Debug)]
 pub struct StatusBarInfo {
-    pub background: Color,
+    pub background: Background,
     pub regions: Vec<Rectangle>,
     pub height: u32,
     pub width: u32,
     pub position: Position,
     pub window: xcb::x::Window,
+    pub theme: Theme,
+    pub connection: Arc<Connection>,
+    /// index of the X screen the bar lives on
+    pub screen_id: i32,
+    /// full size of that screen in pixels
+    pub screen_width: u32,
+    pub screen_height: u32,
+    /// dots per inch, 96 when the screen does not report its size
+    pub dpi: f64,
+}
+
+// by hand because xcb::Connection is not Debug
+impl Debug for StatusBarInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StatusBarInfo")
+            .field("background", &self.background)
+            .field("regions", &self.regions)
+            .field("height", &self.height)
+            .field("width", &self.width)
+            .field("position", &self.position)
+            .field("window", &self.window)
+            .field("theme", &self.theme)
+            .field("screen_id", &self.screen_id)
+            .field("screen_width", &self.screen_width)
+            .field("screen_height", &self.screen_height)
+            .field("dpi", &self.dpi)
+            .finish_non_exhaustive()
+    }
+}
+
+impl StatusBarInfo {
+    /// Screen y coordinate placing a popup of `popup_height` pixels
+    /// right below a top bar or right above a bottom one
+    pub fn popup_y(&self, popup_height: u16) -> i16 {
+        match self.position {
+            Position::Top => self.height as i16,
+            Position::Bottom => self
+                .screen_height
+                .saturating_sub(self.height + u32::from(popup_height))
+                as i16,
+        }
+    }
+}
+
+static CONNECTION: OnceLock<(Arc<Connection>, i32)> = OnceLock::new();
+
+/// The X connection shared by the bar and most widgets
+///
+/// Threads that block on `wait_for_event` must keep a connection of
+/// their own so they do not steal events from the bar
+pub fn shared_connection() -> xcb::ConnResult<(Arc<Connection>, i32)> {
+    if let Some((connection, screen_id)) = CONNECTION.get() {
+        return Ok((Arc::clone(connection), *screen_id));
+    }
+    let (connection, screen_id) = Connection::connect(None)?;
+    let (connection, screen_id) = CONNECTION.get_or_init(|| (Arc::new(connection), screen_id));
+    Ok((Arc::clone(connection), *screen_id))
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -51,6 +146,19 @@ pub fn screen_true_height(connection: &Connection, screen_id: i32) -> u16 {
         .height_in_pixels()
 }
 
+/// Dots per inch reported by the X screen
+pub fn screen_dpi(connection: &Connection, screen_id: i32) -> f64 {
+    let screen = connection
+        .get_setup()
+        .roots()
+        .nth(screen_id as _)
+        .unwrap_or_else(|| panic!("cannot find screen:{}", screen_id));
+    if screen.width_in_millimeters() == 0 {
+        return 96.0;
+    }
+    f64::from(screen.width_in_pixels()) * 25.4 / f64::from(screen.width_in_millimeters())
+}
+
 pub fn percentage_to_index(v: f64, out_range: (usize, usize)) -> usize {
     let scale = (out_range.1 - out_range.0) as f64 / 100.0;
     (v * scale + out_range.0 as f64) as _
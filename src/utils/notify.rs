@@ -0,0 +1,82 @@
+use log::error;
+use std::{
+    collections::VecDeque,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex, Once,
+    },
+    time::Instant,
+};
+
+/// How many notifications the history keeps
+const HISTORY_SIZE: usize = 50;
+
+static HISTORY: Mutex<VecDeque<Notification>> = Mutex::new(VecDeque::new());
+static UNREAD: AtomicUsize = AtomicUsize::new(0);
+static INIT: Once = Once::new();
+
+/// A notification kept in the history
+#[derive(Debug, Clone)]
+pub struct Notification {
+    pub summary: String,
+    pub body: String,
+    pub at: Instant,
+}
+
+impl Notification {
+    /// Age of the notification ("now", "5m", "2h")
+    pub fn age(&self) -> String {
+        let seconds = self.at.elapsed().as_secs();
+        match seconds {
+            0..=59 => String::from("now"),
+            60..=3599 => format!("{}m", seconds / 60),
+            _ => format!("{}h", seconds / 3600),
+        }
+    }
+}
+
+/// Shows a desktop notification and records it in the history
+pub fn notify(summary: &str, body: &str, urgency: libnotify::Urgency) {
+    INIT.call_once(|| {
+        if let Err(e) = libnotify::init("barust") {
+            error!("libnotify init failed: {e}");
+        }
+    });
+    let n = libnotify::Notification::new(summary, Some(body), None);
+    n.set_urgency(urgency);
+    if let Err(e) = n.show() {
+        error!("failed to show notification: {e}");
+    }
+    let mut history = HISTORY.lock().unwrap();
+    if history.len() == HISTORY_SIZE {
+        history.pop_back();
+    }
+    history.push_front(Notification {
+        summary: summary.to_string(),
+        body: body.to_string(),
+        at: Instant::now(),
+    });
+    UNREAD.fetch_add(1, Ordering::Relaxed);
+}
+
+/// The recorded notifications, newest first
+pub fn history() -> Vec<Notification> {
+    HISTORY.lock().unwrap().iter().cloned().collect()
+}
+
+/// Removes the notification at `index` (newest first)
+pub fn dismiss(index: usize) {
+    let mut history = HISTORY.lock().unwrap();
+    if index < history.len() {
+        history.remove(index);
+    }
+}
+
+/// Notifications shown since the last [mark_all_read]
+pub fn unread() -> usize {
+    UNREAD.load(Ordering::Relaxed)
+}
+
+pub fn mark_all_read() {
+    UNREAD.store(0, Ordering::Relaxed);
+}
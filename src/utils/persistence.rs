@@ -0,0 +1,49 @@
+use crate::xdg_data;
+use log::warn;
+use serde_json::Value;
+use std::{collections::HashMap, fs, io, path::PathBuf};
+
+/// Saved widget states, backed by a json file in [xdg_data]
+///
+/// Widgets opt in through [save_state](crate::widgets::Widget::save_state)
+/// and [load_state](crate::widgets::Widget::load_state)
+#[derive(Debug, Default)]
+pub struct PersistentState {
+    states: HashMap<String, Value>,
+}
+
+impl PersistentState {
+    fn path() -> io::Result<PathBuf> {
+        Ok(xdg_data()?.join("state.json"))
+    }
+
+    /// Loads the saved states, empty if the file is missing or invalid
+    pub fn load() -> Self {
+        let Ok(path) = Self::path() else {
+            return Self::default();
+        };
+        let Ok(content) = fs::read_to_string(path) else {
+            return Self::default();
+        };
+        match serde_json::from_str(&content) {
+            Ok(states) => Self { states },
+            Err(e) => {
+                warn!("ignoring invalid state file: {e}");
+                Self::default()
+            }
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.states.get(key)
+    }
+
+    pub fn set(&mut self, key: impl ToString, value: Value) {
+        self.states.insert(key.to_string(), value);
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let content = serde_json::to_string_pretty(&self.states).expect("state map is valid json");
+        fs::write(Self::path()?, content)
+    }
+}
@@ -0,0 +1,269 @@
+use crate::{
+    utils::{set_source_rgba, Color},
+    widgets::WidgetConfig,
+    Result,
+};
+use cairo::{Context, Operator, XCBConnection, XCBDrawable, XCBSurface, XCBVisualType};
+use pango::{FontDescription, Layout};
+use pangocairo::functions::{create_context, show_layout};
+use std::{cell::Cell, sync::Arc};
+use xcb::{
+    x::{
+        Colormap, ColormapAlloc, CreateColormap, CreateWindow, Cw, EventMask, MapWindow, Pixmap,
+        UnmapWindow, VisualClass, Visualtype, Window, WindowClass,
+    },
+    Connection, Xid,
+};
+
+/// A floating override-redirect window widgets can open
+/// as a menu, tooltip or status popup
+pub struct Popup {
+    connection: Arc<Connection>,
+    window: Window,
+    surface: XCBSurface,
+    width: u16,
+    height: u16,
+    font: String,
+    font_size: f64,
+    fg_color: Color,
+    background: Color,
+    lines: Vec<String>,
+    line_height: Cell<f64>,
+    visible: bool,
+}
+
+// SAFETY: the surface is only touched from one thread at a time,
+// widgets own their popup and access it through &mut self
+unsafe impl Send for Popup {}
+
+impl std::fmt::Debug for Popup {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "window: {:?}, width: {:?}, height: {:?}, visible: {:?}",
+            self.window, self.width, self.height, self.visible
+        )
+    }
+}
+
+impl Popup {
+    ///* `x`, `y` position of the top left corner in screen coordinates
+    ///* `width`, `height` size of the popup in pixel
+    ///* `background` popup background color
+    ///* `config` a [&WidgetConfig] used for font and foreground color
+    pub fn new(
+        x: i16,
+        y: i16,
+        width: u16,
+        height: u16,
+        background: Color,
+        config: &WidgetConfig,
+    ) -> Result<Self> {
+        let (connection, _) = Connection::connect(None)?;
+        let connection = Arc::new(connection);
+
+        let window: Window = connection.generate_id();
+        let colormap: Colormap = connection.generate_id();
+
+        let screen = connection
+            .get_setup()
+            .roots()
+            .next()
+            .expect("cannot find screen");
+
+        let depth = screen
+            .allowed_depths()
+            .find(|d| d.depth() == 32)
+            .expect("cannot find valid depth");
+
+        let mut visual_type = depth
+            .visuals()
+            .iter()
+            .find(|v| v.class() == VisualClass::TrueColor)
+            .expect("cannot find valid visual type")
+            .to_owned();
+
+        connection.send_and_check_request(&CreateColormap {
+            alloc: ColormapAlloc::None,
+            mid: colormap,
+            window: screen.root(),
+            visual: visual_type.visual_id(),
+        })?;
+
+        connection.send_and_check_request(&CreateWindow {
+            depth: depth.depth(),
+            wid: window,
+            parent: screen.root(),
+            x,
+            y,
+            width,
+            height,
+            border_width: 0,
+            class: WindowClass::InputOutput,
+            visual: visual_type.visual_id(),
+            value_list: &[
+                Cw::BackPixmap(Pixmap::none()),
+                Cw::BorderPixel(screen.black_pixel()),
+                Cw::OverrideRedirect(true),
+                Cw::EventMask(
+                    EventMask::BUTTON_PRESS | EventMask::EXPOSURE | EventMask::KEY_PRESS,
+                ),
+                Cw::Colormap(colormap),
+            ],
+        })?;
+
+        let surface = unsafe {
+            let conn_ptr = connection.get_raw_conn() as _;
+            XCBSurface::create(
+                &XCBConnection::from_raw_none(conn_ptr),
+                &XCBDrawable(window.resource_id()),
+                &XCBVisualType::from_raw_none(&mut visual_type as *mut Visualtype as _),
+                i32::from(width),
+                i32::from(height),
+            )?
+        };
+
+        connection.flush()?;
+
+        Ok(Self {
+            connection,
+            window,
+            surface,
+            width,
+            height,
+            font: config.font.clone(),
+            font_size: config.font_size,
+            fg_color: config.fg_color,
+            background,
+            lines: Vec::new(),
+            line_height: Cell::new(0.0),
+            visible: false,
+        })
+    }
+
+    pub fn connection(&self) -> &Connection {
+        &self.connection
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    /// Replaces the displayed lines and redraws if visible
+    pub fn set_lines(&mut self, lines: Vec<String>) -> Result<()> {
+        self.lines = lines;
+        if self.visible {
+            self.draw()?;
+        }
+        Ok(())
+    }
+
+    pub fn show(&mut self) -> Result<()> {
+        self.connection.send_and_check_request(&MapWindow {
+            window: self.window,
+        })?;
+        self.visible = true;
+        self.draw()
+    }
+
+    pub fn hide(&mut self) -> Result<()> {
+        self.connection.send_and_check_request(&UnmapWindow {
+            window: self.window,
+        })?;
+        self.visible = false;
+        self.connection.flush()?;
+        Ok(())
+    }
+
+    /// Blocks until the next event on the popup connection
+    pub fn next_event(&self) -> Result<xcb::Event> {
+        Ok(self.connection.wait_for_event()?)
+    }
+
+    /// Redirects all key presses to the popup while it is visible
+    pub fn grab_keyboard(&self) -> Result<()> {
+        let cookie = self.connection.send_request(&xcb::x::GrabKeyboard {
+            owner_events: false,
+            grab_window: self.window,
+            time: xcb::x::CURRENT_TIME,
+            pointer_mode: xcb::x::GrabMode::Async,
+            keyboard_mode: xcb::x::GrabMode::Async,
+        });
+        self.connection.wait_for_reply(cookie)?;
+        Ok(())
+    }
+
+    pub fn ungrab_keyboard(&self) -> Result<()> {
+        self.connection.send_and_check_request(&xcb::x::UngrabKeyboard {
+            time: xcb::x::CURRENT_TIME,
+        })?;
+        self.connection.flush()?;
+        Ok(())
+    }
+
+    /// Index of the displayed line at `y`, measured on the last draw
+    pub fn line_at(&self, y: f64) -> Option<usize> {
+        let line_height = self.line_height.get();
+        if line_height <= 0.0 {
+            return None;
+        }
+        let index = (y / line_height) as usize;
+        (index < self.lines.len()).then_some(index)
+    }
+
+    pub fn toggle(&mut self) -> Result<()> {
+        if self.visible {
+            self.hide()
+        } else {
+            self.show()
+        }
+    }
+
+    fn draw(&self) -> Result<()> {
+        let context = Context::new(&self.surface)?;
+        context.set_operator(Operator::Clear);
+        context.paint()?;
+        context.set_operator(Operator::Over);
+        set_source_rgba(&context, self.background);
+        context.paint()?;
+
+        set_source_rgba(&context, self.fg_color);
+        let layout = self.get_layout(&context);
+        let mut y = 0.0;
+        for line in &self.lines {
+            layout.set_text(line);
+            let line_height = f64::from(layout.pixel_size().1);
+            self.line_height.set(line_height);
+            if y + line_height > f64::from(self.height) {
+                break;
+            }
+            context.move_to(self.font_size / 2.0, y);
+            show_layout(&context, &layout);
+            y += line_height;
+        }
+
+        self.surface.flush();
+        self.connection.flush()?;
+        Ok(())
+    }
+
+    fn get_layout(&self, context: &Context) -> Layout {
+        let pango_context = create_context(context);
+        let layout = Layout::new(&pango_context);
+        let mut font = FontDescription::from_string(&self.font);
+        font.set_absolute_size(self.font_size * f64::from(pango::SCALE));
+        layout.set_font_description(Some(&font));
+        layout
+    }
+}
+
+impl Drop for Popup {
+    fn drop(&mut self) {
+        self.connection
+            .send_and_check_request(&xcb::x::DestroyWindow {
+                window: self.window,
+            })
+            .ok();
+        self.connection.flush().ok();
+    }
+}
@@ -0,0 +1,29 @@
+//! Global low-power flag
+//!
+//! Enabled by the [Battery](crate::widgets::Battery) widget while the
+//! machine discharges below its configured threshold, read by
+//! [TimedHooks](crate::utils::TimedHooks) and the draw loop to stretch
+//! their cadence, trading latency for battery life
+
+use log::debug;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static LOW_POWER: AtomicBool = AtomicBool::new(false);
+
+/// How much [TimedHooks](crate::utils::TimedHooks) stretch their tick
+/// while low power is active
+pub const LOW_POWER_MULTIPLIER: u32 = 4;
+
+/// True while low-power mode is active
+pub fn low_power() -> bool {
+    LOW_POWER.load(Ordering::Relaxed)
+}
+
+pub fn set_low_power(active: bool) {
+    if LOW_POWER.swap(active, Ordering::Relaxed) != active {
+        debug!(
+            "low power mode {}",
+            if active { "enabled" } else { "disabled" }
+        );
+    }
+}
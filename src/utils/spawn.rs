@@ -0,0 +1,44 @@
+//! Detached process launching for click actions
+//!
+//! Click and scroll handlers fire and forget external programs, which
+//! is easy to get subtly wrong: children inherit the bar's session and
+//! die with it, and unreaped ones linger as zombies. These helpers
+//! start the process in its own session, reap it in the background and
+//! log failures instead of surfacing them
+
+use log::warn;
+use std::{os::unix::process::CommandExt, process::Command};
+
+/// Launches an already built [Command] detached from the bar,
+/// `name` is only used in log messages
+pub fn spawn_command(mut command: Command, name: impl Into<String>) {
+    let name = name.into();
+    unsafe {
+        command.pre_exec(|| {
+            // a session of its own, so the child survives the bar
+            if libc::setsid() < 0 {
+                return Err(std::io::Error::last_os_error());
+            }
+            Ok(())
+        });
+    }
+    match command.spawn() {
+        Ok(mut child) => {
+            // reap the child so it does not linger as a zombie
+            std::thread::spawn(move || match child.wait() {
+                Ok(status) if !status.success() => warn!("`{name}` exited with {status}"),
+                Ok(_) => {}
+                Err(e) => warn!("failed to wait on `{name}`: {e}"),
+            });
+        }
+        Err(e) => warn!("failed to spawn `{name}`: {e}"),
+    }
+}
+
+/// Runs `command` through `sh -c`, detached
+pub fn spawn_sh(command: impl Into<String>) {
+    let command = command.into();
+    let mut sh = Command::new("sh");
+    sh.args(["-c", &command]);
+    spawn_command(sh, command);
+}
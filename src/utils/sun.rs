@@ -0,0 +1,70 @@
+//! Sunrise and sunset times from geographic coordinates
+//!
+//! A plain implementation of the sunrise equation
+//! (<https://en.wikipedia.org/wiki/Sunrise_equation>), accurate to a
+//! couple of minutes which is plenty for switching a palette, used by
+//! [day_night_themes](crate::statusbar::StatusBarBuilder::day_night_themes)
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Seconds since the unix epoch
+pub fn unix_now() -> f64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs_f64())
+        .unwrap_or(0.0)
+}
+
+/// Today's sunrise and sunset, as unix timestamps
+#[derive(Debug, Clone, Copy)]
+pub struct SunTimes {
+    pub sunrise: f64,
+    pub sunset: f64,
+}
+
+impl SunTimes {
+    pub fn is_day(&self, now: f64) -> bool {
+        (self.sunrise..self.sunset).contains(&now)
+    }
+
+    /// Seconds until the next sunrise or sunset
+    pub fn next_transition(&self, now: f64) -> f64 {
+        if now < self.sunrise {
+            self.sunrise - now
+        } else if now < self.sunset {
+            self.sunset - now
+        } else {
+            // tomorrow's sunrise moves by a couple of minutes at
+            // most and the scheduler recomputes it on wake anyway
+            self.sunrise + 86_400.0 - now
+        }
+    }
+}
+
+/// The sunrise equation for the day containing `now`,
+/// `longitude` is positive going east
+pub fn sun_times(latitude: f64, longitude: f64, now: f64) -> SunTimes {
+    const UNIX_TO_JULIAN: f64 = 2_440_587.5;
+    const J2000: f64 = 2_451_545.0;
+    let julian = now / 86_400.0 + UNIX_TO_JULIAN;
+    let days = (julian - J2000 + 0.000_8).ceil();
+    let mean_solar = days - longitude / 360.0;
+    let anomaly = (357.5291 + 0.985_600_28 * mean_solar).rem_euclid(360.0);
+    let center = 1.9148 * anomaly.to_radians().sin()
+        + 0.02 * (2.0 * anomaly).to_radians().sin()
+        + 0.000_3 * (3.0 * anomaly).to_radians().sin();
+    let ecliptic = (anomaly + center + 180.0 + 102.9372).rem_euclid(360.0);
+    let transit = J2000 + mean_solar + 0.0053 * anomaly.to_radians().sin()
+        - 0.0069 * (2.0 * ecliptic).to_radians().sin();
+    let declination = (ecliptic.to_radians().sin() * 23.4397_f64.to_radians().sin()).asin();
+    // clamped so polar day and night degenerate to all day or all night
+    let hour_cos = (((-0.833_f64).to_radians().sin()
+        - latitude.to_radians().sin() * declination.sin())
+        / (latitude.to_radians().cos() * declination.cos()))
+    .clamp(-1.0, 1.0);
+    let hour_angle = hour_cos.acos().to_degrees();
+    SunTimes {
+        sunrise: (transit - hour_angle / 360.0 - UNIX_TO_JULIAN) * 86_400.0,
+        sunset: (transit + hour_angle / 360.0 - UNIX_TO_JULIAN) * 86_400.0,
+    }
+}
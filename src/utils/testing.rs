@@ -0,0 +1,188 @@
+//! Deterministic stand-ins for the widget providers
+//!
+//! Enabled by the `testing` feature, meant for unit tests of
+//! custom widgets and of the built-ins: the mocks answer from
+//! in-memory state and never touch the system
+
+use crate::{
+    utils::{HookEvent, HookSender},
+    widgets::{Meteo, VolumeProvider, WeatherProvider},
+};
+use async_channel::{unbounded, Receiver, Sender};
+use async_trait::async_trait;
+use std::{
+    fs::{create_dir_all, remove_dir_all, write},
+    path::{Path, PathBuf},
+    process,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+};
+
+/// A [VolumeProvider] backed by plain fields
+///
+/// `set_volume` and `set_muted` change what the next query returns
+#[derive(Debug)]
+pub struct MockVolumeProvider {
+    state: Mutex<(f64, bool)>,
+}
+
+impl MockVolumeProvider {
+    pub fn new(volume: f64, muted: bool) -> Self {
+        Self {
+            state: Mutex::new((volume, muted)),
+        }
+    }
+
+    pub fn set_volume(&self, volume: f64) {
+        self.state.lock().unwrap().0 = volume;
+    }
+
+    pub fn set_muted(&self, muted: bool) {
+        self.state.lock().unwrap().1 = muted;
+    }
+}
+
+#[async_trait]
+impl VolumeProvider for MockVolumeProvider {
+    async fn volume(&self) -> Option<f64> {
+        Some(self.state.lock().unwrap().0)
+    }
+
+    async fn muted(&self) -> Option<bool> {
+        Some(self.state.lock().unwrap().1)
+    }
+
+    async fn volume_and_muted(&self) -> Option<(f64, bool)> {
+        Some(*self.state.lock().unwrap())
+    }
+}
+
+/// A [WeatherProvider] that always reports the same [Meteo]
+#[derive(Debug)]
+pub struct MockWeatherProvider {
+    meteo: Mutex<Meteo>,
+}
+
+impl MockWeatherProvider {
+    pub fn new(meteo: Meteo) -> Self {
+        Self {
+            meteo: Mutex::new(meteo),
+        }
+    }
+
+    pub fn set_meteo(&self, meteo: Meteo) {
+        *self.meteo.lock().unwrap() = meteo;
+    }
+}
+
+#[async_trait]
+impl WeatherProvider for MockWeatherProvider {
+    async fn get_current_meteo(&self) -> crate::widgets::Result<Meteo> {
+        Ok(self.meteo.lock().unwrap().clone())
+    }
+}
+
+/// Builds a throwaway directory shaped like the sysfs trees the
+/// widgets read (`/sys/class/backlight`, `/sys/class/power_supply`)
+///
+/// The tree is removed on drop
+#[derive(Debug)]
+pub struct FakeSysfs {
+    root: PathBuf,
+}
+
+impl FakeSysfs {
+    pub fn new() -> std::io::Result<Self> {
+        static COUNTER: AtomicU64 = AtomicU64::new(0);
+        let root = std::env::temp_dir().join(format!(
+            "barust-fake-sysfs-{}-{}",
+            process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed),
+        ));
+        create_dir_all(&root)?;
+        Ok(Self { root })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.root
+    }
+
+    /// Adds `class/backlight/<device>` with `brightness` and `max_brightness`
+    pub fn backlight(&self, device: &str, brightness: u64, max: u64) -> std::io::Result<PathBuf> {
+        let folder = self.root.join("class/backlight").join(device);
+        create_dir_all(&folder)?;
+        write(folder.join("brightness"), format!("{brightness}\n"))?;
+        write(folder.join("max_brightness"), format!("{max}\n"))?;
+        Ok(folder)
+    }
+
+    /// Adds `class/power_supply/<name>` with `capacity` and `status`
+    pub fn battery(&self, name: &str, capacity: u8, status: &str) -> std::io::Result<PathBuf> {
+        let folder = self.root.join("class/power_supply").join(name);
+        create_dir_all(&folder)?;
+        write(folder.join("capacity"), format!("{capacity}\n"))?;
+        write(folder.join("status"), format!("{status}\n"))?;
+        Ok(folder)
+    }
+}
+
+impl Drop for FakeSysfs {
+    fn drop(&mut self) {
+        let _ = remove_dir_all(&self.root);
+    }
+}
+
+/// A [TimedHooks](crate::utils::TimedHooks) replacement driven by the test
+///
+/// Nothing fires on its own: `tick` wakes every subscribed hook
+/// once and the events end up in [events](ManualHooks::events)
+#[derive(Debug)]
+pub struct ManualHooks {
+    raw_sender: Sender<HookEvent>,
+    senders: Vec<HookSender>,
+    events: Receiver<HookEvent>,
+    next_id: usize,
+}
+
+impl ManualHooks {
+    pub fn new() -> Self {
+        let (raw_sender, events) = unbounded();
+        Self {
+            raw_sender,
+            senders: Vec::new(),
+            events,
+            next_id: 0,
+        }
+    }
+
+    /// A fresh [HookSender] to hand to a widget `hook`
+    pub fn sender(&mut self) -> HookSender {
+        let id = self.next_id;
+        self.next_id += 1;
+        HookSender::new(self.raw_sender.clone(), id)
+    }
+
+    pub fn subscribe(&mut self, sender: HookSender) {
+        self.senders.push(sender);
+    }
+
+    /// Fires every subscribed hook once
+    pub async fn tick(&self) {
+        for sender in &self.senders {
+            let _ = sender.send().await;
+        }
+    }
+
+    /// The events produced by the widgets so far
+    pub fn events(&self) -> &Receiver<HookEvent> {
+        &self.events
+    }
+}
+
+impl Default for ManualHooks {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,128 @@
+use super::Color;
+use std::{collections::HashMap, io, path::PathBuf};
+
+fn home_dir() -> io::Result<PathBuf> {
+    std::env::var_os("HOME")
+        .map(PathBuf::from)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "HOME is not set"))
+}
+
+/// A named color palette shared by the bar and its widgets
+#[derive(Debug, Clone)]
+pub struct Theme {
+    pub background: Color,
+    pub foreground: Color,
+    pub accent: Color,
+    pub warning: Color,
+    pub critical: Color,
+    ///per-widget foreground overrides, keyed by the widget [std::fmt::Display] name
+    pub overrides: HashMap<String, Color>,
+}
+
+impl Theme {
+    /// Foreground color for a widget, honoring overrides
+    pub fn fg_for(&self, widget_name: &str) -> Color {
+        self.overrides
+            .get(widget_name)
+            .copied()
+            .unwrap_or(self.foreground)
+    }
+
+    /// Sets a per-widget foreground override
+    pub fn with_override(mut self, widget_name: impl ToString, color: Color) -> Self {
+        self.overrides.insert(widget_name.to_string(), color);
+        self
+    }
+
+    /// Loads colors from the pywal cache file (`~/.cache/wal/colors`)
+    pub fn from_pywal() -> io::Result<Self> {
+        let cache = std::env::var("XDG_CACHE_HOME")
+            .map(PathBuf::from)
+            .or_else(|_| home_dir().map(|home| home.join(".cache")))?;
+        let content = std::fs::read_to_string(cache.join("wal/colors"))?;
+        let colors: Vec<Color> = content.lines().filter_map(Color::from_hex).collect();
+        if colors.len() < 8 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "pywal cache has fewer than 8 colors",
+            ));
+        }
+        Ok(Self {
+            background: colors[0],
+            foreground: colors[7],
+            accent: colors[4],
+            warning: colors[3],
+            critical: colors[1],
+            overrides: HashMap::new(),
+        })
+    }
+
+    /// Loads colors from `~/.Xresources`
+    pub fn from_xresources() -> io::Result<Self> {
+        let content = std::fs::read_to_string(home_dir()?.join(".Xresources"))?;
+        let mut theme = Self::default();
+        for line in content.lines() {
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            let Some(color) = Color::from_hex(value.trim()) else {
+                continue;
+            };
+            match key.trim().rsplit(['.', '*']).next().unwrap_or(key) {
+                "background" => theme.background = color,
+                "foreground" => theme.foreground = color,
+                "color1" => theme.critical = color,
+                "color3" => theme.warning = color,
+                "color4" => theme.accent = color,
+                _ => (),
+            }
+        }
+        Ok(theme)
+    }
+
+    pub fn gruvbox() -> Self {
+        Self {
+            background: Color::from_hex("#282828").unwrap(),
+            foreground: Color::from_hex("#ebdbb2").unwrap(),
+            accent: Color::from_hex("#d79921").unwrap(),
+            warning: Color::from_hex("#fe8019").unwrap(),
+            critical: Color::from_hex("#cc241d").unwrap(),
+            overrides: HashMap::new(),
+        }
+    }
+
+    pub fn nord() -> Self {
+        Self {
+            background: Color::from_hex("#2e3440").unwrap(),
+            foreground: Color::from_hex("#d8dee9").unwrap(),
+            accent: Color::from_hex("#88c0d0").unwrap(),
+            warning: Color::from_hex("#ebcb8b").unwrap(),
+            critical: Color::from_hex("#bf616a").unwrap(),
+            overrides: HashMap::new(),
+        }
+    }
+
+    pub fn dracula() -> Self {
+        Self {
+            background: Color::from_hex("#282a36").unwrap(),
+            foreground: Color::from_hex("#f8f8f2").unwrap(),
+            accent: Color::from_hex("#bd93f9").unwrap(),
+            warning: Color::from_hex("#ffb86c").unwrap(),
+            critical: Color::from_hex("#ff5555").unwrap(),
+            overrides: HashMap::new(),
+        }
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            background: Color::new(0.0, 0.0, 0.0, 1.0),
+            foreground: Color::new(1.0, 1.0, 1.0, 1.0),
+            accent: Color::new(0.8, 0.0, 1.0, 1.0),
+            warning: Color::new(1.0, 0.6, 0.0, 1.0),
+            critical: Color::new(1.0, 0.0, 0.0, 1.0),
+            overrides: HashMap::new(),
+        }
+    }
+}
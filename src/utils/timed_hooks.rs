@@ -1,4 +1,4 @@
-use super::hook_sender::HookSender;
+use super::{hook_sender::HookSender, power};
 use log::{debug, error};
 use std::time::Duration;
 use tokio::{task::spawn, time::sleep};
@@ -25,6 +25,12 @@ impl TimedHooks {
                     error!("breaking thread loop");
                 }
 
+                // drop the tick rate while the machine runs on battery
+                let duration = if power::low_power() {
+                    duration * power::LOW_POWER_MULTIPLIER
+                } else {
+                    duration
+                };
                 sleep(duration).await;
                 debug!("waking from sleep");
             }
@@ -1,17 +1,22 @@
 use crate::{
-    utils::{Atoms, HookSender, TimedHooks},
+    utils::{atoms::intern_atom, shared_connection, Atoms, HookSender, StatusBarInfo, TimedHooks},
     widget_default,
-    widgets::{Result, Text, Widget, WidgetConfig},
+    widgets::{ClickEvent, MouseButton, Result, Text, Widget, WidgetConfig},
 };
 use async_trait::async_trait;
 use log::{debug, error};
-use std::{fmt::Display, sync::Arc, thread};
+use std::{
+    fmt::Display,
+    sync::Arc,
+    thread,
+    time::{Duration, Instant},
+};
 use xcb::{
     x::{ChangeWindowAttributes, Cw, Event, EventMask, Window},
-    Connection, XidNew,
+    Connection, Xid, XidNew,
 };
 
-pub fn get_active_window_name(connection: &Connection) -> Result<String> {
+pub fn get_active_window(connection: &Connection) -> Result<Window> {
     let atoms = Atoms::new(connection).map_err(Error::from)?;
     let cookie = connection.send_request(&xcb::x::GetProperty {
         delete: false,
@@ -22,11 +27,16 @@ pub fn get_active_window_name(connection: &Connection) -> Result<String> {
         long_length: u32::MAX,
     });
     let reply = connection.wait_for_reply(cookie).map_err(Error::Xcb)?;
-    let active_window_id = reply
+    reply
         .value::<u32>()
         .first()
         .map(|data| unsafe { Window::new(*data) })
-        .ok_or(Error::Ewmh)?;
+        .ok_or_else(|| Error::Ewmh.into())
+}
+
+pub fn get_active_window_name(connection: &Connection) -> Result<String> {
+    let atoms = Atoms::new(connection).map_err(Error::from)?;
+    let active_window_id = get_active_window(connection)?;
 
     let cookie = connection.send_request(&xcb::x::GetProperty {
         delete: false,
@@ -40,9 +50,99 @@ pub fn get_active_window_name(connection: &Connection) -> Result<String> {
     String::from_utf8(reply.value::<u8>().into()).map_err(|_| Error::Ewmh.into())
 }
 
+/// WM_CLASS of the active window
+pub fn get_active_window_class(connection: &Connection) -> Result<String> {
+    let active_window_id = get_active_window(connection)?;
+    let cookie = connection.send_request(&xcb::x::GetProperty {
+        delete: false,
+        window: active_window_id,
+        property: xcb::x::ATOM_WM_CLASS,
+        r#type: xcb::x::ATOM_STRING,
+        long_offset: 0,
+        long_length: u32::MAX,
+    });
+    let reply = connection.wait_for_reply(cookie).map_err(Error::Xcb)?;
+    // the property holds "instance\0class\0"
+    reply
+        .value::<u8>()
+        .split(|c| *c == 0)
+        .nth(1)
+        .map(|class| String::from_utf8_lossy(class).to_string())
+        .ok_or_else(|| Error::Ewmh.into())
+}
+
+/// Maps WM_CLASS values to icon glyphs, so the window identity
+/// stays visible even when the title is truncated
+///
+/// Classes are matched case-insensitively, the first match wins
+#[derive(Debug, Default)]
+pub struct ClassIconMap {
+    icons: Vec<(String, String)>,
+    fallback: String,
+}
+
+impl ClassIconMap {
+    ///* `fallback` glyph used when no class matches
+    pub fn new(fallback: impl ToString) -> Self {
+        Self {
+            icons: Vec::new(),
+            fallback: fallback.to_string(),
+        }
+    }
+
+    /// Shows `glyph` when the active window class contains `class`
+    pub fn icon(mut self, class: impl ToString, glyph: impl ToString) -> Self {
+        self.icons
+            .push((class.to_string().to_lowercase(), glyph.to_string()));
+        self
+    }
+
+    fn glyph_for(&self, class: &str) -> &str {
+        let class = class.to_lowercase();
+        self.icons
+            .iter()
+            .find(|(c, _)| class.contains(c.as_str()))
+            .map(|(_, glyph)| glyph.as_str())
+            .unwrap_or(&self.fallback)
+    }
+}
+
+/// What a mouse binding on [ActiveWindow] does to the active window
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WindowAction {
+    /// close it via `_NET_CLOSE_WINDOW`
+    Close,
+    /// toggle `_NET_WM_STATE_MAXIMIZED_{HORZ,VERT}`
+    ToggleMaximize,
+}
+
+/// Two clicks closer than this count as a double click
+const DOUBLE_CLICK: Duration = Duration::from_millis(400);
+
+/// The screen area covered by a randr CRTC
+#[derive(Debug, Clone, Copy)]
+struct MonitorBounds {
+    x: i32,
+    y: i32,
+    width: i32,
+    height: i32,
+}
+
+impl MonitorBounds {
+    fn contains(&self, x: i32, y: i32) -> bool {
+        (self.x..self.x + self.width).contains(&x) && (self.y..self.y + self.height).contains(&y)
+    }
+}
+
 pub struct ActiveWindow {
     inner: Text,
-    connection: Connection,
+    connection: Arc<Connection>,
+    per_monitor: bool,
+    monitor: Option<MonitorBounds>,
+    class_icons: Option<ClassIconMap>,
+    actions: Vec<(MouseButton, WindowAction)>,
+    double_actions: Vec<(MouseButton, WindowAction)>,
+    last_click: Option<(MouseButton, Instant)>,
 }
 
 impl std::fmt::Debug for ActiveWindow {
@@ -53,22 +153,214 @@ impl std::fmt::Debug for ActiveWindow {
 
 impl ActiveWindow {
     pub async fn new(config: &WidgetConfig) -> Result<Box<Self>> {
-        let (connection, _) = Connection::connect(None).map_err(Error::from)?;
+        let (connection, _) = shared_connection().map_err(Error::from)?;
         Ok(Box::new(Self {
             inner: *Text::new("", config).await,
             connection,
+            per_monitor: false,
+            monitor: None,
+            class_icons: None,
+            actions: Vec::new(),
+            double_actions: Vec::new(),
+            last_click: None,
         }))
     }
+
+    /// Scrolls long window titles, see [Marquee](crate::widgets::Marquee)
+    pub fn with_marquee(mut self: Box<Self>, marquee: crate::widgets::Marquee) -> Box<Self> {
+        self.inner.set_marquee(marquee);
+        self
+    }
+
+    /// Renders a [ClassIconMap] glyph before the window title
+    pub fn with_class_icons(mut self: Box<Self>, icons: ClassIconMap) -> Box<Self> {
+        self.class_icons = Some(icons);
+        self
+    }
+
+    /// Only shows the active window when it is on the same monitor
+    /// as the bar (for multi-monitor setups with one bar per screen)
+    pub fn per_monitor(mut self: Box<Self>, per_monitor: bool) -> Box<Self> {
+        self.per_monitor = per_monitor;
+        self
+    }
+
+    /// Runs `action` on the active window when `button` is clicked
+    /// (e.g. middle click closes it)
+    pub fn with_action(mut self: Box<Self>, button: MouseButton, action: WindowAction) -> Box<Self> {
+        self.actions.push((button, action));
+        self
+    }
+
+    /// Runs `action` on a quick double click of `button`
+    /// (e.g. double left click toggles maximize)
+    pub fn with_double_click_action(
+        mut self: Box<Self>,
+        button: MouseButton,
+        action: WindowAction,
+    ) -> Box<Self> {
+        self.double_actions.push((button, action));
+        self
+    }
+
+    /// Runs `action` on the active window
+    fn run_action(&self, action: WindowAction) -> Result<()> {
+        let window = get_active_window(&self.connection)?;
+        let atoms = Atoms::new(&self.connection).map_err(Error::from)?;
+        let event = match action {
+            WindowAction::Close => xcb::x::ClientMessageEvent::new(
+                window,
+                atoms._NET_CLOSE_WINDOW,
+                // [timestamp, source indication: direct user action (a pager)]
+                xcb::x::ClientMessageData::Data32([xcb::x::CURRENT_TIME, 2, 0, 0, 0]),
+            ),
+            WindowAction::ToggleMaximize => {
+                let state = intern_atom(&self.connection, "_NET_WM_STATE").map_err(Error::from)?;
+                let horz = intern_atom(&self.connection, "_NET_WM_STATE_MAXIMIZED_HORZ")
+                    .map_err(Error::from)?;
+                let vert = intern_atom(&self.connection, "_NET_WM_STATE_MAXIMIZED_VERT")
+                    .map_err(Error::from)?;
+                xcb::x::ClientMessageEvent::new(
+                    window,
+                    state,
+                    // [toggle, first property, second property, source]
+                    xcb::x::ClientMessageData::Data32([
+                        2,
+                        horz.resource_id(),
+                        vert.resource_id(),
+                        2,
+                        0,
+                    ]),
+                )
+            }
+        };
+        let root = self.connection.get_setup().roots().next().unwrap().root();
+        self.connection
+            .send_and_check_request(&xcb::x::SendEvent {
+                propagate: false,
+                destination: xcb::x::SendEventDest::Window(root),
+                event_mask: EventMask::SUBSTRUCTURE_NOTIFY | EventMask::SUBSTRUCTURE_REDIRECT,
+                event: &event,
+            })
+            .map_err(Error::from)?;
+        self.connection.flush().map_err(Error::from)?;
+        Ok(())
+    }
+
+    /// Finds the CRTC containing the given point in root coordinates
+    fn find_monitor(&self, x: i32, y: i32) -> Result<Option<MonitorBounds>> {
+        let root = self.connection.get_setup().roots().next().unwrap().root();
+        let cookie = self
+            .connection
+            .send_request(&xcb::randr::GetScreenResourcesCurrent { window: root });
+        let resources = self.connection.wait_for_reply(cookie).map_err(Error::Xcb)?;
+        for crtc in resources.crtcs() {
+            let cookie = self.connection.send_request(&xcb::randr::GetCrtcInfo {
+                crtc: *crtc,
+                config_timestamp: resources.config_timestamp(),
+            });
+            let info = self.connection.wait_for_reply(cookie).map_err(Error::Xcb)?;
+            if info.width() == 0 {
+                continue;
+            }
+            let bounds = MonitorBounds {
+                x: i32::from(info.x()),
+                y: i32::from(info.y()),
+                width: i32::from(info.width()),
+                height: i32::from(info.height()),
+            };
+            if bounds.contains(x, y) {
+                return Ok(Some(bounds));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Checks if the window center falls inside the bar monitor
+    fn window_on_monitor(&self, window: Window, monitor: &MonitorBounds) -> bool {
+        let cookie = self.connection.send_request(&xcb::x::GetGeometry {
+            drawable: xcb::x::Drawable::Window(window),
+        });
+        let Ok(geometry) = self.connection.wait_for_reply(cookie) else {
+            return false;
+        };
+        let root = self.connection.get_setup().roots().next().unwrap().root();
+        let cookie = self.connection.send_request(&xcb::x::TranslateCoordinates {
+            src_window: window,
+            dst_window: root,
+            src_x: 0,
+            src_y: 0,
+        });
+        let Ok(coordinates) = self.connection.wait_for_reply(cookie) else {
+            return false;
+        };
+        monitor.contains(
+            i32::from(coordinates.dst_x()) + i32::from(geometry.width()) / 2,
+            i32::from(coordinates.dst_y()) + i32::from(geometry.height()) / 2,
+        )
+    }
 }
 
 #[async_trait]
 impl Widget for ActiveWindow {
+    async fn setup(&mut self, info: &StatusBarInfo) -> Result<()> {
+        if !self.per_monitor {
+            return Ok(());
+        }
+        let cookie = self.connection.send_request(&xcb::x::GetGeometry {
+            drawable: xcb::x::Drawable::Window(info.window),
+        });
+        let geometry = self.connection.wait_for_reply(cookie).map_err(Error::Xcb)?;
+        self.monitor = self.find_monitor(
+            i32::from(geometry.x()) + i32::from(geometry.width()) / 2,
+            i32::from(geometry.y()) + i32::from(geometry.height()) / 2,
+        )?;
+        Ok(())
+    }
+
     async fn update(&mut self) -> Result<()> {
         debug!("updating active_window");
+        if let Some(monitor) = &self.monitor {
+            match get_active_window(&self.connection) {
+                Ok(window) if self.window_on_monitor(window, monitor) => {}
+                _ => {
+                    self.inner.set_text(String::new());
+                    return Ok(());
+                }
+            }
+        }
         if let Ok(window_name) = get_active_window_name(&self.connection) {
-            self.inner.set_text(window_name);
+            let text = match &self.class_icons {
+                Some(icons) => {
+                    let class = get_active_window_class(&self.connection).unwrap_or_default();
+                    format!("{} {}", icons.glyph_for(&class), window_name)
+                }
+                None => window_name,
+            };
+            self.inner.set_text(text);
         }
-        Ok(())
+        // advances the marquee if one is configured
+        self.inner.update().await
+    }
+
+    async fn on_click(&mut self, event: ClickEvent) -> Result<()> {
+        let now = Instant::now();
+        let double = self
+            .last_click
+            .take()
+            .is_some_and(|(button, at)| button == event.button && now - at < DOUBLE_CLICK);
+        if !double {
+            self.last_click = Some((event.button, now));
+        }
+        let bindings = if double {
+            &self.double_actions
+        } else {
+            &self.actions
+        };
+        let Some((_, action)) = bindings.iter().find(|(button, _)| *button == event.button) else {
+            return Ok(());
+        };
+        self.run_action(*action)
     }
 
     async fn hook(&mut self, sender: HookSender, timed_hooks: &mut TimedHooks) -> Result<()> {
@@ -1,11 +1,14 @@
 use crate::{
-    utils::{percentage_to_index, HookSender, TimedHooks},
+    utils::{
+        icons, metrics, notify, percentage_to_index, power, HookSender, IconSet, IconTheme,
+        ResettableTimer, StatusBarInfo, TimedHooks,
+    },
     widget_default,
     widgets::{Result, Text, Widget, WidgetConfig},
 };
 use async_trait::async_trait;
-use log::debug;
-use std::{fmt::Display, fs::read_dir};
+use log::{debug, error};
+use std::{fmt::Display, fs::read_dir, time::Duration};
 
 /// Icons used by [Battery]
 #[derive(Debug)]
@@ -29,28 +32,58 @@ impl Default for BatteryIcons {
         }
     }
 }
-/// Displays status and charge of the battery
+
+impl IconSet for BatteryIcons {
+    fn glyphs(&self) -> Vec<&str> {
+        self.percentages
+            .iter()
+            .chain(&self.percentages_charging)
+            .map(String::as_str)
+            .collect()
+    }
+
+    fn for_theme(theme: IconTheme) -> Self {
+        match theme {
+            IconTheme::NerdFont => Self::default(),
+            IconTheme::Emoji => Self {
+                percentages: ["🪫", "🪫", "🪫", "🔋", "🔋"].map(String::from).to_vec(),
+                percentages_charging: ["⚡"].map(String::from).to_vec(),
+            },
+            IconTheme::Ascii => Self {
+                percentages: ["[----]", "[#---]", "[##--]", "[###-]", "[####]"]
+                    .map(String::from)
+                    .to_vec(),
+                percentages_charging: ["[#---]+", "[##--]+", "[###-]+", "[####]+"]
+                    .map(String::from)
+                    .to_vec(),
+            },
+        }
+    }
+}
+
+#[async_trait]
+pub trait BatteryProvider: std::fmt::Debug + Send + Sync {
+    /// returns the charge percentage and whether the battery is charging
+    async fn status(&self) -> Option<(f64, bool)>;
+    /// the percentage charging stops at when the vendor firmware
+    /// caps it, None when charging is not limited
+    async fn charge_limit(&self) -> Option<u8> {
+        None
+    }
+}
+
+/// Lenovo conservation mode, caps the charge at 60%
+const CONSERVATION_MODE: &str =
+    "/sys/bus/platform/drivers/ideapad_acpi/VPC2004:00/conservation_mode";
+
+/// Reads the battery state from /sys/class/power_supply
 #[derive(Debug)]
-pub struct Battery {
-    format: String,
-    inner: Text,
+pub struct PowerSupplyProvider {
     root_path: String,
-    icons: BatteryIcons,
-    low_battery_warning: Box<dyn LowBatteryWarner>,
 }
 
-impl Battery {
-    ///* `format`
-    ///  * `%c` will be replaced with the charge percentage
-    ///  * `%i` will be replaced with the correct icon from `icons`
-    ///* `icons` sets a custom [BatteryIcons]
-    ///* `config` a [&WidgetConfig]
-    pub async fn new(
-        format: impl ToString,
-        icons: Option<BatteryIcons>,
-        config: &WidgetConfig,
-        low_battery_warning: impl LowBatteryWarner + 'static,
-    ) -> Result<Box<Self>> {
+impl PowerSupplyProvider {
+    pub async fn new() -> Result<Self> {
         let mut root_path = String::default();
         for path in read_dir("/sys/class/power_supply")
             .map_err(Error::from)?
@@ -65,14 +98,7 @@ impl Battery {
         if root_path.is_empty() {
             return Err(Error::NoBattery.into());
         }
-
-        Ok(Box::new(Self {
-            format: format.to_string(),
-            inner: *Text::new("", config).await,
-            root_path,
-            icons: icons.unwrap_or_default(),
-            low_battery_warning: Box::new(low_battery_warning),
-        }))
+        Ok(Self { root_path })
     }
 
     fn read_os_file(&self, filename: &str) -> Option<String> {
@@ -96,18 +122,93 @@ impl Battery {
     }
 }
 
+#[async_trait]
+impl BatteryProvider for PowerSupplyProvider {
+    async fn status(&self) -> Option<(f64, bool)> {
+        let percent = match (self.get_charge(), self.get_energy()) {
+            (Some(c), _) => c,
+            (None, Some(e)) => e,
+            (None, None) => return None,
+        };
+        let is_charging = self.read_os_file("status") == Some("Charging".into());
+        Some((percent, is_charging))
+    }
+
+    async fn charge_limit(&self) -> Option<u8> {
+        if let Some(threshold) = self.read_os_file("charge_control_end_threshold") {
+            let threshold = threshold.parse().ok()?;
+            return (threshold < 100).then_some(threshold);
+        }
+        let conservation = std::fs::read_to_string(CONSERVATION_MODE).ok()?;
+        (conservation.trim() == "1").then_some(60)
+    }
+}
+
+/// Displays status and charge of the battery
+#[derive(Debug)]
+pub struct Battery {
+    format: String,
+    inner: Text,
+    provider: Box<dyn BatteryProvider>,
+    icons: BatteryIcons,
+    low_battery_warning: Box<dyn LowBatteryWarner>,
+    // discharging below this percentage enables low-power mode
+    low_power_threshold: Option<f64>,
+}
+
+impl Battery {
+    ///* `format`
+    ///  * `%c` will be replaced with the charge percentage
+    ///  * `%i` will be replaced with the correct icon from `icons`
+    ///  * `%l` will be replaced with the vendor charge limit
+    ///    (e.g. `80`) when charging is capped, empty otherwise
+    ///* `provider` implements [BatteryProvider]
+    ///* `icons` sets a custom [BatteryIcons]
+    ///* `config` a [&WidgetConfig]
+    pub async fn new(
+        format: impl ToString,
+        provider: Box<impl BatteryProvider + 'static>,
+        icons: Option<BatteryIcons>,
+        config: &WidgetConfig,
+        low_battery_warning: impl LowBatteryWarner + 'static,
+    ) -> Result<Box<Self>> {
+        Ok(Box::new(Self {
+            format: format.to_string(),
+            inner: *Text::new("", config).await,
+            provider,
+            icons: icons.unwrap_or_default(),
+            low_battery_warning: Box::new(low_battery_warning),
+            low_power_threshold: None,
+        }))
+    }
+
+    /// Enables [low power mode](crate::utils::power) while the battery
+    /// discharges below `threshold` percent, slowing every timed hook
+    /// and the draw loop until the charger is back
+    pub fn with_low_power_threshold(mut self: Box<Self>, threshold: f64) -> Box<Self> {
+        self.low_power_threshold = Some(threshold);
+        self
+    }
+}
+
 #[async_trait]
 impl Widget for Battery {
+    async fn setup(&mut self, _info: &StatusBarInfo) -> Result<()> {
+        icons::warn_missing_glyphs(&self.icons, self.inner.font(), "Battery");
+        Ok(())
+    }
+
     async fn update(&mut self) -> Result<()> {
         debug!("updating battery");
-        let percent = match (self.get_charge(), self.get_energy()) {
-            (Some(c), Some(_)) => c,
-            (Some(c), None) => c,
-            (None, Some(e)) => e,
-            (None, None) => return Ok(()),
+        let Some((percent, is_charging)) = self.provider.status().await else {
+            return Ok(());
         };
+        metrics::gauge("battery_percent", percent);
+        metrics::gauge("battery_charging", f64::from(is_charging));
 
-        let is_charging = self.read_os_file("status") == Some("Charging".into());
+        if let Some(threshold) = self.low_power_threshold {
+            power::set_low_power(!is_charging && percent < threshold);
+        }
 
         if self.low_battery_warning.should_warn(percent, is_charging) {
             let f = self.low_battery_warning.warn(percent);
@@ -126,10 +227,15 @@ impl Widget for Battery {
             &percentages[index]
         };
 
-        let text = self
+        let mut text = self
             .format
             .replace("%i", icon)
             .replace("%c", &percent.round().to_string());
+        // only poke the limit files when the specifier is used
+        if text.contains("%l") {
+            let limit = self.provider.charge_limit().await;
+            text = text.replace("%l", &limit.map(|l| l.to_string()).unwrap_or_default());
+        }
         self.inner.set_text(text);
         Ok(())
     }
@@ -156,27 +262,52 @@ pub trait LowBatteryWarner: Send + std::fmt::Debug {
 
 #[derive(Debug)]
 pub struct NotifySend {
-    warn_20: bool,
-    warn_5: bool,
+    ///thresholds in descending order, the last one is critical
+    thresholds: Vec<f64>,
+    warned: Vec<bool>,
+    rewarn: Option<ResettableTimer>,
+}
+
+impl NotifySend {
+    ///* `thresholds` charge percentages that trigger a warning, in descending order
+    ///* `rewarn` if set, repeat the warning at this interval while below a threshold
+    pub fn new(thresholds: Vec<f64>, rewarn: Option<Duration>) -> Self {
+        let warned = vec![false; thresholds.len()];
+        Self {
+            thresholds,
+            warned,
+            rewarn: rewarn.map(ResettableTimer::new),
+        }
+    }
+
+    fn critical(&self) -> f64 {
+        self.thresholds.last().copied().unwrap_or(5.0)
+    }
 }
 
 #[async_trait]
 impl LowBatteryWarner for NotifySend {
     fn should_warn(&mut self, charge: f64, is_charging: bool) -> bool {
         if is_charging {
-            self.warn_20 = false;
-            self.warn_5 = false;
+            self.warned.fill(false);
             return false;
         }
 
-        if charge < 20.0 && !self.warn_20 {
-            self.warn_20 = true;
-            return true;
+        for (threshold, warned) in self.thresholds.iter().zip(self.warned.iter_mut()) {
+            if charge < *threshold && !*warned {
+                *warned = true;
+                if let Some(rewarn) = &mut self.rewarn {
+                    rewarn.reset();
+                }
+                return true;
+            }
         }
 
-        if charge < 5.0 && !self.warn_5 {
-            self.warn_5 = true;
-            return true;
+        if let Some(rewarn) = &mut self.rewarn {
+            if self.warned.iter().any(|w| *w) && rewarn.is_done() {
+                rewarn.reset();
+                return true;
+            }
         }
 
         false
@@ -184,22 +315,224 @@ impl LowBatteryWarner for NotifySend {
 
     async fn warn(&self, charge: f64) {
         let body = format!("Battery is low: {:.1}% left", charge);
-        let n = libnotify::Notification::new("Low battery", Some(body.as_ref()), None);
-        n.set_urgency(if charge < 5.0 {
+        let urgency = if charge < self.critical() {
             libnotify::Urgency::Critical
         } else {
             libnotify::Urgency::Normal
-        });
-        n.show().expect("failed to show notification");
+        };
+        notify::notify("Low battery", &body, urgency);
     }
 }
 
 impl Default for NotifySend {
     fn default() -> Self {
-        libnotify::init("barust").expect("libnotify init failed");
+        Self::new(vec![20.0, 5.0], None)
+    }
+}
+
+/// Runs a user command (e.g. `systemctl suspend`) when the charge
+/// drops below a critical threshold
+#[derive(Debug)]
+pub struct SuspendAction {
+    critical: f64,
+    command: String,
+    triggered: bool,
+}
+
+impl SuspendAction {
+    ///* `critical` charge percentage below which the command is run
+    ///* `command` shell command to run (e.g. `systemctl suspend`)
+    pub fn new(critical: f64, command: impl ToString) -> Self {
         Self {
-            warn_20: false,
-            warn_5: false,
+            critical,
+            command: command.to_string(),
+            triggered: false,
+        }
+    }
+}
+
+#[async_trait]
+impl LowBatteryWarner for SuspendAction {
+    fn should_warn(&mut self, charge: f64, is_charging: bool) -> bool {
+        if is_charging {
+            self.triggered = false;
+            return false;
+        }
+
+        if charge < self.critical && !self.triggered {
+            self.triggered = true;
+            return true;
+        }
+
+        false
+    }
+
+    async fn warn(&self, charge: f64) {
+        debug!("battery at {:.1}%, running `{}`", charge, self.command);
+        if let Err(e) = tokio::process::Command::new("sh")
+            .args(["-c", &self.command])
+            .spawn()
+        {
+            error!("failed to run `{}`: {}", self.command, e);
+        }
+    }
+}
+
+#[cfg(feature = "upower")]
+pub mod upower {
+    use super::{BatteryProvider, Error, Result};
+    use crate::{
+        utils::{HookSender, TimedHooks},
+        widget_default,
+        widgets::{Text, Widget, WidgetConfig},
+    };
+    use async_trait::async_trait;
+    use log::debug;
+    use std::fmt::Display;
+    use zbus::zvariant::OwnedObjectPath;
+
+    const UPOWER_DEST: &str = "org.freedesktop.UPower";
+    const DEVICE_INTERFACE: &str = "org.freedesktop.UPower.Device";
+
+    // device types from the UPower spec
+    const TYPE_MOUSE: u32 = 5;
+    const TYPE_KEYBOARD: u32 = 6;
+    const TYPE_HEADSET: u32 = 17;
+
+    // battery states from the UPower spec
+    const STATE_CHARGING: u32 = 1;
+
+    async fn device_proxy<'a>(
+        connection: &zbus::Connection,
+        path: OwnedObjectPath,
+    ) -> zbus::Result<zbus::Proxy<'a>> {
+        zbus::Proxy::new(connection, UPOWER_DEST, path, DEVICE_INTERFACE).await
+    }
+
+    /// Reads the battery state from UPower over D-Bus
+    #[derive(Debug)]
+    pub struct UpowerProvider {
+        connection: zbus::Connection,
+    }
+
+    impl UpowerProvider {
+        pub async fn new() -> Result<Self> {
+            let connection = zbus::Connection::system().await.map_err(Error::from)?;
+            Ok(Self { connection })
+        }
+    }
+
+    #[async_trait]
+    impl BatteryProvider for UpowerProvider {
+        async fn status(&self) -> Option<(f64, bool)> {
+            let proxy = device_proxy(
+                &self.connection,
+                OwnedObjectPath::try_from("/org/freedesktop/UPower/devices/DisplayDevice").ok()?,
+            )
+            .await
+            .ok()?;
+            let percentage: f64 = proxy.get_property("Percentage").await.ok()?;
+            let state: u32 = proxy.get_property("State").await.ok()?;
+            Some((percentage, state == STATE_CHARGING))
+        }
+    }
+
+    /// Icons used by [Peripherals]
+    #[derive(Debug)]
+    pub struct PeripheralIcons {
+        pub mouse: String,
+        pub keyboard: String,
+        pub headset: String,
+    }
+
+    impl Default for PeripheralIcons {
+        fn default() -> Self {
+            Self {
+                mouse: String::from("󰍽"),
+                keyboard: String::from("󰌌"),
+                headset: String::from("󰋎"),
+            }
+        }
+    }
+
+    /// Displays the charge of wireless peripherals known to UPower
+    #[derive(Debug)]
+    pub struct Peripherals {
+        separator: String,
+        icons: PeripheralIcons,
+        inner: Text,
+        connection: zbus::Connection,
+    }
+
+    impl Peripherals {
+        ///* `separator` placed between each device
+        ///* `icons` sets a custom [PeripheralIcons]
+        ///* `config` a [&WidgetConfig]
+        pub async fn new(
+            separator: impl ToString,
+            icons: Option<PeripheralIcons>,
+            config: &WidgetConfig,
+        ) -> Result<Box<Self>> {
+            let connection = zbus::Connection::system().await.map_err(Error::from)?;
+            Ok(Box::new(Self {
+                separator: separator.to_string(),
+                icons: icons.unwrap_or_default(),
+                inner: *Text::new("", config).await,
+                connection,
+            }))
+        }
+
+        async fn read_devices(&self) -> zbus::Result<Vec<String>> {
+            let upower = zbus::Proxy::new(
+                &self.connection,
+                UPOWER_DEST,
+                "/org/freedesktop/UPower",
+                UPOWER_DEST,
+            )
+            .await?;
+            let paths: Vec<OwnedObjectPath> = upower.call("EnumerateDevices", &()).await?;
+
+            let mut devices = Vec::new();
+            for path in paths {
+                let proxy = device_proxy(&self.connection, path).await?;
+                let Ok(device_type) = proxy.get_property::<u32>("Type").await else {
+                    continue;
+                };
+                let icon = match device_type {
+                    TYPE_MOUSE => &self.icons.mouse,
+                    TYPE_KEYBOARD => &self.icons.keyboard,
+                    TYPE_HEADSET => &self.icons.headset,
+                    _ => continue,
+                };
+                let Ok(percentage) = proxy.get_property::<f64>("Percentage").await else {
+                    continue;
+                };
+                devices.push(format!("{} {}󱉸", icon, percentage.round()));
+            }
+            Ok(devices)
+        }
+    }
+
+    #[async_trait]
+    impl Widget for Peripherals {
+        async fn update(&mut self) -> Result<()> {
+            debug!("updating peripherals");
+            let devices = self.read_devices().await.map_err(Error::from)?;
+            self.inner.set_text(devices.join(&self.separator));
+            Ok(())
+        }
+
+        async fn hook(&mut self, sender: HookSender, timed_hooks: &mut TimedHooks) -> Result<()> {
+            timed_hooks.subscribe(sender);
+            Ok(())
+        }
+
+        widget_default!(draw, size, padding);
+    }
+
+    impl Display for Peripherals {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            String::from("Peripherals").fmt(f)
         }
     }
 }
@@ -210,4 +543,6 @@ pub enum Error {
     IO(#[from] std::io::Error),
     #[error("No battery found")]
     NoBattery,
+    #[cfg(feature = "upower")]
+    Zbus(#[from] zbus::Error),
 }
@@ -1,16 +1,21 @@
 use crate::{
-    utils::{percentage_to_index, HookSender, ResettableTimer, TimedHooks},
+    utils::{
+        icons, percentage_to_index, set_source_rgba, Color, HookSender, IconSet, IconTheme,
+        ResettableTimer, StatusBarInfo, TimedHooks,
+    },
     widget_default,
-    widgets::{Result, Text, Widget, WidgetConfig},
+    widgets::{ClickEvent, MouseButton, OsdBar, Rectangle, Result, Size, Text, Widget, WidgetConfig},
 };
 use async_trait::async_trait;
+use cairo::Context;
 use futures::StreamExt;
 use inotify::Inotify;
 use log::{debug, error};
-use std::{fmt::Display, fs, io::SeekFrom, path::PathBuf};
+use std::{fmt::Display, fs, io::SeekFrom, path::PathBuf, time::Duration};
 use tokio::{
     fs::File,
     io::{AsyncReadExt, AsyncSeekExt},
+    process::Command,
     spawn,
     sync::Mutex,
     time::sleep,
@@ -31,54 +36,61 @@ impl Default for BrightnessIcons {
     }
 }
 
+impl IconSet for BrightnessIcons {
+    fn glyphs(&self) -> Vec<&str> {
+        self.percentages.iter().map(String::as_str).collect()
+    }
+
+    fn for_theme(theme: IconTheme) -> Self {
+        match theme {
+            IconTheme::NerdFont => Self::default(),
+            IconTheme::Emoji => Self {
+                percentages: ["🔅", "🔆"].map(String::from).to_vec(),
+            },
+            IconTheme::Ascii => Self {
+                percentages: ["bri .", "bri o", "bri O", "bri 0"].map(String::from).to_vec(),
+            },
+        }
+    }
+}
+
+#[async_trait]
+pub trait BrightnessProvider: std::fmt::Debug + Send {
+    async fn brightness(&self) -> Option<f64>;
+    async fn set_brightness(&mut self, percent: f64) -> Option<()>;
+    async fn hook(
+        &mut self,
+        sender: HookSender,
+        pool: &mut TimedHooks,
+        _hide_timeout: Duration,
+    ) -> Result<()> {
+        pool.subscribe(sender);
+        Ok(())
+    }
+}
+
+/// Reads and writes the backlight through `/sys/class/backlight`
 #[derive(Debug)]
-pub struct Brightness {
-    format: String,
-    previous_brightness: f64,
-    show_counter: ResettableTimer,
-    inner: Text,
-    icons: BrightnessIcons,
+pub struct SysfsProvider {
     brightness_file: Mutex<File>,
     max_brightness_file: Mutex<File>,
-    device: Option<String>,
+    brightness_path: PathBuf,
 }
 
-impl Brightness {
-    ///* `format`
-    ///  * *%p* will be replaced with the brightness percentage
-    ///  * *%i* will be replaced with the correct icon
-    ///* `icons` sets a custom [VolumeIcons]
-    ///* `config` a [&WidgetConfig]
-    pub async fn new(
-        format: impl ToString,
-        icons: Option<BrightnessIcons>,
-        device: Option<String>,
-        config: &WidgetConfig,
-    ) -> Result<Box<Self>> {
+impl SysfsProvider {
+    ///* `device` name of the backlight device (defaults to the first one found)
+    pub async fn new(device: Option<String>) -> Result<Self> {
         let (brightness_path, max_brightness_path) = Self::brightness_file_path(&device)?;
         let brightness_file = File::open(&brightness_path).await.map_err(Error::from)?;
         let max_brightness_file = File::open(&max_brightness_path)
             .await
             .map_err(Error::from)?;
 
-        Ok(Box::new(Self {
-            format: format.to_string(),
-            previous_brightness: -1.0,
-            show_counter: ResettableTimer::new(config.hide_timeout),
-            inner: *Text::new("", config).await,
-            icons: icons.unwrap_or_default(),
+        Ok(Self {
             brightness_file: Mutex::new(brightness_file),
             max_brightness_file: Mutex::new(max_brightness_file),
-            device,
-        }))
-    }
-
-    fn build_string(&self, current_brightness: f64) -> String {
-        let percentages_len = self.icons.percentages.len();
-        let index = percentage_to_index(current_brightness, (0, percentages_len - 1));
-        self.format
-            .replace("%p", &format!("{:.0}", current_brightness))
-            .replace("%i", &self.icons.percentages[index].to_string())
+            brightness_path,
+        })
     }
 
     async fn read_brightness_raw(&self) -> Result<f64> {
@@ -97,10 +109,6 @@ impl Brightness {
         Ok(buf.trim().parse::<f64>().map_err(Error::from)?)
     }
 
-    async fn brightness(&self) -> Result<f64> {
-        Ok(self.read_brightness_raw().await? / self.read_max_brightness_raw().await? * 100.0)
-    }
-
     fn brightness_file_path(
         device_name: &Option<String>,
     ) -> std::result::Result<(PathBuf, PathBuf), Error> {
@@ -143,37 +151,32 @@ impl Brightness {
 }
 
 #[async_trait]
-impl Widget for Brightness {
-    async fn update(&mut self) -> Result<()> {
-        let current_brightness = self.brightness().await?;
-        if self.previous_brightness == -1.0 {
-            // first_update
-            self.previous_brightness = current_brightness;
-            self.inner.clear();
-            return Ok(());
-        }
-        if current_brightness != self.previous_brightness {
-            self.previous_brightness = current_brightness;
-            self.show_counter.reset();
-        }
-        if self.show_counter.is_done() {
-            self.inner.clear();
-        } else {
-            let text = self.build_string(current_brightness);
-            self.inner.set_text(text);
-        }
-        Ok(())
+impl BrightnessProvider for SysfsProvider {
+    async fn brightness(&self) -> Option<f64> {
+        let raw = self.read_brightness_raw().await.ok()?;
+        let max = self.read_max_brightness_raw().await.ok()?;
+        Some(raw / max * 100.0)
     }
 
-    async fn hook(&mut self, sender: HookSender, _timed_hooks: &mut TimedHooks) -> Result<()> {
-        let (path, _) = Self::brightness_file_path(&self.device)?;
+    async fn set_brightness(&mut self, percent: f64) -> Option<()> {
+        let max = self.read_max_brightness_raw().await.ok()?;
+        let raw = (percent.clamp(0.0, 100.0) / 100.0 * max).round() as u64;
+        tokio::fs::write(&self.brightness_path, raw.to_string())
+            .await
+            .ok()
+    }
 
+    async fn hook(
+        &mut self,
+        sender: HookSender,
+        _pool: &mut TimedHooks,
+        hide_timeout: Duration,
+    ) -> Result<()> {
         let events = Inotify::init().unwrap();
         events
             .watches()
-            .add(path, inotify::WatchMask::MODIFY)
+            .add(&self.brightness_path, inotify::WatchMask::MODIFY)
             .map_err(Error::from)?;
-        let show_counter_duration = self.show_counter.duration;
         spawn(async move {
             let mut buffer = [0; 1024];
             let mut event_stream = events.into_event_stream(&mut buffer).unwrap();
@@ -187,7 +190,7 @@ impl Widget for Brightness {
                         let c_sender = sender.clone();
                         spawn(async move {
                             // hide after some time
-                            sleep(show_counter_duration).await;
+                            sleep(hide_timeout).await;
                             let _ = c_sender.send().await;
                         });
                     }
@@ -201,8 +204,313 @@ impl Widget for Brightness {
         });
         Ok(())
     }
+}
+
+#[cfg(feature = "ddc")]
+pub mod ddc {
+    use super::BrightnessProvider;
+    use async_channel::{bounded, Receiver, Sender};
+    use async_trait::async_trait;
+    use ddc_hi::{Ddc, Display};
+    use std::thread;
+
+    /// VCP feature code for the monitor luminance
+    const BRIGHTNESS_CODE: u8 = 0x10;
+
+    enum Request {
+        Get,
+        Set(f64),
+    }
+
+    /// Controls an external monitor brightness over DDC/CI
+    pub struct DdcProvider {
+        request: Sender<Request>,
+        data: Receiver<Option<f64>>,
+    }
+
+    impl DdcProvider {
+        ///* `monitor` substring of the monitor model to select
+        ///  (defaults to the first monitor found)
+        pub async fn new(monitor: Option<String>) -> Self {
+            let (request_tx, request_rx) = bounded(10);
+            let (data_tx, data_rx) = bounded(10);
+            // i2c access is blocking
+            thread::spawn(move || {
+                let mut display = Display::enumerate().into_iter().find(|d| {
+                    match (&monitor, &d.info.model_name) {
+                        (Some(wanted), Some(model)) => model.contains(wanted.as_str()),
+                        (Some(_), None) => false,
+                        (None, _) => true,
+                    }
+                });
+                while let Ok(request) = request_rx.recv_blocking() {
+                    let data = display.as_mut().and_then(|d| match request {
+                        Request::Get => d
+                            .handle
+                            .get_vcp_feature(BRIGHTNESS_CODE)
+                            .ok()
+                            .map(|v| f64::from(v.value()) / f64::from(v.maximum()) * 100.0),
+                        Request::Set(percent) => {
+                            let max = d.handle.get_vcp_feature(BRIGHTNESS_CODE).ok()?.maximum();
+                            let raw = (percent.clamp(0.0, 100.0) / 100.0 * f64::from(max)).round();
+                            d.handle
+                                .set_vcp_feature(BRIGHTNESS_CODE, raw as u16)
+                                .ok()
+                                .map(|_| percent)
+                        }
+                    });
+                    if data_tx.send_blocking(data).is_err() {
+                        break;
+                    }
+                }
+            });
+            Self {
+                request: request_tx,
+                data: data_rx,
+            }
+        }
+    }
+
+    impl std::fmt::Debug for DdcProvider {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            std::fmt::Display::fmt("DdcProvider", f)
+        }
+    }
+
+    #[async_trait]
+    impl BrightnessProvider for DdcProvider {
+        async fn brightness(&self) -> Option<f64> {
+            self.request.send(Request::Get).await.ok()?;
+            self.data.recv().await.ok()?
+        }
+
+        async fn set_brightness(&mut self, percent: f64) -> Option<()> {
+            self.request.send(Request::Set(percent)).await.ok()?;
+            self.data.recv().await.ok()?.map(|_| ())
+        }
+    }
+}
+
+/// Scales a software brightness through `xrandr` gamma adjustment,
+/// for desktop monitors without a backlight interface
+#[derive(Debug)]
+pub struct XrandrProvider {
+    output: String,
+}
+
+impl XrandrProvider {
+    ///* `output` name of the RandR output (defaults to the first connected one)
+    pub async fn new(output: Option<String>) -> Result<Self> {
+        let output = match output {
+            Some(output) => output,
+            None => Self::first_connected_output().await?,
+        };
+        Ok(Self { output })
+    }
+
+    async fn first_connected_output() -> Result<String> {
+        let query = Command::new("xrandr")
+            .arg("--query")
+            .output()
+            .await
+            .map_err(Error::from)?;
+        Ok(String::from_utf8_lossy(&query.stdout)
+            .lines()
+            .find(|line| line.contains(" connected"))
+            .and_then(|line| line.split_whitespace().next())
+            .map(String::from)
+            .ok_or(Error::NoOutput)?)
+    }
+}
+
+#[async_trait]
+impl BrightnessProvider for XrandrProvider {
+    async fn brightness(&self) -> Option<f64> {
+        let query = Command::new("xrandr")
+            .args(["--verbose", "--query"])
+            .output()
+            .await
+            .ok()?;
+        let stdout = String::from_utf8_lossy(&query.stdout);
+        let mut in_output = false;
+        for line in stdout.lines() {
+            // output headers are flush left, their properties indented
+            if !line.starts_with([' ', '\t']) {
+                in_output = line.starts_with(&self.output);
+            } else if in_output {
+                if let Some(value) = line.trim().strip_prefix("Brightness: ") {
+                    return value.trim().parse::<f64>().ok().map(|v| v * 100.0);
+                }
+            }
+        }
+        None
+    }
+
+    async fn set_brightness(&mut self, percent: f64) -> Option<()> {
+        let value = percent.clamp(0.0, 100.0) / 100.0;
+        Command::new("xrandr")
+            .args(["--output", &self.output, "--brightness", &value.to_string()])
+            .output()
+            .await
+            .ok()
+            .filter(|out| out.status.success())
+            .map(|_| ())
+    }
+}
+
+#[derive(Debug)]
+pub struct Brightness {
+    format: String,
+    previous_brightness: f64,
+    show_counter: ResettableTimer,
+    inner: Text,
+    icons: BrightnessIcons,
+    provider: Box<dyn BrightnessProvider>,
+    scroll_step: f64,
+    fg_color: Color,
+    osd: Option<OsdBar>,
+    osd_visible: bool,
+}
+
+impl Brightness {
+    ///* `format`
+    ///  * *%p* will be replaced with the brightness percentage
+    ///  * *%i* will be replaced with the correct icon
+    ///* `icons` sets a custom [BrightnessIcons]
+    ///* `provider` implements [BrightnessProvider]
+    ///* `config` a [&WidgetConfig]
+    pub async fn new(
+        format: impl ToString,
+        icons: Option<BrightnessIcons>,
+        provider: Box<impl BrightnessProvider + 'static>,
+        config: &WidgetConfig,
+    ) -> Result<Box<Self>> {
+        Ok(Box::new(Self {
+            format: format.to_string(),
+            previous_brightness: -1.0,
+            show_counter: ResettableTimer::new(config.hide_timeout),
+            inner: *Text::new("", config).await,
+            icons: icons.unwrap_or_default(),
+            provider,
+            scroll_step: 5.0,
+            fg_color: config.fg_color,
+            osd: None,
+            osd_visible: false,
+        }))
+    }
+
+    /// Shows an [OsdBar] while the brightness is changing
+    pub fn with_osd(mut self: Box<Self>, osd: OsdBar) -> Box<Self> {
+        self.osd = Some(osd);
+        self
+    }
+
+    /// Sets how much a scroll event changes the brightness (default 5%)
+    pub fn scroll_step(mut self: Box<Self>, step: f64) -> Box<Self> {
+        self.scroll_step = step;
+        self
+    }
+
+    fn build_string(&self, current_brightness: f64) -> String {
+        let percentages_len = self.icons.percentages.len();
+        let index = percentage_to_index(current_brightness, (0, percentages_len - 1));
+        self.format
+            .replace("%p", &format!("{:.0}", current_brightness))
+            .replace("%i", &self.icons.percentages[index].to_string())
+    }
+}
+
+#[async_trait]
+impl Widget for Brightness {
+    async fn setup(&mut self, _info: &StatusBarInfo) -> Result<()> {
+        icons::warn_missing_glyphs(&self.icons, self.inner.font(), "Brightness");
+        Ok(())
+    }
+
+    async fn update(&mut self) -> Result<()> {
+        let Some(current_brightness) = self.provider.brightness().await else {
+            error!("failed to read brightness");
+            return Ok(());
+        };
+        if self.previous_brightness == -1.0 {
+            // first_update
+            self.previous_brightness = current_brightness;
+            self.inner.clear();
+            return Ok(());
+        }
+        if current_brightness != self.previous_brightness {
+            self.previous_brightness = current_brightness;
+            self.show_counter.reset();
+        }
+        if self.show_counter.is_done() {
+            self.inner.clear();
+        } else {
+            let text = self.build_string(current_brightness);
+            self.inner.set_text(text);
+        }
+        self.osd_visible = self.osd.is_some() && !self.show_counter.is_done();
+        Ok(())
+    }
+
+    async fn on_click(&mut self, event: ClickEvent) -> Result<()> {
+        let delta = match event.button {
+            MouseButton::ScrollUp => self.scroll_step,
+            MouseButton::ScrollDown => -self.scroll_step,
+            _ => return Ok(()),
+        };
+        let Some(current) = self.provider.brightness().await else {
+            return Ok(());
+        };
+        if self.provider.set_brightness(current + delta).await.is_none() {
+            error!("failed to set brightness");
+        }
+        Ok(())
+    }
+
+    async fn hook(&mut self, sender: HookSender, timed_hooks: &mut TimedHooks) -> Result<()> {
+        self.provider
+            .hook(sender, timed_hooks, self.show_counter.duration)
+            .await
+    }
+
+    fn draw(&self, context: Context, rectangle: &Rectangle) -> Result<()> {
+        self.inner.draw(context.clone(), rectangle)?;
+        let Some(osd) = &self.osd else {
+            return Ok(());
+        };
+        if !self.osd_visible {
+            return Ok(());
+        }
+        // progress bar to the right of the icon text
+        let start = f64::from(rectangle.width).min(30.0);
+        let available = (f64::from(rectangle.width) - start - 5.0).max(0.0);
+        let y = (f64::from(rectangle.height) - f64::from(osd.thickness)) / 2.0;
+        let mut track = self.fg_color;
+        track.a *= 0.3;
+        set_source_rgba(&context, track);
+        context.rectangle(start, y, available, f64::from(osd.thickness));
+        context.fill().map_err(Error::from)?;
+        set_source_rgba(&context, self.fg_color);
+        context.rectangle(
+            start,
+            y,
+            available * self.previous_brightness.clamp(0.0, 100.0) / 100.0,
+            f64::from(osd.thickness),
+        );
+        context.fill().map_err(Error::from)?;
+        Ok(())
+    }
+
+    fn size(&self, context: &Context) -> Result<Size> {
+        if self.osd_visible {
+            if let Some(osd) = &self.osd {
+                return Ok(Size::Static(osd.width));
+            }
+        }
+        self.inner.size(context)
+    }
 
-    widget_default!(draw, size, padding);
+    widget_default!(padding);
 }
 
 impl Display for Brightness {
@@ -214,9 +522,12 @@ impl Display for Brightness {
 #[derive(Debug, thiserror::Error)]
 #[error(transparent)]
 pub enum Error {
+    Cairo(#[from] cairo::Error),
     Io(#[from] std::io::Error),
     #[error("Failed to find a valid sysfs folder")]
     NoBrightnessFile,
+    #[error("Failed to find a connected RandR output")]
+    NoOutput,
     #[error("Failed to parse brightness file")]
     Parse(#[from] std::num::ParseFloatError),
 }
@@ -0,0 +1,94 @@
+use crate::{
+    utils::{HookSender, TimedHooks},
+    widget_default,
+    widgets::{ClickEvent, MouseButton, Result, Text, Widget, WidgetConfig},
+};
+use async_trait::async_trait;
+use futures::{future::BoxFuture, Future, FutureExt};
+use log::{debug, error};
+use std::fmt::Display;
+
+type Action = Box<dyn FnMut() -> BoxFuture<'static, ()> + Send>;
+
+/// A clickable label running an async action
+///
+/// ```ignore
+/// Button::new("󰄀", screenshot, &WidgetConfig::default()).await
+/// ```
+pub struct Button {
+    inner: Text,
+    action: Action,
+}
+
+impl std::fmt::Debug for Button {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "inner: {:?}", self.inner)
+    }
+}
+
+impl Button {
+    ///* `label` the displayed text (usually an icon)
+    ///* `action` an async closure run on every left click
+    ///* `config` a [&WidgetConfig]
+    pub async fn new<F, Fut>(label: impl ToString, mut action: F, config: &WidgetConfig) -> Box<Self>
+    where
+        F: FnMut() -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        Box::new(Self {
+            inner: *Text::new(label, config).await,
+            action: Box::new(move || action().boxed()),
+        })
+    }
+}
+
+#[async_trait]
+impl Widget for Button {
+    async fn on_click(&mut self, event: ClickEvent) -> Result<()> {
+        if event.button == MouseButton::Left {
+            debug!("button clicked");
+            // the action may take a while (e.g. region selection),
+            // run it without blocking the bar
+            tokio::spawn((self.action)());
+        }
+        Ok(())
+    }
+
+    async fn hook(&mut self, sender: HookSender, timed_hooks: &mut TimedHooks) -> Result<()> {
+        timed_hooks.subscribe(sender);
+        Ok(())
+    }
+
+    widget_default!(draw, size, padding);
+}
+
+impl Display for Button {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        String::from("Button").fmt(f)
+    }
+}
+
+/// Takes a screenshot of a selected region into ~/Pictures
+///
+/// Uses maim on X11 and falls back to grim + slurp on wayland
+pub async fn screenshot() {
+    let command = concat!(
+        "out=~/Pictures/screenshot-$(date +%s).png;",
+        " mkdir -p ~/Pictures;",
+        " maim -s \"$out\" || grim -g \"$(slurp)\" \"$out\"",
+    );
+    match tokio::process::Command::new("sh")
+        .args(["-c", command])
+        .output()
+        .await
+    {
+        Ok(output) if !output.status.success() => {
+            error!(
+                "screenshot failed: {}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+        }
+        Ok(_) => {}
+        Err(e) => error!("failed to run screenshot command: {e}"),
+    }
+}
@@ -0,0 +1,114 @@
+use crate::{
+    utils::{HookSender, Rectangle, ResettableTimer, StatusBarInfo, TimedHooks},
+    widgets::{ClickEvent, MouseButton, Result, Size, Widget},
+};
+use async_trait::async_trait;
+use cairo::Context;
+use log::debug;
+use std::{fmt::Display, time::Duration};
+
+/// Shows one of its widgets at a time, sharing a single bar slot
+///
+/// The displayed widget changes every `interval` or on left click,
+/// other clicks are forwarded to the visible widget
+#[derive(Debug)]
+pub struct Carousel {
+    widgets: Vec<Box<dyn Widget>>,
+    current: usize,
+    rotation: ResettableTimer,
+}
+
+impl Carousel {
+    ///* `widgets` the widgets to rotate through
+    ///* `interval` how long each widget stays visible
+    pub async fn new(widgets: Vec<Box<dyn Widget>>, interval: Duration) -> Box<Self> {
+        Box::new(Self {
+            widgets,
+            current: 0,
+            rotation: ResettableTimer::new(interval),
+        })
+    }
+
+    fn current_widget(&self) -> &dyn Widget {
+        self.widgets[self.current].as_ref()
+    }
+
+    fn advance(&mut self) {
+        self.current = (self.current + 1) % self.widgets.len();
+        self.rotation.reset();
+    }
+}
+
+#[async_trait]
+impl Widget for Carousel {
+    fn draw(&self, context: Context, rectangle: &Rectangle) -> Result<()> {
+        self.current_widget().draw(context, rectangle)
+    }
+
+    async fn setup(&mut self, info: &StatusBarInfo) -> Result<()> {
+        for widget in &mut self.widgets {
+            widget.setup(info).await?;
+        }
+        Ok(())
+    }
+
+    async fn update(&mut self) -> Result<()> {
+        debug!("updating carousel");
+        if self.rotation.is_done() {
+            self.advance();
+        }
+        for widget in &mut self.widgets {
+            widget.update().await?;
+        }
+        Ok(())
+    }
+
+    async fn on_click(&mut self, event: ClickEvent) -> Result<()> {
+        if event.button == MouseButton::Left {
+            self.advance();
+            return Ok(());
+        }
+        self.widgets[self.current].on_click(event).await
+    }
+
+    async fn hook(&mut self, sender: HookSender, pool: &mut TimedHooks) -> Result<()> {
+        // rotation is checked on the shared timer,
+        // children also get their own hooks
+        pool.subscribe(sender.clone());
+        for widget in &mut self.widgets {
+            widget.hook(sender.clone(), pool).await?;
+        }
+        Ok(())
+    }
+
+    async fn teardown(&mut self) -> Result<()> {
+        for widget in &mut self.widgets {
+            widget.teardown().await?;
+        }
+        Ok(())
+    }
+
+    fn save_state(&self) -> Option<serde_json::Value> {
+        Some(self.current.into())
+    }
+
+    fn load_state(&mut self, state: &serde_json::Value) {
+        if let Some(index) = state.as_u64() {
+            self.current = index as usize % self.widgets.len();
+        }
+    }
+
+    fn size(&self, context: &Context) -> Result<Size> {
+        self.current_widget().size(context)
+    }
+
+    fn padding(&self) -> u32 {
+        self.current_widget().padding()
+    }
+}
+
+impl Display for Carousel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        String::from("Carousel").fmt(f)
+    }
+}
@@ -0,0 +1,130 @@
+use crate::{
+    utils::{spawn, HookSender, StatusBarInfo, TimedHooks},
+    widgets::{ClickEvent, ClickModifiers, MouseButton, Rectangle, Result, Size, Widget},
+};
+use async_trait::async_trait;
+use cairo::Context;
+use std::fmt::Display;
+
+/// What a mouse binding on a [Clickable] runs
+pub enum MouseAction {
+    /// run a shell command, fire and forget
+    Spawn(String),
+    /// arbitrary user callback
+    Callback(Box<dyn FnMut(ClickEvent) + Send>),
+}
+
+impl std::fmt::Debug for MouseAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Spawn(command) => f.debug_tuple("Spawn").field(command).finish(),
+            Self::Callback(_) => f.debug_tuple("Callback").finish(),
+        }
+    }
+}
+
+/// Wraps any widget with a map of mouse bindings
+///
+/// Bindings win over the wrapped widget's own
+/// [on_click](Widget::on_click), unbound buttons still reach it
+///
+/// ```ignore
+/// Clickable::new(Volume::new(...).await)
+///     .on(MouseButton::Left, MouseAction::Spawn("pavucontrol".into()))
+///     .on(MouseButton::Middle, MouseAction::Spawn("~/bin/audio-menu".into()))
+/// ```
+#[derive(Debug)]
+pub struct Clickable {
+    inner: Box<dyn Widget>,
+    bindings: Vec<(MouseButton, ClickModifiers, MouseAction)>,
+}
+
+impl Clickable {
+    pub fn new(inner: Box<dyn Widget>) -> Box<Self> {
+        Box::new(Self {
+            inner,
+            bindings: Vec::new(),
+        })
+    }
+
+    /// Binds `button` pressed with no modifiers to `action`
+    pub fn on(self: Box<Self>, button: MouseButton, action: MouseAction) -> Box<Self> {
+        self.on_with_modifiers(button, ClickModifiers::default(), action)
+    }
+
+    /// Binds `button` pressed while exactly `modifiers` are held
+    pub fn on_with_modifiers(
+        mut self: Box<Self>,
+        button: MouseButton,
+        modifiers: ClickModifiers,
+        action: MouseAction,
+    ) -> Box<Self> {
+        self.bindings.push((button, modifiers, action));
+        self
+    }
+}
+
+#[async_trait]
+impl Widget for Clickable {
+    fn draw(&self, context: Context, rectangle: &Rectangle) -> Result<()> {
+        self.inner.draw(context, rectangle)
+    }
+
+    async fn setup(&mut self, info: &StatusBarInfo) -> Result<()> {
+        self.inner.setup(info).await
+    }
+
+    async fn update(&mut self) -> Result<()> {
+        self.inner.update().await
+    }
+
+    async fn hook(&mut self, sender: HookSender, pool: &mut TimedHooks) -> Result<()> {
+        self.inner.hook(sender, pool).await
+    }
+
+    async fn on_click(&mut self, event: ClickEvent) -> Result<()> {
+        let binding = self
+            .bindings
+            .iter_mut()
+            .find(|(button, modifiers, _)| *button == event.button && *modifiers == event.modifiers);
+        let Some((_, _, action)) = binding else {
+            return self.inner.on_click(event).await;
+        };
+        match action {
+            MouseAction::Spawn(command) => spawn::spawn_sh(command.as_str()),
+            MouseAction::Callback(callback) => callback(event),
+        }
+        Ok(())
+    }
+
+    async fn teardown(&mut self) -> Result<()> {
+        self.inner.teardown().await
+    }
+
+    fn save_state(&self) -> Option<serde_json::Value> {
+        self.inner.save_state()
+    }
+
+    fn load_state(&mut self, state: &serde_json::Value) {
+        self.inner.load_state(state)
+    }
+
+    fn size(&self, context: &Context) -> Result<Size> {
+        self.inner.size(context)
+    }
+
+    fn padding(&self) -> u32 {
+        self.inner.padding()
+    }
+
+    fn priority(&self) -> u32 {
+        self.inner.priority()
+    }
+}
+
+impl Display for Clickable {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // keep the wrapped widget's persistence key
+        self.inner.fmt(f)
+    }
+}
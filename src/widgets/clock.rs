@@ -1,16 +1,18 @@
 use crate::utils::{HookSender, TimedHooks};
 use crate::{
     widget_default,
-    widgets::{Result, Text, Widget, WidgetConfig},
+    widgets::{ClickEvent, MouseButton, Result, Text, Widget, WidgetConfig},
 };
 use async_trait::async_trait;
-use chrono::Local;
-use log::debug;
+use chrono::{Local, Locale};
+use log::{debug, error};
 use std::fmt::{Debug, Display};
 
 /// Displays a datetime
 pub struct Clock {
-    format: String,
+    formats: Vec<String>,
+    active_format: usize,
+    locale: Option<Locale>,
     inner: Text,
 }
 
@@ -19,7 +21,7 @@ impl Debug for Clock {
         write!(
             f,
             "Clock(format: {}, padding: {})",
-            self.format,
+            self.formats[self.active_format],
             self.inner.padding(),
         )
     }
@@ -32,20 +34,61 @@ impl Clock {
         let format = format.to_string();
         Box::new(Self {
             inner: *Text::new("", config).await,
-            format,
+            formats: vec![format],
+            active_format: 0,
+            locale: None,
         })
     }
+
+    /// Alternative formats a click cycles through
+    /// (e.g. time, date, ISO week)
+    pub fn with_formats(mut self: Box<Self>, formats: Vec<impl ToString>) -> Box<Self> {
+        self.formats.extend(formats.iter().map(ToString::to_string));
+        self
+    }
+
+    /// Renders month and day names in the given locale (e.g. "it_IT")
+    pub fn with_locale(mut self: Box<Self>, locale: impl ToString) -> Box<Self> {
+        let locale = locale.to_string();
+        match Locale::try_from(locale.as_str()) {
+            Ok(locale) => self.locale = Some(locale),
+            Err(_) => error!("unknown locale: {locale}"),
+        }
+        self
+    }
 }
 
 #[async_trait]
 impl Widget for Clock {
     async fn update(&mut self) -> Result<()> {
         debug!("updating clock");
-        let text = Local::now().format(&self.format);
+        let now = Local::now();
+        let format = &self.formats[self.active_format];
+        let text = match self.locale {
+            Some(locale) => now.format_localized(format, locale).to_string(),
+            None => now.format(format).to_string(),
+        };
         self.inner.set_text(text);
         Ok(())
     }
 
+    async fn on_click(&mut self, event: ClickEvent) -> Result<()> {
+        if event.button == MouseButton::Left {
+            self.active_format = (self.active_format + 1) % self.formats.len();
+        }
+        Ok(())
+    }
+
+    fn save_state(&self) -> Option<serde_json::Value> {
+        Some(self.active_format.into())
+    }
+
+    fn load_state(&mut self, state: &serde_json::Value) {
+        if let Some(index) = state.as_u64() {
+            self.active_format = index as usize % self.formats.len();
+        }
+    }
+
     async fn hook(&mut self, sender: HookSender, timed_hooks: &mut TimedHooks) -> Result<()> {
         timed_hooks.subscribe(sender);
         Ok(())
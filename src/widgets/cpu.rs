@@ -1,12 +1,49 @@
 use crate::{
-    utils::{HookSender, TimedHooks},
+    utils::{format, metrics, FormatValue, HookSender, TimedHooks},
     widget_default,
     widgets::{Result, Text, Widget, WidgetConfig},
 };
 use async_trait::async_trait;
 use log::debug;
 use psutil::cpu::{CpuPercentCollector, CpuTimesPercentCollector};
-use std::fmt::Display;
+use std::{fmt::Display, fs};
+
+/// Average core frequency in GHz from cpufreq
+fn average_frequency() -> Option<f64> {
+    let mut total = 0;
+    let mut count = 0;
+    for entry in fs::read_dir("/sys/devices/system/cpu").ok()?.flatten() {
+        let path = entry.path().join("cpufreq/scaling_cur_freq");
+        let Ok(content) = fs::read_to_string(path) else {
+            continue;
+        };
+        let Ok(khz) = content.trim().parse::<u64>() else {
+            continue;
+        };
+        total += khz;
+        count += 1;
+    }
+    (count > 0).then(|| total as f64 / count as f64 / 1_000_000.0)
+}
+
+/// Package temperature in degrees from hwmon, thermal zone as fallback
+fn package_temperature() -> Option<f64> {
+    for entry in fs::read_dir("/sys/class/hwmon").ok()?.flatten() {
+        let path = entry.path();
+        let name = fs::read_to_string(path.join("name")).unwrap_or_default();
+        if !matches!(name.trim(), "coretemp" | "k10temp" | "zenpower") {
+            continue;
+        }
+        if let Some(milli) = fs::read_to_string(path.join("temp1_input"))
+            .ok()
+            .and_then(|temp| temp.trim().parse::<i64>().ok())
+        {
+            return Some(milli as f64 / 1000.0);
+        }
+    }
+    let temp = fs::read_to_string("/sys/class/thermal/thermal_zone0/temp").ok()?;
+    Some(temp.trim().parse::<i64>().ok()? as f64 / 1000.0)
+}
 
 /// Displays cpu informations
 #[derive(Debug)]
@@ -24,6 +61,8 @@ impl Cpu {
     ///  * *%s* will be replaced with the time spent in system mode
     ///  * *%i* will be replaced with the time spent idle
     ///  * *%b* will be replaced with the time spent busy
+    ///  * *%f* will be replaced with the average core frequency in GHz
+    ///  * *%t* will be replaced with the package temperature
     ///* `config` a [&WidgetConfig]
     pub async fn new(format: impl ToString, config: &WidgetConfig) -> Result<Box<Self>> {
         Ok(Box::new(Self {
@@ -41,14 +80,24 @@ impl Widget for Cpu {
         debug!("updating cpu");
         let times = self.times.cpu_times_percent().map_err(Error::from)?;
         let cpu_percent = self.per.cpu_percent().map_err(Error::from)?;
-        let text = self
-            .format
-            .replace("%p", &format!("{: >4.1}", cpu_percent))
-            .replace("%u", &format!("{: >4.1}", times.user()))
-            .replace("%s", &format!("{: >4.1}", times.system()))
-            .replace("%i", &format!("{: >4.1}", times.idle()))
-            .replace("%b", &format!("{: >4.1}", times.busy()));
-        self.inner.set_text(text);
+        metrics::gauge("cpu_usage_percent", cpu_percent.into());
+        let mut values = vec![
+            ('p', FormatValue::Number(cpu_percent.into())),
+            ('u', FormatValue::Number(times.user().into())),
+            ('s', FormatValue::Number(times.system().into())),
+            ('i', FormatValue::Number(times.idle().into())),
+            ('b', FormatValue::Number(times.busy().into())),
+        ];
+        // only touch sysfs when the specifiers are used
+        if format::uses(&self.format, 'f') {
+            let frequency = average_frequency().unwrap_or(0.0);
+            values.push(('f', FormatValue::Number(frequency)));
+        }
+        if format::uses(&self.format, 't') {
+            let temperature = package_temperature().unwrap_or(0.0);
+            values.push(('t', FormatValue::Celsius(temperature)));
+        }
+        self.inner.set_format(&self.format, &values);
         Ok(())
     }
 
@@ -1,15 +1,68 @@
-use crate::utils::{bytes_to_closest, HookSender, TimedHooks};
+use crate::utils::{bytes_to_closest, Color, FormatValue, HookSender, TimedHooks};
 use crate::{
     widget_default,
     widgets::{Result, Text, Widget, WidgetConfig},
 };
 use async_trait::async_trait;
-use std::fmt::Display;
+use std::{ffi::CString, fmt::Display, fs, time::Instant};
+
+/// Inode usage of the filesystem at `path`, in percent
+fn inode_percent(path: &str) -> Option<f64> {
+    let path = CString::new(path).ok()?;
+    let mut stats: libc::statvfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statvfs(path.as_ptr(), &mut stats) } != 0 {
+        return None;
+    }
+    if stats.f_files == 0 {
+        return None;
+    }
+    let used = stats.f_files - stats.f_ffree;
+    Some(used as f64 / stats.f_files as f64 * 100.0)
+}
+
+/// The device name backing the mount point containing `path`
+fn find_device(path: &str) -> Option<String> {
+    let mounts = fs::read_to_string("/proc/mounts").ok()?;
+    let mut best: Option<(usize, String)> = None;
+    for line in mounts.lines() {
+        let mut fields = line.split_whitespace();
+        let (Some(device), Some(mount)) = (fields.next(), fields.next()) else {
+            continue;
+        };
+        let Some(device) = device.strip_prefix("/dev/") else {
+            continue;
+        };
+        if path.starts_with(mount) && best.as_ref().map_or(true, |(len, _)| mount.len() > *len) {
+            best = Some((mount.len(), device.to_string()));
+        }
+    }
+    best.map(|(_, device)| device)
+}
+
+/// Total bytes read and written by `device` since boot
+fn read_io(device: &str) -> Option<(u64, u64)> {
+    let stats = fs::read_to_string("/proc/diskstats").ok()?;
+    for line in stats.lines() {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.get(2) != Some(&device) {
+            continue;
+        }
+        // sectors are always 512 bytes in diskstats
+        let sectors_read: u64 = fields.get(5)?.parse().ok()?;
+        let sectors_written: u64 = fields.get(9)?.parse().ok()?;
+        return Some((sectors_read * 512, sectors_written * 512));
+    }
+    None
+}
 
 #[derive(Debug)]
 pub struct Disk {
     format: String,
     path: String,
+    device: Option<String>,
+    previous_io: Option<(u64, u64, Instant)>,
+    threshold: Option<(f64, Color)>,
+    fg_color: Color,
     inner: Text,
 }
 
@@ -19,31 +72,82 @@ impl Disk {
     ///  * *%u* will be replaced with the used disk
     ///  * *%f* will be replaced with the free disk
     ///  * *%t* will be replaced with the total disk
+    ///  * *%i* will be replaced with the inode used percent
+    ///  * *%r* will be replaced with the read throughput
+    ///  * *%w* will be replaced with the write throughput
     ///* `config` a [&WidgetConfig]
     pub async fn new(
         format: impl ToString,
         path: impl ToString,
         config: &WidgetConfig,
     ) -> Box<Self> {
+        let path = path.to_string();
         Box::new(Self {
             format: format.to_string(),
-            path: path.to_string(),
+            device: find_device(&path),
+            path,
+            previous_io: None,
+            threshold: None,
+            fg_color: config.fg_color,
             inner: *Text::new("", config).await,
         })
     }
+
+    /// Colors the widget when the used percent exceeds `percent`
+    pub fn with_threshold(mut self: Box<Self>, percent: f64, color: Color) -> Box<Self> {
+        self.threshold = Some((percent, color));
+        self
+    }
+
+    /// Read and write rates in bytes per second since the last update
+    fn io_rates(&mut self) -> (u64, u64) {
+        let Some(device) = &self.device else {
+            return (0, 0);
+        };
+        let Some((read, written)) = read_io(device) else {
+            return (0, 0);
+        };
+        let now = Instant::now();
+        let rates = match self.previous_io {
+            Some((previous_read, previous_written, at)) => {
+                let elapsed = now.duration_since(at).as_secs_f64().max(1.0);
+                (
+                    (read.saturating_sub(previous_read) as f64 / elapsed) as u64,
+                    (written.saturating_sub(previous_written) as f64 / elapsed) as u64,
+                )
+            }
+            None => (0, 0),
+        };
+        self.previous_io = Some((read, written, now));
+        rates
+    }
 }
 
 #[async_trait]
 impl Widget for Disk {
     async fn update(&mut self) -> Result<()> {
         let disk_usage = psutil::disk::disk_usage(self.path.clone()).map_err(Error::from)?;
-        let text = self
-            .format
-            .replace("%p", &disk_usage.percent().to_string())
-            .replace("%u", &bytes_to_closest(disk_usage.used()))
-            .replace("%f", &bytes_to_closest(disk_usage.free()))
-            .replace("%t", &bytes_to_closest(disk_usage.total()));
-        self.inner.set_text(text);
+        let (read_rate, write_rate) = self.io_rates();
+        let inodes = inode_percent(&self.path).unwrap_or(0.0);
+        if let Some((percent, color)) = self.threshold {
+            if f64::from(disk_usage.percent()) > percent {
+                self.inner.set_fg_color(color);
+            } else {
+                self.inner.set_fg_color(self.fg_color);
+            }
+        }
+        self.inner.set_format(
+            &self.format,
+            &[
+                ('p', FormatValue::Number(disk_usage.percent().into())),
+                ('u', FormatValue::Bytes(disk_usage.used())),
+                ('f', FormatValue::Bytes(disk_usage.free())),
+                ('t', FormatValue::Bytes(disk_usage.total())),
+                ('i', FormatValue::Number(inodes)),
+                ('r', FormatValue::Text(format!("{}/s", bytes_to_closest(read_rate)))),
+                ('w', FormatValue::Text(format!("{}/s", bytes_to_closest(write_rate)))),
+            ],
+        );
         Ok(())
     }
 
@@ -0,0 +1,131 @@
+use crate::{
+    utils::{HookSender, StatusBarInfo, TimedHooks},
+    widget_default,
+    widgets::{ClickEvent, MouseButton, Result, Text, Widget, WidgetConfig},
+};
+use async_trait::async_trait;
+use log::{debug, error};
+use std::fmt::Display;
+
+/// Icons used by [DoNotDisturb]
+#[derive(Debug)]
+pub struct DndIcons {
+    pub enabled: String,
+    pub disabled: String,
+}
+
+impl Default for DndIcons {
+    fn default() -> Self {
+        Self {
+            enabled: String::from("󰂛"),
+            disabled: String::from("󰂚"),
+        }
+    }
+}
+
+type DndAction = Box<dyn Fn(bool) + Send>;
+
+/// A do-not-disturb toggle
+///
+/// Left click flips the state, the state survives restarts
+/// through [PersistentState](crate::utils::PersistentState)
+pub struct DoNotDisturb {
+    enabled: bool,
+    icons: DndIcons,
+    action: DndAction,
+    inner: Text,
+}
+
+impl std::fmt::Debug for DoNotDisturb {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "enabled: {:?}, inner: {:?}", self.enabled, self.inner)
+    }
+}
+
+fn dunstctl(paused: bool) {
+    let paused = if paused { "true" } else { "false" };
+    if let Err(e) = tokio::process::Command::new("dunstctl")
+        .args(["set-paused", paused])
+        .spawn()
+    {
+        error!("failed to run dunstctl: {e}");
+    }
+}
+
+impl DoNotDisturb {
+    ///* `icons` sets a custom [DndIcons]
+    ///* `config` a [&WidgetConfig]
+    pub async fn new(icons: Option<DndIcons>, config: &WidgetConfig) -> Result<Box<Self>> {
+        Ok(Box::new(Self {
+            enabled: false,
+            icons: icons.unwrap_or_default(),
+            action: Box::new(dunstctl),
+            inner: *Text::new("", config).await,
+        }))
+    }
+
+    /// Replaces the default `dunstctl set-paused` action with a
+    /// custom one, called with the new state on every toggle
+    pub fn with_action(mut self: Box<Self>, action: impl Fn(bool) + Send + 'static) -> Box<Self> {
+        self.action = Box::new(action);
+        self
+    }
+
+}
+
+#[async_trait]
+impl Widget for DoNotDisturb {
+    async fn setup(&mut self, _info: &StatusBarInfo) -> Result<()> {
+        (self.action)(self.enabled);
+        Ok(())
+    }
+
+    async fn update(&mut self) -> Result<()> {
+        debug!("updating dnd");
+        let icon = if self.enabled {
+            &self.icons.enabled
+        } else {
+            &self.icons.disabled
+        };
+        self.inner.set_text(icon.clone());
+        Ok(())
+    }
+
+    async fn on_click(&mut self, event: ClickEvent) -> Result<()> {
+        if event.button == MouseButton::Left {
+            self.enabled = !self.enabled;
+            (self.action)(self.enabled);
+        }
+        Ok(())
+    }
+
+    async fn hook(&mut self, sender: HookSender, timed_hooks: &mut TimedHooks) -> Result<()> {
+        timed_hooks.subscribe(sender);
+        Ok(())
+    }
+
+    fn save_state(&self) -> Option<serde_json::Value> {
+        Some(serde_json::Value::Bool(self.enabled))
+    }
+
+    fn load_state(&mut self, state: &serde_json::Value) {
+        if let Some(enabled) = state.as_bool() {
+            self.enabled = enabled;
+            (self.action)(self.enabled);
+        }
+    }
+
+    widget_default!(draw, size, padding);
+}
+
+impl Display for DoNotDisturb {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        String::from("DoNotDisturb").fmt(f)
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error(transparent)]
+pub enum Error {
+    IO(#[from] std::io::Error),
+}
@@ -1,24 +1,19 @@
 use crate::{
     utils::{HookSender, Rectangle, StatusBarInfo, TimedHooks},
-    widgets::{Png, Result, Size, Svg, Widget, WidgetConfig},
+    widgets::{ClickEvent, Png, Result, Size, Svg, Widget, WidgetConfig},
 };
 use async_trait::async_trait;
 use cairo::Context;
-use std::{
-    fmt::Display,
-    ops::{Deref, DerefMut},
-    path::PathBuf,
-};
+use std::{collections::HashMap, fmt::Display, path::PathBuf};
 
 #[derive(Debug)]
-pub enum Icon {
+enum IconKind {
     Svg(Svg),
     Png(Png),
 }
 
-impl Icon {
-    pub fn new(path: impl Into<PathBuf>, width: u32, config: &WidgetConfig) -> Result<Box<Self>> {
-        let path: PathBuf = path.into();
+impl IconKind {
+    fn load(path: &PathBuf, width: u32, config: &WidgetConfig) -> Result<Self> {
         if !path.is_file() {
             return Err(
                 Error::UnsupportedFileType(format!("{} is not a file", path.display())).into(),
@@ -27,62 +22,138 @@ impl Icon {
 
         // if the extension is missing, assume it's a png
         if path.extension().map(|ext| ext == "png").unwrap_or(true) {
-            Png::new(path, width, config)
-                .map(|w| Icon::Png(*w))
-                .map(Box::new)
+            Png::new(path.clone(), width, config).map(|w| IconKind::Png(*w))
         } else if path.extension().map(|ext| ext == "svg").unwrap_or(true) {
-            Svg::new(path, width, config)
-                .map(|w| Icon::Svg(*w))
-                .map(Box::new)
+            Svg::new(path.clone(), width, config).map(|w| IconKind::Svg(*w))
         } else {
             Err(Error::UnsupportedFileType(path.display().to_string()).into())
         }
     }
-}
 
-impl Deref for Icon {
-    type Target = dyn Widget;
-    fn deref(&self) -> &Self::Target {
+    fn as_widget(&self) -> &dyn Widget {
         match self {
-            Icon::Svg(svg) => svg,
-            Icon::Png(png) => png,
+            IconKind::Svg(svg) => svg,
+            IconKind::Png(png) => png,
         }
     }
 }
 
-impl DerefMut for Icon {
-    fn deref_mut(&mut self) -> &mut Self::Target {
-        match self {
-            Icon::Svg(svg) => svg,
-            Icon::Png(png) => png,
+type PathUpdater = Box<dyn Fn() -> Option<PathBuf> + Send>;
+type ClickCallback = Box<dyn FnMut(ClickEvent) + Send>;
+
+pub struct Icon {
+    // rasterized surfaces keyed by path (all share the same width)
+    cache: HashMap<PathBuf, IconKind>,
+    current: PathBuf,
+    width: u32,
+    config: WidgetConfig,
+    updater: Option<PathUpdater>,
+    on_click: Option<ClickCallback>,
+}
+
+impl std::fmt::Debug for Icon {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "current: {:?}, width: {:?}, cached: {:?}",
+            self.current,
+            self.width,
+            self.cache.len()
+        )
+    }
+}
+
+impl Icon {
+    pub fn new(path: impl Into<PathBuf>, width: u32, config: &WidgetConfig) -> Result<Box<Self>> {
+        let path: PathBuf = path.into();
+        let inner = IconKind::load(&path, width, config)?;
+        let mut cache = HashMap::new();
+        cache.insert(path.clone(), inner);
+        Ok(Box::new(Self {
+            cache,
+            current: path,
+            width,
+            config: config.clone(),
+            updater: None,
+            on_click: None,
+        }))
+    }
+
+    /// Sets a closure called on every update, returning the path
+    /// of the image to display (e.g. a VPN on/off icon)
+    pub fn with_updater(
+        mut self: Box<Self>,
+        updater: impl Fn() -> Option<PathBuf> + Send + 'static,
+    ) -> Box<Self> {
+        self.updater = Some(Box::new(updater));
+        self
+    }
+
+    /// Sets a closure called when the icon is clicked
+    pub fn with_on_click(
+        mut self: Box<Self>,
+        on_click: impl FnMut(ClickEvent) + Send + 'static,
+    ) -> Box<Self> {
+        self.on_click = Some(Box::new(on_click));
+        self
+    }
+
+    /// Swaps the displayed image, rasterizing it only the first time
+    pub fn set_path(&mut self, path: impl Into<PathBuf>) -> Result<()> {
+        let path: PathBuf = path.into();
+        if !self.cache.contains_key(&path) {
+            let inner = IconKind::load(&path, self.width, &self.config)?;
+            self.cache.insert(path.clone(), inner);
         }
+        self.current = path;
+        Ok(())
+    }
+
+    fn inner(&self) -> &dyn Widget {
+        self.cache
+            .get(&self.current)
+            .expect("current icon is always cached")
+            .as_widget()
     }
 }
 
 #[async_trait]
 impl Widget for Icon {
     fn draw(&self, context: Context, rectangle: &Rectangle) -> Result<()> {
-        self.deref().draw(context, rectangle)
+        self.inner().draw(context, rectangle)
     }
 
-    async fn setup(&mut self, info: &StatusBarInfo) -> Result<()> {
-        self.deref_mut().setup(info).await
+    async fn setup(&mut self, _info: &StatusBarInfo) -> Result<()> {
+        Ok(())
     }
 
     async fn update(&mut self) -> Result<()> {
-        self.deref_mut().update().await
+        if let Some(updater) = &self.updater {
+            if let Some(path) = updater() {
+                self.set_path(path)?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn on_click(&mut self, event: ClickEvent) -> Result<()> {
+        if let Some(on_click) = &mut self.on_click {
+            on_click(event);
+        }
+        Ok(())
     }
 
     async fn hook(&mut self, sender: HookSender, pool: &mut TimedHooks) -> Result<()> {
-        self.deref_mut().hook(sender, pool).await
+        pool.subscribe(sender);
+        Ok(())
     }
 
     fn size(&self, context: &Context) -> Result<Size> {
-        self.deref().size(context)
+        self.inner().size(context)
     }
 
     fn padding(&self) -> u32 {
-        self.deref().padding()
+        self.inner().padding()
     }
 }
 
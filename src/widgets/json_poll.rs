@@ -0,0 +1,93 @@
+use crate::{
+    utils::{http_client, HookSender, TimedHooks},
+    widget_default,
+    widgets::{Result, Text, Widget, WidgetConfig},
+};
+use async_trait::async_trait;
+use log::{debug, error, warn};
+use serde_json::Value;
+use std::{fmt::Display, time::Duration};
+use tokio::time::sleep;
+
+/// Displays a value extracted from a json api
+///
+/// Covers the common "poll an endpoint, pick a field, show it"
+/// integration without a custom [Widget] implementation
+pub struct JsonPoll {
+    url: String,
+    pointer: String,
+    interval: Duration,
+    formatter: Box<dyn Fn(&Value) -> String + Send>,
+    inner: Text,
+}
+
+impl std::fmt::Debug for JsonPoll {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("JsonPoll")
+            .field("url", &self.url)
+            .field("pointer", &self.pointer)
+            .field("interval", &self.interval)
+            .field("inner", &self.inner)
+            .finish_non_exhaustive()
+    }
+}
+
+impl JsonPoll {
+    ///* `url` fetched every `interval`
+    ///* `pointer` a json pointer (e.g. `/data/0/price`) selecting the value
+    ///* `formatter` turns the selected value into the displayed text
+    ///* `config` a [&WidgetConfig]
+    pub async fn new(
+        url: impl ToString,
+        interval: Duration,
+        pointer: impl ToString,
+        formatter: impl Fn(&Value) -> String + Send + 'static,
+        config: &WidgetConfig,
+    ) -> Box<Self> {
+        Box::new(Self {
+            url: url.to_string(),
+            pointer: pointer.to_string(),
+            interval,
+            formatter: Box::new(formatter),
+            inner: *Text::new("", config).await,
+        })
+    }
+}
+
+#[async_trait]
+impl Widget for JsonPoll {
+    async fn update(&mut self) -> Result<()> {
+        debug!("updating json_poll");
+        let Ok(Some(response)) = http_client().get_json(&self.url).await else {
+            return Ok(());
+        };
+        let Some(value) = response.pointer(&self.pointer) else {
+            warn!("no value at {} in response from {}", self.pointer, self.url);
+            return Ok(());
+        };
+        self.inner.set_text((self.formatter)(value));
+        Ok(())
+    }
+
+    async fn hook(&mut self, sender: HookSender, _timed_hooks: &mut TimedHooks) -> Result<()> {
+        let interval = self.interval;
+        tokio::spawn(async move {
+            loop {
+                if sender.send().await.is_err() {
+                    error!("breaking json_poll hook");
+                    break;
+                }
+                sleep(interval).await;
+            }
+        });
+        Ok(())
+    }
+
+    widget_default!(draw, size, padding);
+}
+
+impl Display for JsonPoll {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        String::from("JsonPoll").fmt(f)
+    }
+}
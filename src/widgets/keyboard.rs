@@ -0,0 +1,165 @@
+use crate::{
+    utils::{HookSender, TimedHooks},
+    widget_default,
+    widgets::{Result, Text, Widget, WidgetConfig},
+};
+use async_trait::async_trait;
+use log::{debug, error};
+use std::{fmt::Display, thread};
+use xcb::{Connection, Extension};
+
+/// Icons used by [Keyboard]
+#[derive(Debug)]
+pub struct KeyboardIcons {
+    pub caps_on: String,
+    pub caps_off: String,
+    pub num_on: String,
+    pub num_off: String,
+}
+
+impl Default for KeyboardIcons {
+    fn default() -> Self {
+        Self {
+            caps_on: String::from("󰪛"),
+            caps_off: String::from(""),
+            num_on: String::from("󰎠"),
+            num_off: String::from(""),
+        }
+    }
+}
+
+fn get_indicator_state(connection: &Connection) -> Result<u32> {
+    let cookie = connection.send_request(&xcb::xkb::GetIndicatorState {
+        device_spec: xcb::xkb::Id::UseCoreKbd as xcb::xkb::DeviceSpec,
+    });
+    let reply = connection.wait_for_reply(cookie).map_err(Error::Xcb)?;
+    Ok(reply.state())
+}
+
+fn enable_xkb(connection: &Connection) -> Result<()> {
+    connection
+        .wait_for_reply(connection.send_request(&xcb::xkb::UseExtension {
+            wanted_major: 1,
+            wanted_minor: 0,
+        }))
+        .map_err(Error::Xcb)?;
+    Ok(())
+}
+
+/// Displays the CapsLock and NumLock state
+pub struct Keyboard {
+    format: String,
+    icons: KeyboardIcons,
+    inner: Text,
+    connection: Connection,
+}
+
+impl std::fmt::Debug for Keyboard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "inner: {:?}", self.inner)
+    }
+}
+
+impl Keyboard {
+    ///* `format`
+    ///  * *%c* will be replaced with the CapsLock icon
+    ///  * *%n* will be replaced with the NumLock icon
+    ///* `icons` sets a custom [KeyboardIcons]
+    ///* `config` a [&WidgetConfig]
+    pub async fn new(
+        format: impl ToString,
+        icons: Option<KeyboardIcons>,
+        config: &WidgetConfig,
+    ) -> Result<Box<Self>> {
+        let (connection, _) =
+            Connection::connect_with_extensions(None, &[Extension::Xkb], &[]).map_err(Error::from)?;
+        enable_xkb(&connection)?;
+        Ok(Box::new(Self {
+            format: format.to_string(),
+            icons: icons.unwrap_or_default(),
+            inner: *Text::new("", config).await,
+            connection,
+        }))
+    }
+
+    fn build_string(&self, state: u32) -> String {
+        // indicator 0 is CapsLock, indicator 1 is NumLock
+        // in the core keyboard keymap
+        let caps = if state & 0x1 != 0 {
+            &self.icons.caps_on
+        } else {
+            &self.icons.caps_off
+        };
+        let num = if state & 0x2 != 0 {
+            &self.icons.num_on
+        } else {
+            &self.icons.num_off
+        };
+        self.format.replace("%c", caps).replace("%n", num)
+    }
+}
+
+#[async_trait]
+impl Widget for Keyboard {
+    async fn update(&mut self) -> Result<()> {
+        debug!("updating keyboard");
+        let state = get_indicator_state(&self.connection)?;
+        self.inner.set_text(self.build_string(state));
+        Ok(())
+    }
+
+    async fn hook(&mut self, sender: HookSender, _timed_hooks: &mut TimedHooks) -> Result<()> {
+        let (connection, _) =
+            Connection::connect_with_extensions(None, &[Extension::Xkb], &[]).map_err(Error::from)?;
+        enable_xkb(&connection)?;
+        connection
+            .send_and_check_request(&xcb::xkb::SelectEvents {
+                device_spec: xcb::xkb::Id::UseCoreKbd as xcb::xkb::DeviceSpec,
+                affect_which: xcb::xkb::EventType::INDICATOR_STATE_NOTIFY,
+                clear: xcb::xkb::EventType::empty(),
+                select_all: xcb::xkb::EventType::INDICATOR_STATE_NOTIFY,
+                affect_map: xcb::xkb::MapPart::empty(),
+                map: xcb::xkb::MapPart::empty(),
+                details: &[],
+            })
+            .map_err(Error::from)?;
+        connection.flush().map_err(Error::from)?;
+        thread::spawn(move || loop {
+            if matches!(
+                connection.wait_for_event(),
+                Ok(xcb::Event::Xkb(xcb::xkb::Event::IndicatorStateNotify(_)))
+            ) && sender.send_blocking().is_err()
+            {
+                error!("breaking keyboard hook");
+                break;
+            }
+        });
+        Ok(())
+    }
+
+    widget_default!(draw, size, padding);
+}
+
+impl Display for Keyboard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        String::from("Keyboard").fmt(f)
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error(transparent)]
+pub enum Error {
+    Xcb(#[from] xcb::Error),
+}
+
+impl From<xcb::ConnError> for Error {
+    fn from(e: xcb::ConnError) -> Self {
+        Error::Xcb(xcb::Error::Connection(e))
+    }
+}
+
+impl From<xcb::ProtocolError> for Error {
+    fn from(e: xcb::ProtocolError) -> Self {
+        Error::Xcb(xcb::Error::Protocol(e))
+    }
+}
@@ -0,0 +1,237 @@
+use crate::{
+    utils::{spawn, Color, HookSender, Popup, StatusBarInfo, TimedHooks},
+    widget_default,
+    widgets::{ClickEvent, MouseButton, Result, Text, Widget, WidgetConfig},
+};
+use async_trait::async_trait;
+use log::{debug, error};
+use std::{fmt::Display, fs, path::Path, thread};
+
+/// An application found in a .desktop file
+#[derive(Debug, Clone)]
+struct DesktopApp {
+    name: String,
+    exec: String,
+}
+
+/// Parses the [Desktop Entry] section of a .desktop file
+fn parse_desktop_file(path: &Path) -> Option<DesktopApp> {
+    let content = fs::read_to_string(path).ok()?;
+    let mut name = None;
+    let mut exec = None;
+    let mut in_entry = false;
+    for line in content.lines() {
+        if line.starts_with('[') {
+            in_entry = line.trim() == "[Desktop Entry]";
+            continue;
+        }
+        if !in_entry {
+            continue;
+        }
+        if let Some(value) = line.strip_prefix("NoDisplay=") {
+            if value.trim() == "true" {
+                return None;
+            }
+        } else if let Some(value) = line.strip_prefix("Name=") {
+            name.get_or_insert_with(|| value.trim().to_string());
+        } else if let Some(value) = line.strip_prefix("Exec=") {
+            // strip the freedesktop field codes (%f, %u, ...)
+            let cleaned = value
+                .split_whitespace()
+                .filter(|token| !token.starts_with('%'))
+                .collect::<Vec<_>>()
+                .join(" ");
+            exec.get_or_insert(cleaned);
+        }
+    }
+    Some(DesktopApp {
+        name: name?,
+        exec: exec?,
+    })
+}
+
+fn scan_applications() -> Vec<DesktopApp> {
+    let mut dirs = vec![String::from("/usr/share/applications")];
+    if let Ok(home) = std::env::var("HOME") {
+        dirs.push(format!("{home}/.local/share/applications"));
+    }
+    let mut apps = Vec::new();
+    for dir in dirs {
+        let Ok(entries) = fs::read_dir(dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().is_some_and(|e| e == "desktop") {
+                if let Some(app) = parse_desktop_file(&path) {
+                    apps.push(app);
+                }
+            }
+        }
+    }
+    apps.sort_by(|a, b| a.name.cmp(&b.name));
+    apps.dedup_by(|a, b| a.name == b.name);
+    apps
+}
+
+fn launch(app: &DesktopApp) {
+    debug!("launching {}", app.name);
+    spawn::spawn_sh(&*app.exec);
+}
+
+/// Reads the first keysym bound to a keycode
+fn keysym_for(keycode: u8, connection: &xcb::Connection) -> Option<u32> {
+    let cookie = connection.send_request(&xcb::x::GetKeyboardMapping {
+        first_keycode: keycode,
+        count: 1,
+    });
+    let reply = connection.wait_for_reply(cookie).ok()?;
+    reply.keysyms().first().copied()
+}
+
+const KEY_BACKSPACE: u32 = 0xff08;
+const KEY_RETURN: u32 = 0xff0d;
+const KEY_ESCAPE: u32 = 0xff1b;
+
+/// Runs the interactive popup until the user picks an application
+/// or dismisses it
+fn run_picker(mut popup: Popup, apps: Vec<DesktopApp>) {
+    let mut filter = String::new();
+    let refresh = |popup: &mut Popup, filter: &str| {
+        let mut lines = vec![format!("> {filter}")];
+        lines.extend(
+            apps.iter()
+                .filter(|a| a.name.to_lowercase().contains(&filter.to_lowercase()))
+                .map(|a| a.name.clone()),
+        );
+        if let Err(e) = popup.set_lines(lines) {
+            error!("failed to draw launcher popup: {e}");
+        }
+    };
+
+    if let Err(e) = popup.show() {
+        error!("failed to show launcher popup: {e}");
+        return;
+    }
+    refresh(&mut popup, &filter);
+    if let Err(e) = popup.grab_keyboard() {
+        error!("failed to grab keyboard: {e}");
+    }
+
+    loop {
+        let Ok(event) = popup.next_event() else {
+            break;
+        };
+        let xcb::Event::X(xcb::x::Event::KeyPress(key)) = event else {
+            continue;
+        };
+        let Some(keysym) = keysym_for(key.detail(), popup.connection()) else {
+            continue;
+        };
+        match keysym {
+            KEY_ESCAPE => break,
+            KEY_RETURN => {
+                if let Some(app) = apps
+                    .iter()
+                    .find(|a| a.name.to_lowercase().contains(&filter.to_lowercase()))
+                {
+                    launch(app);
+                }
+                break;
+            }
+            KEY_BACKSPACE => {
+                filter.pop();
+                refresh(&mut popup, &filter);
+            }
+            // printable ascii
+            0x20..=0x7e => {
+                filter.push(keysym as u8 as char);
+                refresh(&mut popup, &filter);
+            }
+            _ => {}
+        }
+    }
+    popup.ungrab_keyboard().ok();
+    popup.hide().ok();
+}
+
+/// An application launcher
+///
+/// Left click opens a popup listing the installed applications,
+/// typing filters the list, return launches the first match
+pub struct Launcher {
+    inner: Text,
+    config: WidgetConfig,
+    popup_y: i16,
+    popup_height: u16,
+}
+
+impl std::fmt::Debug for Launcher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "inner: {:?}", self.inner)
+    }
+}
+
+impl Launcher {
+    ///* `icon` the displayed label
+    ///* `popup_height` height of the application popup in pixel
+    ///* `config` a [&WidgetConfig]
+    pub async fn new(
+        icon: impl ToString,
+        popup_height: u16,
+        config: &WidgetConfig,
+    ) -> Box<Self> {
+        Box::new(Self {
+            inner: *Text::new(icon, config).await,
+            config: config.clone(),
+            popup_y: 0,
+            popup_height,
+        })
+    }
+}
+
+#[async_trait]
+impl Widget for Launcher {
+    async fn setup(&mut self, info: &StatusBarInfo) -> Result<()> {
+        self.popup_y = info.popup_y(self.popup_height);
+        Ok(())
+    }
+
+    async fn on_click(&mut self, event: ClickEvent) -> Result<()> {
+        if event.button != MouseButton::Left {
+            return Ok(());
+        }
+        let config = self.config.clone();
+        let popup_y = self.popup_y;
+        let popup_height = self.popup_height;
+        // the popup runs its own event loop, keep it off the bar one
+        thread::spawn(move || {
+            let apps = scan_applications();
+            match Popup::new(
+                0,
+                popup_y,
+                400,
+                popup_height,
+                Color::new(0.0, 0.0, 0.0, 0.9),
+                &config,
+            ) {
+                Ok(popup) => run_picker(popup, apps),
+                Err(e) => error!("failed to open launcher popup: {e}"),
+            }
+        });
+        Ok(())
+    }
+
+    async fn hook(&mut self, sender: HookSender, timed_hooks: &mut TimedHooks) -> Result<()> {
+        timed_hooks.subscribe(sender);
+        Ok(())
+    }
+
+    widget_default!(draw, size, padding);
+}
+
+impl Display for Launcher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        String::from("Launcher").fmt(f)
+    }
+}
@@ -1,27 +1,58 @@
 use crate::{
-    utils::{HookSender, TimedHooks},
+    utils::{connectivity, notify, Color, HookSender, Popup, StatusBarInfo, TimedHooks},
     widget_default,
-    widgets::{Result, Text, Widget, WidgetConfig, WidgetError},
+    widgets::{ClickEvent, MouseButton, Result, Text, Widget, WidgetConfig, WidgetError},
     xdg_cache, xdg_config,
 };
-use async_channel::Receiver;
+use async_channel::{Receiver, Sender};
 use async_trait::async_trait;
 use futures::Future;
 use imap::Session;
 use log::{debug, error, warn};
 use native_tls::TlsStream;
-use std::{fmt::Display, net::TcpStream, path::PathBuf, pin::Pin, time::Duration};
-use tokio::{process::Command, time::sleep};
+use std::{fmt::Display, net::TcpStream, path::PathBuf, pin::Pin, thread, time::Duration};
+use tokio::{
+    process::Command,
+    select,
+    time::{sleep, timeout},
+};
 use yup_oauth2::{
-    authenticator_delegate::{DefaultInstalledFlowDelegate, InstalledFlowDelegate},
-    InstalledFlowAuthenticator, InstalledFlowReturnMethod,
+    authenticator_delegate::{
+        DefaultInstalledFlowDelegate, DeviceAuthResponse, DeviceFlowDelegate, InstalledFlowDelegate,
+    },
+    DeviceFlowAuthenticator, InstalledFlowAuthenticator, InstalledFlowReturnMethod,
 };
 
+const POPUP_HEIGHT: u16 = 200;
+
+/// Unread counts and previews of every watched folder
+#[derive(Debug, Default, Clone)]
+struct MailData {
+    counts: Vec<(String, usize)>,
+    /// (sender, subject) of the latest unread messages
+    unread: Vec<(String, String)>,
+}
+
+impl MailData {
+    fn total(&self) -> usize {
+        self.counts.iter().map(|(_, count)| count).sum()
+    }
+}
+
 #[derive(Debug)]
 pub struct Mail {
     inner: Text,
     format: String,
-    message_receiver: Receiver<Result<usize>>,
+    folders: Vec<String>,
+    filter: String,
+    preview: usize,
+    authenticator: Option<Box<dyn ImapLogin>>,
+    data_sender: Sender<Result<MailData>>,
+    data_receiver: Receiver<Result<MailData>>,
+    data: MailData,
+    config: WidgetConfig,
+    popup_y: i16,
+    fetch_handle: Option<tokio::task::JoinHandle<()>>,
 }
 
 #[async_trait]
@@ -86,6 +117,8 @@ impl imap::Authenticator for GmailOAuth2 {
 pub struct GmailLogin {
     user: String,
     client_secret_path: PathBuf,
+    token_cache: Option<PathBuf>,
+    headless: bool,
 }
 
 impl GmailLogin {
@@ -96,33 +129,66 @@ impl GmailLogin {
         Self {
             user: user.to_string(),
             client_secret_path: config_path.join(client_secret_path.into()),
+            token_cache: None,
+            headless: false,
         }
     }
+
+    /// Where the oauth tokens are cached
+    /// (defaults to `tokencache.json` in the barust cache directory)
+    pub fn token_cache(mut self, path: impl Into<PathBuf>) -> Self {
+        self.token_cache = Some(path.into());
+        self
+    }
+
+    /// Authenticates with the device-code flow instead of
+    /// opening a browser, for machines without one
+    pub fn headless(mut self) -> Self {
+        self.headless = true;
+        self
+    }
 }
 
 #[async_trait]
 impl ImapLogin for GmailLogin {
     async fn login(&self) -> Result<Session<TlsStream<TcpStream>>> {
-        let cache_path = xdg_cache().map_err(Error::from)?;
         let secret = yup_oauth2::read_application_secret(&self.client_secret_path)
             .await
             .map_err(|e| {
                 Error::ClientSecret(e, self.client_secret_path.to_string_lossy().to_string())
             })?;
 
-        let persistent_path = cache_path.join(&self.user).join("tokencache.json");
+        let persistent_path = match &self.token_cache {
+            Some(path) => path.clone(),
+            None => xdg_cache()
+                .map_err(Error::from)?
+                .join(&self.user)
+                .join("tokencache.json"),
+        };
         std::fs::create_dir_all(persistent_path.parent().unwrap()).map_err(Error::from)?;
-        let auth =
+        let auth = if self.headless {
+            DeviceFlowAuthenticator::builder(secret)
+                .persist_tokens_to_disk(persistent_path)
+                .flow_delegate(Box::new(HeadlessDeviceDelegate::new(&self.user)))
+                .build()
+                .await
+                .map_err(Error::from)?
+        } else {
             InstalledFlowAuthenticator::builder(secret, InstalledFlowReturnMethod::HTTPRedirect)
                 .persist_tokens_to_disk(persistent_path)
                 .flow_delegate(Box::new(InstalledFlowBrowserDelegate::new(&self.user)))
                 .build()
                 .await
-                .map_err(Error::from)?;
+                .map_err(Error::from)?
+        };
 
         let scopes = &["https://mail.google.com/"];
 
-        let token = auth.token(scopes).await.map_err(Error::from)?;
+        // an expired refresh token would otherwise hang the fetch loop
+        let token = timeout(Duration::from_secs(30), auth.token(scopes))
+            .await
+            .map_err(|_| Error::OauthTimeout)?
+            .map_err(Error::from)?;
         let token = token.token().unwrap();
 
         let gmail_auth = GmailOAuth2 {
@@ -178,21 +244,57 @@ impl InstalledFlowDelegate for InstalledFlowBrowserDelegate {
                 .await
         }
         warn!("opening browser for oauth2");
-        let n = libnotify::Notification::new(
+        notify::notify(
             "Login gmail",
             format!("Login to {} account", self.user).as_str(),
-            None,
+            libnotify::Urgency::Normal,
         );
-        n.set_urgency(libnotify::Urgency::Normal);
-        n.show().ok();
 
         Box::pin(browser_user_url(url, need_code))
     }
 }
 
+/// Presents the device-code url through a notification
+/// instead of opening a browser
+#[derive(Clone)]
+struct HeadlessDeviceDelegate {
+    user: String,
+}
+
+impl HeadlessDeviceDelegate {
+    fn new(user: &str) -> Self {
+        Self {
+            user: user.to_string(),
+        }
+    }
+}
+
+impl DeviceFlowDelegate for HeadlessDeviceDelegate {
+    fn present_user_code<'a>(
+        &'a self,
+        response: &'a DeviceAuthResponse,
+    ) -> Pin<Box<dyn Future<Output = ()> + Send + 'a>> {
+        warn!(
+            "oauth device flow: visit {} and enter {}",
+            response.verification_uri, response.user_code
+        );
+        notify::notify(
+            "Login gmail",
+            format!(
+                "Visit {} and enter code {} for {}",
+                response.verification_uri, response.user_code, self.user
+            )
+            .as_str(),
+            libnotify::Urgency::Critical,
+        );
+        Box::pin(async {})
+    }
+}
+
 impl Mail {
     ///* `format`
-    ///  * *%c* will be replaced with the unread mail count
+    ///  * *%c* will be replaced with the total unread mail count
+    ///  * *%c{Folder}* will be replaced with the count of that folder
     ///* `domain` domain of the mail server
     ///* `authenticator` implements `ImapLogin`
     ///* `folder_name` folder to check for mail (defaults to "INBOX")
@@ -205,78 +307,223 @@ impl Mail {
         filter: impl Into<Option<&str>>,
         config: &WidgetConfig,
     ) -> Result<Box<Self>> {
-        let (tx, rx) = async_channel::unbounded();
-
-        let filter = filter.into().unwrap_or("(UNSEEN)").to_string();
-        let folder_name = folder_name.into().unwrap_or("INBOX").to_string();
-
-        tokio::task::spawn(async move {
-            loop {
-                let count =
-                    fetch_message_count(&authenticator, &folder_name, &filter).await;
-                if tx.send(count).await.is_err() {
-                    break;
-                }
-                sleep(Duration::from_secs(60)).await;
-            }
-            error!("mail thread broke");
-        });
+        let (data_sender, data_receiver) = async_channel::unbounded();
 
         Ok(Box::new(Self {
             inner: *Text::new("", config).await,
             format: format.to_string(),
-            message_receiver: rx,
+            folders: vec![folder_name.into().unwrap_or("INBOX").to_string()],
+            filter: filter.into().unwrap_or("(UNSEEN)").to_string(),
+            preview: 0,
+            authenticator: Some(Box::new(authenticator)),
+            data_sender,
+            data_receiver,
+            data: MailData::default(),
+            config: config.clone(),
+            popup_y: 0,
+            fetch_handle: None,
         }))
     }
+
+    /// Additional folders to watch beside `folder_name`,
+    /// addressable in the format as `%c{Folder}`
+    pub fn with_folders(mut self: Box<Self>, folders: Vec<impl ToString>) -> Box<Self> {
+        self.folders.extend(folders.iter().map(ToString::to_string));
+        self
+    }
+
+    /// Left click opens a popup listing the sender and subject
+    /// of the latest `items` unread messages
+    pub fn with_preview(mut self: Box<Self>, items: usize) -> Box<Self> {
+        self.preview = items;
+        self
+    }
+}
+
+/// First value of `name` in a raw header block
+fn header_value(header: &str, name: &str) -> Option<String> {
+    header.lines().find_map(|line| {
+        let (key, value) = line.split_once(':')?;
+        key.eq_ignore_ascii_case(name)
+            .then(|| value.trim().to_string())
+    })
 }
 
-async fn fetch_message_count(
-    authenticator: &impl ImapLogin,
-    folder_name: &str,
+async fn fetch_mail_data(
+    authenticator: &(impl ImapLogin + ?Sized),
+    folders: &[String],
     filter: &str,
-) -> Result<usize> {
+    preview: usize,
+) -> Result<MailData> {
     let mut session = authenticator.login().await?;
-    session.select(folder_name).map_err(Error::from)?;
-    let count = session
-        .search(filter)
-        .map(|ids| ids.len())
-        .map_err(Error::from)?;
-    Ok(count)
+    let mut data = MailData::default();
+    for folder in folders {
+        session.select(folder).map_err(Error::from)?;
+        let mut ids: Vec<u32> = session
+            .search(filter)
+            .map_err(Error::from)?
+            .into_iter()
+            .collect();
+        data.counts.push((folder.clone(), ids.len()));
+        if preview == 0 || ids.is_empty() {
+            continue;
+        }
+        // highest ids are the most recent messages
+        ids.sort_unstable_by(|a, b| b.cmp(a));
+        ids.truncate(preview);
+        let set = ids
+            .iter()
+            .map(u32::to_string)
+            .collect::<Vec<_>>()
+            .join(",");
+        // the imap crate does not expose ENVELOPE, read the headers
+        for message in session
+            .fetch(set, "RFC822.HEADER")
+            .map_err(Error::from)?
+            .iter()
+        {
+            let Some(header) = message.header() else {
+                continue;
+            };
+            let header = String::from_utf8_lossy(header);
+            let from = header_value(&header, "From").unwrap_or_default();
+            let subject = header_value(&header, "Subject").unwrap_or_default();
+            data.unread.push((from, subject));
+        }
+    }
+    data.unread.truncate(preview);
+    session.logout().ok();
+    Ok(data)
+}
+
+/// Runs the popup until the user clicks anywhere
+fn run_mail_preview(mut popup: Popup, lines: Vec<String>) {
+    if let Err(e) = popup.set_lines(lines) {
+        error!("failed to draw mail popup: {e}");
+        return;
+    }
+    if let Err(e) = popup.show() {
+        error!("failed to show mail popup: {e}");
+        return;
+    }
+    loop {
+        let Ok(event) = popup.next_event() else {
+            break;
+        };
+        if matches!(event, xcb::Event::X(xcb::x::Event::ButtonPress(_))) {
+            break;
+        }
+    }
+    popup.hide().ok();
 }
 
 #[async_trait]
 impl Widget for Mail {
+    async fn setup(&mut self, info: &StatusBarInfo) -> Result<()> {
+        self.popup_y = info.popup_y(POPUP_HEIGHT);
+        Ok(())
+    }
+
     async fn update(&mut self) -> Result<()> {
         debug!("updating mail");
-        let Ok(message_count) = self.message_receiver.try_recv() else {
+        let Ok(data) = self.data_receiver.try_recv() else {
             return Ok(());
         };
 
-        let message_count = match message_count {
-            Ok(c) => c,
+        let data = match data {
+            Ok(d) => d,
             Err(e) => {
                 if matches!(e, WidgetError::Mail(Error::ClientSecret(_, _))) {
                     // can't recover from this
                     return Err(e);
                 }
+                warn!("mail fetch failed: {e}");
+                // make the failure visible instead of showing stale counts
+                let mut text = self.format.clone();
+                for folder in &self.folders {
+                    text = text.replace(&format!("%c{{{folder}}}"), "!");
+                }
+                self.inner.set_text(text.replace("%c", "!"));
                 return Ok(());
             }
         };
+        self.data = data;
 
-        if message_count == 0 {
+        if self.data.total() == 0 {
             self.inner.clear();
         } else {
-            let new_text = self
-                .format
-                .replace("%c", message_count.to_string().as_str());
-            self.inner.set_text(new_text);
+            let mut text = self.format.clone();
+            for (folder, count) in &self.data.counts {
+                text = text.replace(&format!("%c{{{folder}}}"), &count.to_string());
+            }
+            let text = text.replace("%c", &self.data.total().to_string());
+            self.inner.set_text(text);
         };
 
         Ok(())
     }
 
+    async fn on_click(&mut self, event: ClickEvent) -> Result<()> {
+        if event.button != MouseButton::Left || self.data.unread.is_empty() {
+            return Ok(());
+        }
+        let lines: Vec<String> = self
+            .data
+            .unread
+            .iter()
+            .map(|(from, subject)| format!("{from}: {subject}"))
+            .collect();
+        let config = self.config.clone();
+        let popup_y = self.popup_y;
+        // the popup runs its own event loop, keep it off the bar one
+        thread::spawn(move || {
+            match Popup::new(
+                0,
+                popup_y,
+                500,
+                POPUP_HEIGHT,
+                Color::new(0.0, 0.0, 0.0, 0.9),
+                &config,
+            ) {
+                Ok(popup) => run_mail_preview(popup, lines),
+                Err(e) => error!("failed to open mail popup: {e}"),
+            }
+        });
+        Ok(())
+    }
+
     async fn hook(&mut self, sender: HookSender, pool: &mut TimedHooks) -> Result<()> {
         pool.subscribe(sender);
+        let Some(authenticator) = self.authenticator.take() else {
+            return Ok(());
+        };
+        let folders = self.folders.clone();
+        let filter = self.filter.clone();
+        let preview = self.preview;
+        let data_sender = self.data_sender.clone();
+        self.fetch_handle = Some(tokio::task::spawn(async move {
+            loop {
+                connectivity().wait_until_online().await;
+                let data = fetch_mail_data(&*authenticator, &folders, &filter, preview).await;
+                if data_sender.send(data).await.is_err() {
+                    break;
+                }
+                select!(
+                    _ = sleep(Duration::from_secs(60)) => {}
+                    // refetch as soon as the connection returns
+                    _ = connectivity().wait_for_reconnect() => {}
+                );
+            }
+            error!("mail thread broke");
+        }));
+        Ok(())
+    }
+
+    async fn teardown(&mut self) -> Result<()> {
+        // stop the fetch loop so no new sessions are opened
+        if let Some(handle) = &self.fetch_handle {
+            handle.abort();
+        }
         Ok(())
     }
 
@@ -297,5 +544,7 @@ pub enum Error {
     Io(#[from] std::io::Error),
     #[error("{0} while reading client secret at {1}")]
     ClientSecret(std::io::Error, String),
+    #[error("oauth token request timed out")]
+    OauthTimeout,
     YupOauth2(#[from] yup_oauth2::Error),
 }
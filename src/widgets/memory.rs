@@ -1,5 +1,5 @@
 use crate::{
-    utils::bytes_to_closest,
+    utils::{metrics, FormatValue},
     widget_default,
     widgets::{Result, Text, Widget, WidgetConfig},
 };
@@ -22,6 +22,8 @@ impl Memory {
     ///  * *%a* will be replaced with the available ram
     ///  * *%u* will be replaced with the used ram
     ///  * *%f* will be replaced with the free ram
+    ///
+    ///  `%{p:5.1}` style specifiers are supported, see [format](crate::utils::format)
     ///* `config` a [&WidgetConfig]
     pub async fn new(format: impl ToString, config: &WidgetConfig) -> Box<Self> {
         Box::new(Self {
@@ -36,14 +38,18 @@ impl Widget for Memory {
     async fn update(&mut self) -> Result<()> {
         debug!("updating memory");
         let ram = virtual_memory().map_err(Error::from)?;
-        let text = self
-            .format
-            .replace("%p", &format!("{:.2}", ram.percent()))
-            .replace("%t", &bytes_to_closest(ram.total()))
-            .replace("%a", &bytes_to_closest(ram.available()))
-            .replace("%u", &bytes_to_closest(ram.used()))
-            .replace("%f", &bytes_to_closest(ram.free()));
-        self.inner.set_text(text);
+        metrics::gauge("memory_used_percent", ram.percent().into());
+        metrics::gauge("memory_used_bytes", ram.used() as f64);
+        self.inner.set_format(
+            &self.format,
+            &[
+                ('p', FormatValue::Number(ram.percent().into())),
+                ('t', FormatValue::Bytes(ram.total())),
+                ('a', FormatValue::Bytes(ram.available())),
+                ('u', FormatValue::Bytes(ram.used())),
+                ('f', FormatValue::Bytes(ram.free())),
+            ],
+        );
         Ok(())
     }
 
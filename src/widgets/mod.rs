@@ -1,4 +1,4 @@
-use crate::utils::{Color, HookSender, Rectangle, StatusBarInfo, TimedHooks};
+use crate::utils::{Background, Color, HookSender, Position, Rectangle, StatusBarInfo, TimedHooks};
 use async_trait::async_trait;
 use cairo::Context;
 use std::{fmt::Display, time::Duration};
@@ -6,29 +6,56 @@ use thiserror::Error;
 
 mod replaceable;
 
-pub use replaceable::ReplaceableWidget;
+pub use replaceable::{OnError, ReplaceableWidget};
 
 mod active_window;
 mod bat;
 mod brightness;
+mod button;
+mod carousel;
+mod clickable;
 #[cfg(feature = "clock")]
 mod clock;
 #[cfg(feature = "cpu")]
 mod cpu;
 #[cfg(feature = "disk")]
 mod disk;
+mod dnd;
 mod icon;
+#[cfg(feature = "json")]
+mod json_poll;
+mod keyboard;
+mod launcher;
 mod mail;
 #[cfg(feature = "memory")]
 mod memory;
+mod mpd;
+#[cfg(feature = "mpris")]
+mod mpris;
 mod network;
+mod notifications;
+#[cfg(feature = "networkmanager")]
+mod nm;
 mod png;
+#[cfg(feature = "process")]
+mod process;
+mod redshift;
+mod registry;
+mod remote;
+#[cfg(feature = "rss")]
+mod rss;
 mod spacer;
 mod svg;
+#[cfg(feature = "systemd")]
+mod systemd;
 mod systray;
+mod task_list;
 #[cfg(feature = "temp")]
 mod temp;
 mod text;
+mod throttle;
+#[cfg(feature = "ticker")]
+mod ticker;
 mod update;
 mod volume;
 mod weather;
@@ -36,54 +63,130 @@ mod weather;
 mod wlan;
 mod workspaces;
 
-pub use active_window::ActiveWindow;
-pub use bat::{Battery, BatteryIcons, LowBatteryWarner, NotifySend};
-pub use brightness::Brightness;
+pub use active_window::{ActiveWindow, ClassIconMap, WindowAction};
+#[cfg(feature = "upower")]
+pub use bat::upower::{PeripheralIcons, Peripherals, UpowerProvider};
+pub use bat::{
+    Battery, BatteryIcons, BatteryProvider, LowBatteryWarner, NotifySend, PowerSupplyProvider,
+    SuspendAction,
+};
+#[cfg(feature = "ddc")]
+pub use brightness::ddc::DdcProvider;
+pub use brightness::{Brightness, BrightnessIcons, BrightnessProvider, SysfsProvider, XrandrProvider};
+pub use button::{screenshot, Button};
+pub use carousel::Carousel;
+pub use clickable::{Clickable, MouseAction};
 #[cfg(feature = "clock")]
 pub use clock::Clock;
 #[cfg(feature = "cpu")]
 pub use cpu::Cpu;
 #[cfg(feature = "disk")]
 pub use disk::Disk;
+pub use dnd::{DndIcons, DoNotDisturb};
 pub use icon::Icon;
+#[cfg(feature = "json")]
+pub use json_poll::JsonPoll;
+pub use keyboard::{Keyboard, KeyboardIcons};
+pub use launcher::Launcher;
 pub use mail::{GmailLogin, ImapLogin, Mail, PasswordLogin};
 #[cfg(feature = "memory")]
 pub use memory::Memory;
+pub use mpd::Mpd;
+#[cfg(feature = "mpris")]
+pub use mpris::Mpris;
 pub use network::{Network, NetworkIcons};
+pub use notifications::Notifications;
+#[cfg(feature = "networkmanager")]
+pub use nm::Nm;
 pub use png::Png;
+#[cfg(feature = "process")]
+pub use process::{ProcessMode, TopProcess};
+pub use redshift::{Redshift, RedshiftIcons};
+pub use registry::{WidgetBuilder, WidgetRegistry};
+pub use remote::Remote;
+#[cfg(feature = "rss")]
+pub use rss::Rss;
 pub use spacer::Spacer;
 pub use svg::Svg;
+#[cfg(feature = "systemd")]
+pub use systemd::Systemd;
 pub use systray::Systray;
+pub use task_list::TaskList;
 #[cfg(feature = "temp")]
 pub use temp::Temperatures;
-pub use text::Text;
-pub use update::{Apt, Update, UpdateSource};
+pub use text::{Marquee, Text, TextAlign};
+pub use throttle::{Throttle, ThrottleIcons};
+#[cfg(feature = "ticker")]
+pub use ticker::{CoinGeckoProvider, Ticker, TickerProvider};
+pub use update::{Apt, Flatpak, Update, UpdateSource};
 #[cfg(feature = "pulseaudio")]
 pub use volume::pulseaudio::PulseaudioProvider;
 pub use volume::{Volume, VolumeIcons, VolumeProvider};
 #[cfg(feature = "openmeteo")]
 pub use weather::openmeteo::OpenMeteoProvider;
-pub use weather::{MeteoIcons, Weather, WeatherProvider};
+pub use weather::{Meteo, MeteoIcons, TemperatureUnit, Weather, WeatherProvider};
 #[cfg(feature = "wlan")]
 pub use wlan::Wlan;
+#[cfg(feature = "qtile")]
+pub use workspaces::qtile::QtileStatusProvider;
 pub use workspaces::{
-    ActiveProvider, NeverHide, WorkspaceHider, WorkspaceStatus, WorkspaceStatusProvider, Workspaces,
+    ActiveProvider, HerbstluftwmProvider, NeverHide, OccupiedProvider, WorkspaceHider,
+    WorkspaceRenamer, WorkspaceRule, WorkspaceStatus, WorkspaceStatusProvider, Workspaces,
 };
 
+/// A mouse button (or scroll direction) pressed on a widget
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MouseButton {
+    Left,
+    Middle,
+    Right,
+    ScrollUp,
+    ScrollDown,
+}
+
+/// Keyboard modifiers held during a [ClickEvent]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ClickModifiers {
+    pub shift: bool,
+    pub ctrl: bool,
+    pub alt: bool,
+    /// the super/windows key
+    pub logo: bool,
+}
+
+/// A mouse press inside a widget region
+#[derive(Debug, Clone, Copy)]
+pub struct ClickEvent {
+    pub button: MouseButton,
+    pub modifiers: ClickModifiers,
+    /// horizontal position relative to the widget region
+    pub x: u32,
+}
+
+#[derive(Debug, Clone, Copy)]
 pub enum Size {
+    /// an equal share of the space left over by the static widgets
     Flex,
+    /// a weighted share of the leftover space, clamped to the bounds
+    Weighted {
+        weight: u32,
+        min: Option<u32>,
+        max: Option<u32>,
+    },
+    /// a fixed fraction (`0.0..=1.0`) of the leftover space
+    Percent(f64),
     Static(u32),
 }
 
 impl Size {
     pub fn is_flex(&self) -> bool {
-        matches!(self, Size::Flex)
+        !matches!(self, Size::Static(_))
     }
 
     pub fn unwrap_or(&self, s: u32) -> u32 {
         match self {
-            Size::Flex => s,
             Size::Static(s) => *s,
+            _ => s,
         }
     }
 }
@@ -102,8 +205,68 @@ pub trait Widget: std::fmt::Debug + Display + Send {
     async fn hook(&mut self, _sender: HookSender, _pool: &mut TimedHooks) -> Result<()> {
         Ok(())
     }
+    /// Called when a mouse button is pressed inside the widget region
+    async fn on_click(&mut self, _event: ClickEvent) -> Result<()> {
+        Ok(())
+    }
+    /// Called once before the bar exits so the widget can release
+    /// external resources (X windows, network sessions, ...)
+    async fn teardown(&mut self) -> Result<()> {
+        Ok(())
+    }
+    /// Serializes state that should survive a bar restart, see
+    /// [PersistentState](crate::utils::PersistentState)
+    fn save_state(&self) -> Option<serde_json::Value> {
+        None
+    }
+    /// Restores state produced by [save_state](Widget::save_state)
+    fn load_state(&mut self, _state: &serde_json::Value) {}
     fn size(&self, context: &Context) -> Result<Size>;
     fn padding(&self) -> u32;
+    /// Larger priorities survive longer when the bar overflows, see
+    /// [OverflowPolicy](crate::statusbar::OverflowPolicy)
+    fn priority(&self) -> u32 {
+        100
+    }
+}
+
+/// OSD-style progress bar drawn by hide_timeout widgets
+/// (Volume, Brightness) while their value is changing
+#[derive(Debug, Clone)]
+pub struct OsdBar {
+    /// widget width while the bar is visible
+    pub width: u32,
+    /// height of the bar line
+    pub thickness: u32,
+}
+
+impl Default for OsdBar {
+    fn default() -> Self {
+        Self {
+            width: 150,
+            thickness: 4,
+        }
+    }
+}
+
+/// Accent line drawn along the edge of a widget region,
+/// the classic module underline
+#[derive(Debug, Clone, Copy)]
+pub struct Accent {
+    pub color: Color,
+    pub thickness: u32,
+    /// edge of the region the line sticks to
+    pub position: Position,
+}
+
+impl Default for Accent {
+    fn default() -> Self {
+        Self {
+            color: Color::new(1.0, 1.0, 1.0, 1.0),
+            thickness: 2,
+            position: Position::Bottom,
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -112,8 +275,20 @@ pub struct WidgetConfig {
     pub font_size: f64,
     pub padding: u32,
     pub fg_color: Color,
+    pub background: Option<Background>,
     pub hide_timeout: Duration,
     pub flex: bool,
+    pub accent: Option<Accent>,
+    /// larger priorities survive longer when the bar overflows,
+    /// see [OverflowPolicy](crate::statusbar::OverflowPolicy)
+    pub priority: u32,
+    /// how text wider than its region is shortened
+    pub ellipsize: pango::EllipsizeMode,
+    /// wraps long text over multiple lines instead of overflowing,
+    /// mostly useful in tall bars
+    pub wrap: Option<pango::WrapMode>,
+    /// keeps newlines in the content from breaking the line
+    pub single_line: bool,
 }
 
 impl WidgetConfig {
@@ -122,6 +297,7 @@ impl WidgetConfig {
         font_size: f64,
         padding: u32,
         fg_color: Color,
+        background: Option<Background>,
         hide_timeout: Duration,
         flex: bool,
     ) -> WidgetConfig {
@@ -130,8 +306,22 @@ impl WidgetConfig {
             font_size,
             padding,
             fg_color,
+            background,
             hide_timeout,
             flex,
+            accent: None,
+            priority: 100,
+            ellipsize: pango::EllipsizeMode::None,
+            wrap: None,
+            single_line: true,
+        }
+    }
+
+    /// A default config using the [Theme](crate::utils::Theme) foreground color
+    pub fn from_theme(theme: &crate::utils::Theme) -> Self {
+        Self {
+            fg_color: theme.foreground,
+            ..Self::default()
         }
     }
 }
@@ -143,8 +333,14 @@ impl Default for WidgetConfig {
             font_size: 15.0,
             padding: 10,
             fg_color: Color::new(1.0, 1.0, 1.0, 1.0),
+            background: None,
             hide_timeout: Duration::from_secs(1),
             flex: false,
+            accent: None,
+            priority: 100,
+            ellipsize: pango::EllipsizeMode::None,
+            wrap: None,
+            single_line: true,
         }
     }
 }
@@ -161,16 +357,31 @@ pub enum WidgetError {
     Cpu(#[from] cpu::Error),
     #[cfg(feature = "disk")]
     Disk(#[from] disk::Error),
+    Dnd(#[from] dnd::Error),
     Icon(#[from] icon::Error),
+    Keyboard(#[from] keyboard::Error),
     Mail(#[from] mail::Error),
     #[cfg(feature = "memory")]
     Memory(#[from] memory::Error),
+    Mpd(#[from] mpd::Error),
+    #[cfg(feature = "mpris")]
+    Mpris(#[from] mpris::Error),
     Network(#[from] network::Error),
+    #[cfg(feature = "networkmanager")]
+    Nm(#[from] nm::Error),
     Png(#[from] png::Error),
+    #[cfg(feature = "process")]
+    Process(#[from] process::Error),
+    Registry(#[from] registry::Error),
+    #[cfg(feature = "rss")]
+    Rss(#[from] rss::Error),
     #[error("Spacer")]
     Spacer,
     Svg(#[from] svg::Error),
+    #[cfg(feature = "systemd")]
+    Systemd(#[from] systemd::Error),
     Systray(#[from] systray::Error),
+    TaskList(#[from] task_list::Error),
     #[cfg(feature = "temp")]
     Temperatures(#[from] temp::Error),
     Text(#[from] text::Error),
@@ -200,6 +411,10 @@ macro_rules! widget_default {
         fn padding(&self) -> u32 {
             self.inner.padding()
         }
+        // layout metadata travels with padding so every wrapper forwards it
+        fn priority(&self) -> u32 {
+            self.inner.priority()
+        }
     };
     (draw) => {
         fn draw(&self, context: cairo::Context, rectangle: &$crate::utils::Rectangle) -> Result<()> {
@@ -0,0 +1,210 @@
+use crate::{
+    utils::{HookSender, TimedHooks},
+    widget_default,
+    widgets::{ClickEvent, MouseButton, Result, Text, Widget, WidgetConfig},
+};
+use async_trait::async_trait;
+use log::debug;
+use std::{collections::HashMap, fmt::Display, time::Duration};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    net::TcpStream,
+    spawn,
+    time::sleep,
+};
+
+/// Runs `command` on a fresh connection and parses the
+/// `key: value` response lines
+async fn run_command(address: &str, command: &str) -> std::io::Result<HashMap<String, String>> {
+    let stream = TcpStream::connect(address).await?;
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+    // greeting
+    reader.read_line(&mut line).await?;
+    reader.get_mut().write_all(command.as_bytes()).await?;
+    reader.get_mut().write_all(b"\n").await?;
+    let mut values = HashMap::new();
+    loop {
+        line.clear();
+        if reader.read_line(&mut line).await? == 0 {
+            break;
+        }
+        let line = line.trim_end();
+        if line == "OK" || line.starts_with("ACK") {
+            break;
+        }
+        if let Some((key, value)) = line.split_once(": ") {
+            values.insert(key.to_string(), value.to_string());
+        }
+    }
+    Ok(values)
+}
+
+/// Blocks on the MPD `idle` command and notifies the bar on every change
+async fn idle_loop(address: &str, sender: &HookSender) -> std::io::Result<()> {
+    let stream = TcpStream::connect(address).await?;
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+    reader.read_line(&mut line).await?;
+    loop {
+        reader
+            .get_mut()
+            .write_all(b"idle player mixer options\n")
+            .await?;
+        loop {
+            line.clear();
+            if reader.read_line(&mut line).await? == 0 {
+                return Err(std::io::ErrorKind::ConnectionReset.into());
+            }
+            if line.trim_end() == "OK" {
+                break;
+            }
+        }
+        if sender.send().await.is_err() {
+            debug!("breaking mpd idle loop");
+            return Ok(());
+        }
+    }
+}
+
+fn format_time(seconds: f64) -> String {
+    let seconds = seconds as u64;
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+/// Displays the current MPD song, talking the MPD protocol
+/// directly so no MPRIS bridge is needed
+///
+/// Left click toggles play/pause, right and middle click skip,
+/// scrolling changes the volume
+#[derive(Debug)]
+pub struct Mpd {
+    format: String,
+    address: String,
+    inner: Text,
+}
+
+impl Mpd {
+    ///* `format`
+    ///  * *%t* will be replaced with the song title
+    ///  * *%a* will be replaced with the artist
+    ///  * *%e* will be replaced with the elapsed time (m:ss)
+    ///  * *%d* will be replaced with the song duration (m:ss)
+    ///  * *%s* will be replaced with the playback status icon
+    ///* `address` of the MPD server (defaults to `127.0.0.1:6600`)
+    ///* `config` a [&WidgetConfig]
+    pub async fn new(
+        format: impl ToString,
+        address: Option<String>,
+        config: &WidgetConfig,
+    ) -> Box<Self> {
+        Box::new(Self {
+            format: format.to_string(),
+            address: address.unwrap_or_else(|| String::from("127.0.0.1:6600")),
+            inner: *Text::new("", config).await,
+        })
+    }
+}
+
+#[async_trait]
+impl Widget for Mpd {
+    async fn update(&mut self) -> Result<()> {
+        debug!("updating mpd");
+        let status = match run_command(&self.address, "status").await {
+            Ok(status) => status,
+            Err(e) => {
+                debug!("mpd unreachable: {e}");
+                self.inner.clear();
+                return Ok(());
+            }
+        };
+        let state = status.get("state").map(String::as_str).unwrap_or("stop");
+        if state == "stop" {
+            self.inner.clear();
+            return Ok(());
+        }
+        let song = run_command(&self.address, "currentsong")
+            .await
+            .unwrap_or_default();
+        let elapsed = status
+            .get("elapsed")
+            .and_then(|v| v.parse::<f64>().ok())
+            .unwrap_or(0.0);
+        let duration = status
+            .get("duration")
+            .and_then(|v| v.parse::<f64>().ok())
+            .unwrap_or(0.0);
+        let icon = if state == "play" { "▶" } else { "⏸" };
+        let text = self
+            .format
+            .replace("%t", song.get("Title").map(String::as_str).unwrap_or(""))
+            .replace("%a", song.get("Artist").map(String::as_str).unwrap_or(""))
+            .replace("%e", &format_time(elapsed))
+            .replace("%d", &format_time(duration))
+            .replace("%s", icon);
+        self.inner.set_text(text);
+        Ok(())
+    }
+
+    async fn on_click(&mut self, event: ClickEvent) -> Result<()> {
+        let status = run_command(&self.address, "status")
+            .await
+            .map_err(Error::from)?;
+        let command = match event.button {
+            MouseButton::Left => {
+                if status.get("state").map(String::as_str) == Some("play") {
+                    String::from("pause 1")
+                } else {
+                    String::from("play")
+                }
+            }
+            MouseButton::Right => String::from("next"),
+            MouseButton::Middle => String::from("previous"),
+            MouseButton::ScrollUp | MouseButton::ScrollDown => {
+                let volume: i64 = status
+                    .get("volume")
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or(50);
+                let delta = if event.button == MouseButton::ScrollUp {
+                    5
+                } else {
+                    -5
+                };
+                format!("setvol {}", (volume + delta).clamp(0, 100))
+            }
+        };
+        run_command(&self.address, &command)
+            .await
+            .map_err(Error::from)?;
+        Ok(())
+    }
+
+    async fn hook(&mut self, sender: HookSender, timed_hooks: &mut TimedHooks) -> Result<()> {
+        // the elapsed time still ticks while idle is quiet
+        timed_hooks.subscribe(sender.clone());
+        let address = self.address.clone();
+        spawn(async move {
+            loop {
+                if let Err(e) = idle_loop(&address, &sender).await {
+                    debug!("mpd idle connection lost: {e}");
+                }
+                sleep(Duration::from_secs(5)).await;
+            }
+        });
+        Ok(())
+    }
+
+    widget_default!(draw, size, padding);
+}
+
+impl Display for Mpd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        String::from("Mpd").fmt(f)
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error(transparent)]
+pub enum Error {
+    Io(#[from] std::io::Error),
+}
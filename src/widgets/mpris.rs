@@ -0,0 +1,252 @@
+use crate::{
+    utils::{HookSender, OwnedImageSurface, TimedHooks},
+    widgets::{Rectangle, Result, Size, Text, Widget, WidgetConfig},
+    xdg_cache,
+};
+use async_trait::async_trait;
+use cairo::{Context, ImageSurface};
+use log::{debug, error};
+use std::{collections::HashMap, fmt::Display, fs::File, path::PathBuf};
+use zbus::zvariant::OwnedValue;
+
+const MPRIS_PREFIX: &str = "org.mpris.MediaPlayer2.";
+const MPRIS_PATH: &str = "/org/mpris/MediaPlayer2";
+const PLAYER_INTERFACE: &str = "org.mpris.MediaPlayer2.Player";
+
+/// gap between the artwork and the title
+const ARTWORK_GAP: u32 = 5;
+
+/// Downloads the artwork into the cache directory, once per url
+async fn cached_artwork(url: &str) -> Option<PathBuf> {
+    let mut name: String = url
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    name.truncate(100);
+    let path = xdg_cache().ok()?.join(name);
+    if path.exists() {
+        return Some(path);
+    }
+    let bytes = reqwest::get(url).await.ok()?.bytes().await.ok()?;
+    tokio::fs::write(&path, &bytes).await.ok()?;
+    Some(path)
+}
+
+/// Displays the currently playing track over MPRIS
+///
+/// When several players are running the first match in `players`
+/// wins (e.g. spotify over the browser)
+pub struct Mpris {
+    format: String,
+    players: Vec<String>,
+    connection: zbus::Connection,
+    artwork_size: Option<u32>,
+    artwork: Option<OwnedImageSurface>,
+    artwork_url: Option<String>,
+    inner: Text,
+}
+
+impl std::fmt::Debug for Mpris {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "format: {:?}, players: {:?}", self.format, self.players)
+    }
+}
+
+impl Mpris {
+    ///* `format`
+    ///  * *%t* will be replaced with the track title
+    ///  * *%a* will be replaced with the artist
+    ///  * *%s* will be replaced with the playback status icon
+    ///* `players` player names in order of priority, all players
+    ///  are accepted when empty
+    ///* `config` a [&WidgetConfig]
+    pub async fn new(
+        format: impl ToString,
+        players: Vec<impl ToString>,
+        config: &WidgetConfig,
+    ) -> Result<Box<Self>> {
+        let connection = zbus::Connection::session().await.map_err(Error::from)?;
+        Ok(Box::new(Self {
+            format: format.to_string(),
+            players: players.iter().map(ToString::to_string).collect(),
+            connection,
+            artwork_size: None,
+            artwork: None,
+            artwork_url: None,
+            inner: *Text::new("", config).await,
+        }))
+    }
+
+    /// Renders the album artwork next to the title, scaled into
+    /// a square of `size` pixels and cached on disk
+    pub fn with_artwork(mut self: Box<Self>, size: u32) -> Box<Self> {
+        self.artwork_size = Some(size);
+        self
+    }
+
+    /// The running player with the highest priority
+    async fn active_player(&self) -> zbus::Result<Option<String>> {
+        let dbus = zbus::fdo::DBusProxy::new(&self.connection).await?;
+        let names: Vec<String> = dbus
+            .list_names()
+            .await?
+            .into_iter()
+            .map(|name| name.to_string())
+            .filter(|name| name.starts_with(MPRIS_PREFIX))
+            .collect();
+        for player in &self.players {
+            if let Some(name) = names.iter().find(|name| name.contains(player.as_str())) {
+                return Ok(Some(name.clone()));
+            }
+        }
+        if self.players.is_empty() {
+            return Ok(names.into_iter().next());
+        }
+        Ok(None)
+    }
+
+    async fn player_proxy<'a>(&self, name: &'a str) -> zbus::Result<zbus::Proxy<'a>> {
+        zbus::Proxy::new(&self.connection, name.to_string(), MPRIS_PATH, PLAYER_INTERFACE).await
+    }
+
+    async fn load_artwork(&mut self, url: Option<&str>) {
+        if self.artwork_url.as_deref() == url {
+            return;
+        }
+        self.artwork_url = url.map(String::from);
+        self.artwork = None;
+        let Some(url) = url else {
+            return;
+        };
+        let path = if let Some(file) = url.strip_prefix("file://") {
+            PathBuf::from(file)
+        } else {
+            match cached_artwork(url).await {
+                Some(path) => path,
+                None => return,
+            }
+        };
+        let surface = File::open(&path)
+            .map_err(Error::from)
+            .and_then(|mut file| ImageSurface::create_from_png(&mut file).map_err(Error::from))
+            .and_then(|surface| OwnedImageSurface::new(surface).map_err(Error::from));
+        match surface {
+            Ok(surface) => self.artwork = Some(surface),
+            // players often serve jpeg artwork, cairo only decodes png
+            Err(e) => debug!("failed to decode artwork: {e}"),
+        }
+    }
+}
+
+#[async_trait]
+impl Widget for Mpris {
+    async fn update(&mut self) -> Result<()> {
+        debug!("updating mpris");
+        let player = match self.active_player().await {
+            Ok(Some(player)) => player,
+            Ok(None) => {
+                self.inner.clear();
+                self.load_artwork(None).await;
+                return Ok(());
+            }
+            Err(e) => {
+                error!("mpris query failed: {e}");
+                return Ok(());
+            }
+        };
+        let proxy = self.player_proxy(&player).await.map_err(Error::from)?;
+        let metadata: HashMap<String, OwnedValue> = proxy
+            .get_property("Metadata")
+            .await
+            .unwrap_or_default();
+        let title = metadata
+            .get("xesam:title")
+            .and_then(|v| v.downcast_ref::<String>().ok())
+            .unwrap_or_default();
+        let artist = metadata
+            .get("xesam:artist")
+            .and_then(|v| Vec::<String>::try_from(v.clone()).ok())
+            .unwrap_or_default()
+            .join(", ");
+        let status: String = proxy
+            .get_property("PlaybackStatus")
+            .await
+            .unwrap_or_default();
+        let status_icon = match status.as_str() {
+            "Playing" => "▶",
+            "Paused" => "⏸",
+            _ => "⏹",
+        };
+        if self.artwork_size.is_some() {
+            let art_url = metadata
+                .get("mpris:artUrl")
+                .and_then(|v| v.downcast_ref::<String>().ok());
+            self.load_artwork(art_url.as_deref()).await;
+        }
+        let text = self
+            .format
+            .replace("%t", &title)
+            .replace("%a", &artist)
+            .replace("%s", status_icon);
+        self.inner.set_text(text);
+        Ok(())
+    }
+
+    fn draw(&self, context: Context, rectangle: &Rectangle) -> Result<()> {
+        let mut text_rectangle = *rectangle;
+        if let (Some(size), Some(artwork)) = (self.artwork_size, &self.artwork) {
+            let side = f64::from(size.min(rectangle.height));
+            artwork
+                .with_surface(|surface: &ImageSurface| -> std::result::Result<(), Error> {
+                    context.save()?;
+                    context.scale(
+                        side / f64::from(surface.width()),
+                        side / f64::from(surface.height()),
+                    );
+                    context.set_source_surface(surface, 0.0, 0.0)?;
+                    context.paint()?;
+                    // restoring drops the reference to the surface
+                    context.restore()?;
+                    Ok(())
+                })?;
+            context.translate(f64::from(size + ARTWORK_GAP), 0.0);
+            text_rectangle.width = rectangle.width.saturating_sub(size + ARTWORK_GAP);
+        }
+        self.inner.draw(context, &text_rectangle)
+    }
+
+    fn size(&self, context: &Context) -> Result<Size> {
+        let size = self.inner.size(context)?;
+        if let (Some(artwork_size), Some(_)) = (self.artwork_size, &self.artwork) {
+            if let Size::Static(width) = size {
+                return Ok(Size::Static(width + artwork_size + ARTWORK_GAP));
+            }
+        }
+        Ok(size)
+    }
+
+    async fn hook(&mut self, sender: HookSender, timed_hooks: &mut TimedHooks) -> Result<()> {
+        timed_hooks.subscribe(sender);
+        Ok(())
+    }
+
+    fn padding(&self) -> u32 {
+        self.inner.padding()
+    }
+}
+
+impl Display for Mpris {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        String::from("Mpris").fmt(f)
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error(transparent)]
+pub enum Error {
+    BorrowCairo(#[from] cairo::BorrowError),
+    Cairo(#[from] cairo::Error),
+    CairoIo(#[from] cairo::IoError),
+    Io(#[from] std::io::Error),
+    Zbus(#[from] zbus::Error),
+}
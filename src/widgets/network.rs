@@ -1,4 +1,5 @@
 use crate::{
+    utils::{icons, IconSet, IconTheme, StatusBarInfo},
     widget_default,
     widgets::{Result, Text, Widget, WidgetConfig},
 };
@@ -41,6 +42,31 @@ impl Default for NetworkIcons {
     }
 }
 
+impl IconSet for NetworkIcons {
+    fn glyphs(&self) -> Vec<&str> {
+        vec![&self.wireless, &self.ethernet, &self.online, &self.offline]
+    }
+
+    fn for_theme(theme: IconTheme) -> Self {
+        match theme {
+            IconTheme::NerdFont => Self {
+                wireless: String::from("󰖩"),
+                ethernet: String::from("󰈀"),
+                online: String::from("󰱓"),
+                offline: String::from("󰅛"),
+            },
+            IconTheme::Emoji => Self {
+                wireless: String::from("📶"),
+                ethernet: String::from("🔌"),
+                online: String::from("✔"),
+                offline: String::from("✖"),
+            },
+            // the default icons are already plain text
+            IconTheme::Ascii => Self::default(),
+        }
+    }
+}
+
 /// Displays informations about a network interface
 #[derive(Debug)]
 pub struct Network {
@@ -75,6 +101,11 @@ impl Network {
 
 #[async_trait]
 impl Widget for Network {
+    async fn setup(&mut self, _info: &StatusBarInfo) -> Result<()> {
+        icons::warn_missing_glyphs(&self.icons, self.inner.font(), "Network");
+        Ok(())
+    }
+
     async fn update(&mut self) -> Result<()> {
         debug!("updating network");
         let text = if let Ok((wireless, online)) = get_interface_stats(&self.interface) {
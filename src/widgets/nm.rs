@@ -0,0 +1,353 @@
+use crate::{
+    utils::{Color, HookSender, Popup, StatusBarInfo, TimedHooks},
+    widget_default,
+    widgets::{ClickEvent, MouseButton, Result, Text, Widget, WidgetConfig},
+};
+use async_channel::{bounded, Receiver, Sender};
+use async_trait::async_trait;
+use log::{debug, error, warn};
+use std::{collections::HashMap, fmt::Display, thread};
+use zbus::zvariant::{ObjectPath, OwnedObjectPath, OwnedValue};
+
+const NM_DEST: &str = "org.freedesktop.NetworkManager";
+const NM_PATH: &str = "/org/freedesktop/NetworkManager";
+
+const POPUP_HEIGHT: u16 = 200;
+
+/// DeviceType of wifi adapters in the NetworkManager spec
+const WIRELESS_DEVICE: u32 = 2;
+
+/// Renders a strength percentage as signal bars
+fn signal_bars(strength: u8) -> String {
+    let bars = ["▂", "▄", "▆", "█"];
+    let filled = (usize::from(strength) * bars.len()).div_ceil(100).min(bars.len());
+    let mut out = String::new();
+    for (index, bar) in bars.iter().enumerate() {
+        if index < filled {
+            out.push_str(bar);
+        } else {
+            out.push(' ');
+        }
+    }
+    out
+}
+
+/// Runs the network popup until the user picks an ssid or closes it
+fn run_network_picker(
+    mut popup: Popup,
+    lines: Vec<String>,
+    ssids: Vec<String>,
+    selection: Sender<String>,
+) {
+    if let Err(e) = popup.set_lines(lines) {
+        error!("failed to draw network popup: {e}");
+        return;
+    }
+    if let Err(e) = popup.show() {
+        error!("failed to show network popup: {e}");
+        return;
+    }
+    loop {
+        let Ok(event) = popup.next_event() else {
+            break;
+        };
+        let xcb::Event::X(xcb::x::Event::ButtonPress(press)) = event else {
+            continue;
+        };
+        if press.detail() == 1 {
+            if let Some(index) = popup.line_at(f64::from(press.event_y())) {
+                let _ = selection.send_blocking(ssids[index].clone());
+            }
+        }
+        break;
+    }
+    popup.hide().ok();
+}
+
+async fn nm_proxy<'a, P>(
+    connection: &zbus::Connection,
+    path: P,
+    interface: &'a str,
+) -> zbus::Result<zbus::Proxy<'a>>
+where
+    P: TryInto<ObjectPath<'a>>,
+    P::Error: Into<zbus::Error>,
+{
+    zbus::Proxy::new(connection, NM_DEST, path, interface).await
+}
+
+/// The connection currently routing traffic
+#[derive(Debug, Default)]
+struct ActiveConnection {
+    name: String,
+    connection_type: String,
+    signal: Option<u8>,
+}
+
+/// Displays the active NetworkManager connection
+///
+/// Left click opens a popup listing the visible wifi networks,
+/// clicking one connects to it when a saved connection exists,
+/// right click toggles Wi-Fi
+pub struct Nm {
+    format: String,
+    inner: Text,
+    connection: zbus::Connection,
+    config: WidgetConfig,
+    popup_y: i16,
+    pending_connect: Option<Receiver<String>>,
+}
+
+impl std::fmt::Debug for Nm {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "inner: {:?}", self.inner)
+    }
+}
+
+impl Nm {
+    ///* `format`
+    ///  * *%n* will be replaced with the active connection name
+    ///  * *%t* will be replaced with the connection type
+    ///  * *%s* will be replaced with the signal strength (Wi-Fi only)
+    ///* `config` a [&WidgetConfig]
+    pub async fn new(format: impl ToString, config: &WidgetConfig) -> Result<Box<Self>> {
+        let connection = zbus::Connection::system().await.map_err(Error::from)?;
+        Ok(Box::new(Self {
+            format: format.to_string(),
+            inner: *Text::new("", config).await,
+            connection,
+            config: config.clone(),
+            popup_y: 0,
+            pending_connect: None,
+        }))
+    }
+
+    async fn active_connection(&self) -> zbus::Result<Option<ActiveConnection>> {
+        let nm = nm_proxy(&self.connection, NM_PATH, NM_DEST).await?;
+        let primary: OwnedObjectPath = nm.get_property("PrimaryConnection").await?;
+        if primary.as_str() == "/" {
+            return Ok(None);
+        }
+
+        let active = nm_proxy(
+            &self.connection,
+            primary,
+            "org.freedesktop.NetworkManager.Connection.Active",
+        )
+        .await?;
+        let name: String = active.get_property("Id").await?;
+        let connection_type: String = active.get_property("Type").await?;
+
+        let mut signal = None;
+        if connection_type.contains("wireless") {
+            let access_point: OwnedObjectPath = active.get_property("SpecificObject").await?;
+            if access_point.as_str() != "/" {
+                let ap = nm_proxy(
+                    &self.connection,
+                    access_point,
+                    "org.freedesktop.NetworkManager.AccessPoint",
+                )
+                .await?;
+                signal = ap.get_property("Strength").await.ok();
+            }
+        }
+
+        Ok(Some(ActiveConnection {
+            name,
+            connection_type,
+            signal,
+        }))
+    }
+
+    async fn toggle_wifi(&self) -> zbus::Result<()> {
+        let nm = nm_proxy(&self.connection, NM_PATH, NM_DEST).await?;
+        let enabled: bool = nm.get_property("WirelessEnabled").await?;
+        nm.set_property("WirelessEnabled", !enabled).await?;
+        Ok(())
+    }
+
+    /// Visible wifi networks as (ssid, strength), strongest first
+    async fn visible_networks(&self) -> zbus::Result<Vec<(String, u8)>> {
+        let nm = nm_proxy(&self.connection, NM_PATH, NM_DEST).await?;
+        let devices: Vec<OwnedObjectPath> = nm.call("GetDevices", &()).await?;
+        let mut networks: HashMap<String, u8> = HashMap::new();
+        for path in devices {
+            let device = nm_proxy(
+                &self.connection,
+                path.clone(),
+                "org.freedesktop.NetworkManager.Device",
+            )
+            .await?;
+            let device_type: u32 = device.get_property("DeviceType").await?;
+            if device_type != WIRELESS_DEVICE {
+                continue;
+            }
+            let wireless = nm_proxy(
+                &self.connection,
+                path,
+                "org.freedesktop.NetworkManager.Device.Wireless",
+            )
+            .await?;
+            let access_points: Vec<OwnedObjectPath> =
+                wireless.get_property("AccessPoints").await?;
+            for ap_path in access_points {
+                let ap = nm_proxy(
+                    &self.connection,
+                    ap_path,
+                    "org.freedesktop.NetworkManager.AccessPoint",
+                )
+                .await?;
+                let ssid: Vec<u8> = ap.get_property("Ssid").await?;
+                let ssid = String::from_utf8_lossy(&ssid).to_string();
+                if ssid.is_empty() {
+                    continue;
+                }
+                let strength: u8 = ap.get_property("Strength").await?;
+                let entry = networks.entry(ssid).or_default();
+                *entry = (*entry).max(strength);
+            }
+        }
+        let mut networks: Vec<_> = networks.into_iter().collect();
+        networks.sort_by(|a, b| b.1.cmp(&a.1));
+        Ok(networks)
+    }
+
+    /// Activates the saved connection matching `ssid`
+    /// returns false when none is saved
+    async fn connect_known(&self, ssid: &str) -> zbus::Result<bool> {
+        let settings = nm_proxy(
+            &self.connection,
+            "/org/freedesktop/NetworkManager/Settings",
+            "org.freedesktop.NetworkManager.Settings",
+        )
+        .await?;
+        let paths: Vec<OwnedObjectPath> = settings.call("ListConnections", &()).await?;
+        for path in paths {
+            let proxy = nm_proxy(
+                &self.connection,
+                path.clone(),
+                "org.freedesktop.NetworkManager.Settings.Connection",
+            )
+            .await?;
+            let connection_settings: HashMap<String, HashMap<String, OwnedValue>> =
+                proxy.call("GetSettings", &()).await?;
+            let id = connection_settings
+                .get("connection")
+                .and_then(|c| c.get("id"))
+                .and_then(|v| v.downcast_ref::<String>().ok());
+            if id.as_deref() != Some(ssid) {
+                continue;
+            }
+            let nm = nm_proxy(&self.connection, NM_PATH, NM_DEST).await?;
+            // the device and access point are left for NetworkManager to pick
+            let root = ObjectPath::try_from("/").expect("`/` is a valid path");
+            let _: OwnedObjectPath = nm
+                .call("ActivateConnection", &(path, root.clone(), root))
+                .await?;
+            return Ok(true);
+        }
+        Ok(false)
+    }
+}
+
+#[async_trait]
+impl Widget for Nm {
+    async fn setup(&mut self, info: &StatusBarInfo) -> Result<()> {
+        // open the popup just below (or above) the bar
+        self.popup_y = info.popup_y(POPUP_HEIGHT);
+        Ok(())
+    }
+
+    async fn update(&mut self) -> Result<()> {
+        debug!("updating nm");
+        if let Some(receiver) = &self.pending_connect {
+            if let Ok(ssid) = receiver.try_recv() {
+                match self.connect_known(&ssid).await {
+                    Ok(true) => debug!("connecting to {ssid}"),
+                    Ok(false) => warn!("no saved connection for {ssid}"),
+                    Err(e) => error!("failed to connect to {ssid}: {e}"),
+                }
+                self.pending_connect = None;
+            }
+        }
+        let text = match self.active_connection().await {
+            Ok(Some(active)) => self
+                .format
+                .replace("%n", &active.name)
+                .replace("%t", &active.connection_type)
+                .replace(
+                    "%s",
+                    &active.signal.map(|s| s.to_string()).unwrap_or_default(),
+                ),
+            Ok(None) => String::from("offline"),
+            Err(e) => {
+                error!("nm query failed: {e}");
+                return Ok(());
+            }
+        };
+        self.inner.set_text(text);
+        Ok(())
+    }
+
+    async fn on_click(&mut self, event: ClickEvent) -> Result<()> {
+        match event.button {
+            MouseButton::Left => {
+                let networks = match self.visible_networks().await {
+                    Ok(networks) => networks,
+                    Err(e) => {
+                        error!("failed to scan networks: {e}");
+                        return Ok(());
+                    }
+                };
+                if networks.is_empty() {
+                    return Ok(());
+                }
+                let lines: Vec<String> = networks
+                    .iter()
+                    .map(|(ssid, strength)| format!("{}  {ssid}", signal_bars(*strength)))
+                    .collect();
+                let ssids: Vec<String> = networks.into_iter().map(|(ssid, _)| ssid).collect();
+                let (tx, rx) = bounded(1);
+                self.pending_connect = Some(rx);
+                let config = self.config.clone();
+                let popup_y = self.popup_y;
+                // the popup runs its own event loop, keep it off the bar one
+                thread::spawn(move || {
+                    match Popup::new(
+                        0,
+                        popup_y,
+                        300,
+                        POPUP_HEIGHT,
+                        Color::new(0.0, 0.0, 0.0, 0.9),
+                        &config,
+                    ) {
+                        Ok(popup) => run_network_picker(popup, lines, ssids, tx),
+                        Err(e) => error!("failed to open network popup: {e}"),
+                    }
+                });
+            }
+            MouseButton::Right => self.toggle_wifi().await.map_err(Error::from)?,
+            _ => {}
+        }
+        Ok(())
+    }
+
+    async fn hook(&mut self, sender: HookSender, timed_hooks: &mut TimedHooks) -> Result<()> {
+        timed_hooks.subscribe(sender);
+        Ok(())
+    }
+
+    widget_default!(draw, size, padding);
+}
+
+impl Display for Nm {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        String::from("Nm").fmt(f)
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error(transparent)]
+pub enum Error {
+    Zbus(#[from] zbus::Error),
+}
@@ -0,0 +1,149 @@
+use crate::{
+    utils::{notify, Color, HookSender, Popup, StatusBarInfo, TimedHooks},
+    widget_default,
+    widgets::{ClickEvent, MouseButton, Result, Text, Widget, WidgetConfig},
+};
+use async_trait::async_trait;
+use log::error;
+use std::{fmt::Display, thread};
+
+const POPUP_HEIGHT: u16 = 200;
+
+/// Fills the popup with the recent notifications,
+/// false when there is nothing left to show
+fn refresh(popup: &mut Popup, max_items: usize) -> bool {
+    let lines: Vec<String> = notify::history()
+        .iter()
+        .take(max_items)
+        .map(|n| format!("{:>4}  {}: {}", n.age(), n.summary, n.body))
+        .collect();
+    if lines.is_empty() {
+        return false;
+    }
+    if let Err(e) = popup.set_lines(lines) {
+        error!("failed to draw notification popup: {e}");
+    }
+    true
+}
+
+/// Runs the popup until the user closes it,
+/// left clicking an entry dismisses it
+fn run_history(mut popup: Popup, max_items: usize) {
+    if !refresh(&mut popup, max_items) {
+        return;
+    }
+    if let Err(e) = popup.show() {
+        error!("failed to show notification popup: {e}");
+        return;
+    }
+    loop {
+        let Ok(event) = popup.next_event() else {
+            break;
+        };
+        let xcb::Event::X(xcb::x::Event::ButtonPress(press)) = event else {
+            continue;
+        };
+        // any other button closes the popup
+        if press.detail() != 1 {
+            break;
+        }
+        if let Some(index) = popup.line_at(f64::from(press.event_y())) {
+            notify::dismiss(index);
+        }
+        if !refresh(&mut popup, max_items) {
+            break;
+        }
+    }
+    popup.hide().ok();
+}
+
+/// Displays the unread notification count
+///
+/// Left click opens a popup with the recent notifications,
+/// clicking an entry dismisses it
+pub struct Notifications {
+    format: String,
+    inner: Text,
+    config: WidgetConfig,
+    popup_y: i16,
+    max_items: usize,
+}
+
+impl std::fmt::Debug for Notifications {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "inner: {:?}", self.inner)
+    }
+}
+
+impl Notifications {
+    ///* `format`
+    ///  * *%c* will be replaced with the unread notification count
+    ///* `config` a [&WidgetConfig]
+    pub async fn new(format: impl ToString, config: &WidgetConfig) -> Box<Self> {
+        Box::new(Self {
+            format: format.to_string(),
+            inner: *Text::new("", config).await,
+            config: config.clone(),
+            popup_y: 0,
+            max_items: 10,
+        })
+    }
+
+    /// Sets how many notifications the popup lists (default 10)
+    pub fn max_items(mut self: Box<Self>, max_items: usize) -> Box<Self> {
+        self.max_items = max_items;
+        self
+    }
+}
+
+#[async_trait]
+impl Widget for Notifications {
+    async fn setup(&mut self, info: &StatusBarInfo) -> Result<()> {
+        self.popup_y = info.popup_y(POPUP_HEIGHT);
+        Ok(())
+    }
+
+    async fn update(&mut self) -> Result<()> {
+        let text = self.format.replace("%c", &notify::unread().to_string());
+        self.inner.set_text(text);
+        Ok(())
+    }
+
+    async fn on_click(&mut self, event: ClickEvent) -> Result<()> {
+        if event.button != MouseButton::Left {
+            return Ok(());
+        }
+        notify::mark_all_read();
+        let config = self.config.clone();
+        let popup_y = self.popup_y;
+        let max_items = self.max_items;
+        // the popup runs its own event loop, keep it off the bar one
+        thread::spawn(move || {
+            match Popup::new(
+                0,
+                popup_y,
+                400,
+                POPUP_HEIGHT,
+                Color::new(0.0, 0.0, 0.0, 0.9),
+                &config,
+            ) {
+                Ok(popup) => run_history(popup, max_items),
+                Err(e) => error!("failed to open notification popup: {e}"),
+            }
+        });
+        Ok(())
+    }
+
+    async fn hook(&mut self, sender: HookSender, timed_hooks: &mut TimedHooks) -> Result<()> {
+        timed_hooks.subscribe(sender);
+        Ok(())
+    }
+
+    widget_default!(draw, size, padding);
+}
+
+impl Display for Notifications {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        String::from("Notifications").fmt(f)
+    }
+}
@@ -29,15 +29,25 @@ impl Debug for Png {
 
 impl Png {
     pub fn new(path: PathBuf, width: u32, config: &WidgetConfig) -> Result<Box<Self>> {
-        let mut file = File::open(path).map_err(Error::from)?;
-        let surface = ImageSurface::create_from_png(&mut file).map_err(Error::from)?;
         Ok(Box::new(Self {
-            surface: OwnedImageSurface::new(surface).map_err(Error::from)?,
+            surface: Self::load_surface(&path)?,
             padding: config.padding,
             fg_color: config.fg_color,
             width,
         }))
     }
+
+    /// Swaps the displayed image at runtime
+    pub fn set_path(&mut self, path: impl Into<PathBuf>) -> Result<()> {
+        self.surface = Self::load_surface(&path.into())?;
+        Ok(())
+    }
+
+    fn load_surface(path: &PathBuf) -> Result<OwnedImageSurface> {
+        let mut file = File::open(path).map_err(Error::from)?;
+        let surface = ImageSurface::create_from_png(&mut file).map_err(Error::from)?;
+        OwnedImageSurface::new(surface).map_err(|e| Error::from(e).into())
+    }
 }
 
 #[async_trait]
@@ -0,0 +1,144 @@
+use crate::{
+    utils::{HookSender, TimedHooks},
+    widget_default,
+    widgets::{ClickEvent, MouseButton, Result, Text, Widget, WidgetConfig},
+};
+use async_trait::async_trait;
+use log::{debug, error};
+use psutil::process::Process;
+use std::{collections::HashMap, fmt::Display, time::Duration};
+use tokio::time::sleep;
+
+/// Which resource [TopProcess] ranks processes by
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProcessMode {
+    Cpu,
+    Memory,
+}
+
+/// Displays the process using the most cpu (or memory)
+///
+/// Left click switches between cpu and memory mode
+pub struct TopProcess {
+    format: String,
+    mode: ProcessMode,
+    interval: Duration,
+    // process handles are kept between updates so cpu_percent
+    // measures the usage since the last sample
+    processes: HashMap<u32, Process>,
+    inner: Text,
+}
+
+impl std::fmt::Debug for TopProcess {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "mode: {:?}, inner: {:?}", self.mode, self.inner)
+    }
+}
+
+impl TopProcess {
+    ///* `format`
+    ///  * *%n* will be replaced with the process name
+    ///  * *%p* will be replaced with the resource percentage
+    ///* `mode` rank processes by cpu or memory
+    ///* `interval` how often to resample
+    ///* `config` a [&WidgetConfig]
+    pub async fn new(
+        format: impl ToString,
+        mode: ProcessMode,
+        interval: Duration,
+        config: &WidgetConfig,
+    ) -> Box<Self> {
+        Box::new(Self {
+            format: format.to_string(),
+            mode,
+            interval,
+            processes: HashMap::new(),
+            inner: *Text::new("", config).await,
+        })
+    }
+
+    fn top_process(&mut self) -> Result<Option<(String, f32)>> {
+        let mut seen = Vec::new();
+        for process in psutil::process::processes()
+            .map_err(Error::from)?
+            .into_iter()
+            .flatten()
+        {
+            seen.push(process.pid());
+            self.processes.entry(process.pid()).or_insert(process);
+        }
+        self.processes.retain(|pid, _| seen.contains(pid));
+
+        let mut top: Option<(String, f32)> = None;
+        for process in self.processes.values_mut() {
+            let usage = match self.mode {
+                ProcessMode::Cpu => process.cpu_percent(),
+                ProcessMode::Memory => process.memory_percent(),
+            };
+            let Ok(usage) = usage else {
+                continue;
+            };
+            if top.as_ref().map_or(true, |(_, best)| usage > *best) {
+                let Ok(name) = process.name() else {
+                    continue;
+                };
+                top = Some((name, usage));
+            }
+        }
+        Ok(top)
+    }
+}
+
+#[async_trait]
+impl Widget for TopProcess {
+    async fn update(&mut self) -> Result<()> {
+        debug!("updating top process");
+        let Some((name, usage)) = self.top_process()? else {
+            return Ok(());
+        };
+        let text = self
+            .format
+            .replace("%n", &name)
+            .replace("%p", &format!("{:.1}", usage));
+        self.inner.set_text(text);
+        Ok(())
+    }
+
+    async fn on_click(&mut self, event: ClickEvent) -> Result<()> {
+        if event.button == MouseButton::Left {
+            self.mode = match self.mode {
+                ProcessMode::Cpu => ProcessMode::Memory,
+                ProcessMode::Memory => ProcessMode::Cpu,
+            };
+        }
+        Ok(())
+    }
+
+    async fn hook(&mut self, sender: HookSender, _timed_hooks: &mut TimedHooks) -> Result<()> {
+        let interval = self.interval;
+        tokio::spawn(async move {
+            loop {
+                if sender.send().await.is_err() {
+                    error!("breaking top process hook");
+                    break;
+                }
+                sleep(interval).await;
+            }
+        });
+        Ok(())
+    }
+
+    widget_default!(draw, size, padding);
+}
+
+impl Display for TopProcess {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        String::from("TopProcess").fmt(f)
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error(transparent)]
+pub enum Error {
+    Psutil(#[from] psutil::Error),
+}
@@ -0,0 +1,184 @@
+use crate::{
+    utils::{HookSender, TimedHooks},
+    widget_default,
+    widgets::{ClickEvent, MouseButton, Result, Text, Widget, WidgetConfig},
+};
+use async_trait::async_trait;
+use log::{debug, error};
+use std::fmt::Display;
+use tokio::process::Command;
+
+const MIN_TEMPERATURE: u32 = 1000;
+const MAX_TEMPERATURE: u32 = 6500;
+
+/// Icons used by [Redshift]
+#[derive(Debug)]
+pub struct RedshiftIcons {
+    ///displayed while the screen is at its day temperature
+    pub day: String,
+    ///displayed while the night temperature is applied
+    pub night: String,
+}
+
+impl Default for RedshiftIcons {
+    fn default() -> Self {
+        Self {
+            day: String::from("☀"),
+            night: String::from("🌙"),
+        }
+    }
+}
+
+/// The first of gammastep/redshift available on the system
+async fn find_command() -> Option<String> {
+    for command in ["gammastep", "redshift"] {
+        if Command::new(command)
+            .arg("-V")
+            .output()
+            .await
+            .is_ok_and(|out| out.status.success())
+        {
+            return Some(String::from(command));
+        }
+    }
+    None
+}
+
+/// Shows whether a night color temperature is applied through
+/// gammastep or redshift
+///
+/// Left click toggles between day and night, scrolling adjusts
+/// the night temperature
+#[derive(Debug)]
+pub struct Redshift {
+    format: String,
+    command: Option<String>,
+    icons: RedshiftIcons,
+    night: bool,
+    temperature: u32,
+    scroll_step: u32,
+    inner: Text,
+}
+
+impl Redshift {
+    ///* `format`
+    ///  * *%i* will be replaced with the day/night icon
+    ///  * *%t* will be replaced with the night temperature in kelvin
+    ///* `icons` sets a custom [RedshiftIcons]
+    ///* `config` a [&WidgetConfig]
+    pub async fn new(
+        format: impl ToString,
+        icons: Option<RedshiftIcons>,
+        config: &WidgetConfig,
+    ) -> Box<Self> {
+        let command = find_command().await;
+        if command.is_none() {
+            error!("neither gammastep nor redshift found");
+        }
+        Box::new(Self {
+            format: format.to_string(),
+            command,
+            icons: icons.unwrap_or_default(),
+            night: false,
+            temperature: 4500,
+            scroll_step: 100,
+            inner: *Text::new("", config).await,
+        })
+    }
+
+    /// Sets the night temperature in kelvin (default 4500)
+    pub fn temperature(mut self: Box<Self>, temperature: u32) -> Box<Self> {
+        self.temperature = temperature.clamp(MIN_TEMPERATURE, MAX_TEMPERATURE);
+        self
+    }
+
+    /// Sets how much a scroll event changes the temperature (default 100K)
+    pub fn scroll_step(mut self: Box<Self>, step: u32) -> Box<Self> {
+        self.scroll_step = step;
+        self
+    }
+
+    /// Applies the current state in one-shot mode
+    async fn apply(&self) {
+        let Some(command) = &self.command else {
+            return;
+        };
+        let args = if self.night {
+            vec![String::from("-P"), String::from("-O"), self.temperature.to_string()]
+        } else {
+            vec![String::from("-x")]
+        };
+        if let Err(e) = Command::new(command).args(&args).output().await {
+            error!("failed to run {command}: {e}");
+        }
+    }
+}
+
+#[async_trait]
+impl Widget for Redshift {
+    async fn update(&mut self) -> Result<()> {
+        debug!("updating redshift");
+        if self.command.is_none() {
+            self.inner.clear();
+            return Ok(());
+        }
+        let icon = if self.night {
+            &self.icons.night
+        } else {
+            &self.icons.day
+        };
+        let text = self
+            .format
+            .replace("%i", icon)
+            .replace("%t", &self.temperature.to_string());
+        self.inner.set_text(text);
+        Ok(())
+    }
+
+    async fn on_click(&mut self, event: ClickEvent) -> Result<()> {
+        match event.button {
+            MouseButton::Left => self.night = !self.night,
+            MouseButton::ScrollUp => {
+                self.temperature = (self.temperature + self.scroll_step).min(MAX_TEMPERATURE);
+            }
+            MouseButton::ScrollDown => {
+                self.temperature = self
+                    .temperature
+                    .saturating_sub(self.scroll_step)
+                    .max(MIN_TEMPERATURE);
+            }
+            _ => return Ok(()),
+        }
+        self.apply().await;
+        Ok(())
+    }
+
+    fn save_state(&self) -> Option<serde_json::Value> {
+        Some(serde_json::json!({
+            "night": self.night,
+            "temperature": self.temperature,
+        }))
+    }
+
+    fn load_state(&mut self, state: &serde_json::Value) {
+        if let Some(night) = state.get("night").and_then(serde_json::Value::as_bool) {
+            self.night = night;
+        }
+        if let Some(temperature) = state.get("temperature").and_then(serde_json::Value::as_u64) {
+            self.temperature = (temperature as u32).clamp(MIN_TEMPERATURE, MAX_TEMPERATURE);
+        }
+    }
+
+    async fn hook(&mut self, sender: HookSender, timed_hooks: &mut TimedHooks) -> Result<()> {
+        timed_hooks.subscribe(sender);
+        Ok(())
+    }
+
+    widget_default!(draw, size, padding);
+}
+
+impl Display for Redshift {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        String::from("Redshift").fmt(f)
+    }
+}
@@ -0,0 +1,239 @@
+//! Builds widgets by type name from json options
+//!
+//! Used by config-file loaders and IPC to instantiate widgets
+//! dynamically; widgets that need custom providers or callbacks
+//! (Button, Launcher, Workspaces, Mail, ...) are not registered
+//! by default and can be added with [register](WidgetRegistry::register)
+
+use crate::widgets::{Result, Widget, WidgetConfig};
+use serde_json::Value;
+use std::{collections::HashMap, future::Future, pin::Pin};
+use thiserror::Error;
+
+pub type BuildFuture = Pin<Box<dyn Future<Output = Result<Box<dyn Widget>>> + Send>>;
+
+/// An async constructor taking the widget options as json
+pub type WidgetBuilder = Box<dyn Fn(Value, WidgetConfig) -> BuildFuture + Send + Sync>;
+
+/// Maps widget type names (`"clock"`, `"cpu"`, ...) to constructors
+pub struct WidgetRegistry {
+    builders: HashMap<String, WidgetBuilder>,
+}
+
+fn string_option(options: &Value, name: &'static str) -> std::result::Result<String, Error> {
+    options
+        .get(name)
+        .and_then(Value::as_str)
+        .map(String::from)
+        .ok_or(Error::MissingOption(name))
+}
+
+fn string_or(options: &Value, name: &str, default: &str) -> String {
+    options
+        .get(name)
+        .and_then(Value::as_str)
+        .unwrap_or(default)
+        .to_string()
+}
+
+macro_rules! builder {
+    (|$options:ident, $config:ident| $body:expr) => {
+        Box::new(move |$options: Value, $config: WidgetConfig| {
+            let future: BuildFuture = Box::pin(async move {
+                let widget: Box<dyn Widget> = $body;
+                Ok(widget)
+            });
+            future
+        })
+    };
+}
+
+impl WidgetRegistry {
+    /// An empty registry
+    pub fn new() -> Self {
+        Self {
+            builders: HashMap::new(),
+        }
+    }
+
+    /// A registry with every built-in widget that can be
+    /// constructed from plain options
+    pub fn with_builtins() -> Self {
+        use crate::widgets::*;
+        let mut registry = Self::new();
+        registry.register(
+            "text",
+            builder!(|options, config| {
+                Text::new(string_or(&options, "text", ""), &config).await
+            }),
+        );
+        registry.register(
+            "spacer",
+            builder!(|options, _config| {
+                let size = options.get("size").and_then(Value::as_u64).unwrap_or(10);
+                Spacer::new(size as u32).await
+            }),
+        );
+        registry.register(
+            "active_window",
+            builder!(|_options, config| ActiveWindow::new(&config).await?),
+        );
+        registry.register(
+            "mpd",
+            builder!(|options, config| {
+                let address = options
+                    .get("address")
+                    .and_then(Value::as_str)
+                    .map(String::from);
+                Mpd::new(string_or(&options, "format", "%t - %a"), address, &config).await
+            }),
+        );
+        registry.register(
+            "network",
+            builder!(|options, config| {
+                let interface = string_option(&options, "interface")?;
+                Network::new(string_or(&options, "format", "%n %s"), interface, None, &config).await
+            }),
+        );
+        registry.register(
+            "brightness",
+            builder!(|options, config| {
+                let device = options
+                    .get("device")
+                    .and_then(Value::as_str)
+                    .map(String::from);
+                let provider = Box::new(SysfsProvider::new(device).await?);
+                Brightness::new(string_or(&options, "format", "%i %p%"), None, provider, &config)
+                    .await?
+            }),
+        );
+        registry.register(
+            "battery",
+            builder!(|options, config| {
+                let provider = Box::new(PowerSupplyProvider::new().await?);
+                Battery::new(
+                    string_or(&options, "format", "%i %c%"),
+                    provider,
+                    None,
+                    &config,
+                    NotifySend::default(),
+                )
+                .await?
+            }),
+        );
+        #[cfg(feature = "clock")]
+        registry.register(
+            "clock",
+            builder!(|options, config| {
+                Clock::new(string_or(&options, "format", "%H:%M"), &config).await
+            }),
+        );
+        #[cfg(feature = "cpu")]
+        registry.register(
+            "cpu",
+            builder!(|options, config| {
+                Cpu::new(string_or(&options, "format", "%p%"), &config).await?
+            }),
+        );
+        #[cfg(feature = "memory")]
+        registry.register(
+            "memory",
+            builder!(|options, config| {
+                Memory::new(string_or(&options, "format", "%p%"), &config).await
+            }),
+        );
+        #[cfg(feature = "temp")]
+        registry.register(
+            "temp",
+            builder!(|options, config| {
+                Temperatures::new(string_or(&options, "format", "%t°"), &config).await
+            }),
+        );
+        #[cfg(feature = "disk")]
+        registry.register(
+            "disk",
+            builder!(|options, config| {
+                let path = string_or(&options, "path", "/");
+                Disk::new(string_or(&options, "format", "%f"), path, &config).await
+            }),
+        );
+        #[cfg(feature = "wlan")]
+        registry.register(
+            "wlan",
+            builder!(|options, config| {
+                let interface = string_option(&options, "interface")?;
+                Wlan::new(string_or(&options, "format", "%e"), interface, &config).await
+            }),
+        );
+        #[cfg(feature = "pulseaudio")]
+        registry.register(
+            "volume",
+            builder!(|options, config| {
+                let provider = Box::new(PulseaudioProvider::new().await?);
+                Volume::new(string_or(&options, "format", "%i %p%"), provider, None, &config).await
+            }),
+        );
+        #[cfg(feature = "openmeteo")]
+        registry.register(
+            "weather",
+            builder!(|options, config| {
+                Weather::new(
+                    &string_or(&options, "format", "%city %icon %cur"),
+                    MeteoIcons::default(),
+                    &config,
+                    OpenMeteoProvider::new(),
+                )
+                .await
+            }),
+        );
+        registry
+    }
+
+    /// Adds (or replaces) a constructor for `name`
+    pub fn register(&mut self, name: impl ToString, builder: WidgetBuilder) {
+        self.builders.insert(name.to_string(), builder);
+    }
+
+    /// Builds the widget registered as `name` with `options`
+    pub async fn build(
+        &self,
+        name: &str,
+        options: Value,
+        config: &WidgetConfig,
+    ) -> Result<Box<dyn Widget>> {
+        let builder = self
+            .builders
+            .get(name)
+            .ok_or_else(|| Error::UnknownWidget(name.to_string()))?;
+        builder(options, config.clone()).await
+    }
+
+    /// The registered widget names, sorted
+    pub fn names(&self) -> Vec<&str> {
+        let mut names: Vec<_> = self.builders.keys().map(String::as_str).collect();
+        names.sort_unstable();
+        names
+    }
+}
+
+impl Default for WidgetRegistry {
+    fn default() -> Self {
+        Self::with_builtins()
+    }
+}
+
+impl std::fmt::Debug for WidgetRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WidgetRegistry")
+            .field("names", &self.names())
+            .finish()
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("Unknown widget: {0}")]
+    UnknownWidget(String),
+    #[error("Missing widget option: {0}")]
+    MissingOption(&'static str),
+}
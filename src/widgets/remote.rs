@@ -0,0 +1,140 @@
+use crate::{
+    utils::{Color, HookSender, TimedHooks},
+    widget_default,
+    widgets::{Result, Text, Widget, WidgetConfig},
+};
+use async_channel::{bounded, Receiver, Sender};
+use async_trait::async_trait;
+use log::{debug, warn};
+use std::{fmt::Display, time::Duration};
+use tokio::{
+    io::{AsyncBufReadExt, BufReader},
+    net::TcpStream,
+    spawn,
+    time::sleep,
+};
+
+/// A status message pushed by the remote endpoint
+#[derive(Debug, Clone)]
+struct Message {
+    text: String,
+    color: Option<Color>,
+    urgent: bool,
+}
+
+impl Message {
+    /// Parses `{"text": "...", "color": "#ff0000", "urgent": true}`
+    fn parse(line: &str) -> Option<Self> {
+        let value: serde_json::Value = serde_json::from_str(line).ok()?;
+        Some(Self {
+            text: value.get("text")?.as_str()?.to_string(),
+            color: value
+                .get("color")
+                .and_then(serde_json::Value::as_str)
+                .and_then(Color::from_hex),
+            urgent: value
+                .get("urgent")
+                .and_then(serde_json::Value::as_bool)
+                .unwrap_or(false),
+        })
+    }
+}
+
+/// Reads newline-delimited JSON messages until the connection drops
+async fn read_messages(
+    address: &str,
+    messages: &Sender<Message>,
+    sender: &HookSender,
+) -> std::io::Result<()> {
+    let stream = TcpStream::connect(address).await?;
+    let mut lines = BufReader::new(stream).lines();
+    while let Some(line) = lines.next_line().await? {
+        let Some(message) = Message::parse(&line) else {
+            warn!("invalid remote message: {line}");
+            continue;
+        };
+        let urgent = message.urgent;
+        if messages.send(message).await.is_err() {
+            debug!("breaking remote loop");
+            return Ok(());
+        }
+        let notified = if urgent {
+            sender.send_urgent().await
+        } else {
+            sender.send().await
+        };
+        if notified.is_err() {
+            debug!("breaking remote loop");
+            return Ok(());
+        }
+    }
+    Err(std::io::ErrorKind::ConnectionReset.into())
+}
+
+/// Renders status messages pushed over TCP, so remote machines
+/// and scripts can display text in the local bar
+///
+/// The endpoint sends one JSON object per line:
+/// `{"text": "...", "color": "#ff0000", "urgent": true}`
+#[derive(Debug)]
+pub struct Remote {
+    address: String,
+    messages: Sender<Message>,
+    pending: Receiver<Message>,
+    fg_color: Color,
+    inner: Text,
+}
+
+impl Remote {
+    ///* `address` of the endpoint to connect to
+    ///* `config` a [&WidgetConfig]
+    pub async fn new(address: impl ToString, config: &WidgetConfig) -> Box<Self> {
+        let (messages, pending) = bounded(10);
+        Box::new(Self {
+            address: address.to_string(),
+            messages,
+            pending,
+            fg_color: config.fg_color,
+            inner: *Text::new("", config).await,
+        })
+    }
+}
+
+#[async_trait]
+impl Widget for Remote {
+    async fn update(&mut self) -> Result<()> {
+        debug!("updating remote");
+        let mut last = None;
+        while let Ok(message) = self.pending.try_recv() {
+            last = Some(message);
+        }
+        if let Some(message) = last {
+            self.inner
+                .set_fg_color(message.color.unwrap_or(self.fg_color));
+            self.inner.set_text(message.text);
+        }
+        Ok(())
+    }
+
+    async fn hook(&mut self, sender: HookSender, _timed_hooks: &mut TimedHooks) -> Result<()> {
+        let address = self.address.clone();
+        let messages = self.messages.clone();
+        spawn(async move {
+            loop {
+                if let Err(e) = read_messages(&address, &messages, &sender).await {
+                    debug!("remote connection lost: {e}");
+                }
+                sleep(Duration::from_secs(5)).await;
+            }
+        });
+        Ok(())
+    }
+
+    widget_default!(draw, size, padding);
+}
+
+impl Display for Remote {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        String::from("Remote").fmt(f)
+    }
+}
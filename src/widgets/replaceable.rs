@@ -1,6 +1,6 @@
 use crate::{
-    utils::{HookSender, Rectangle, StatusBarInfo, TimedHooks},
-    widgets::{Size, Text, Widget, WidgetConfig, WidgetError},
+    utils::{set_source_rgba, Color, HookSender, Rectangle, StatusBarInfo, TimedHooks},
+    widgets::{ClickEvent, Size, Text, Widget, WidgetConfig, WidgetError},
 };
 use cairo::Context;
 use log::error;
@@ -9,77 +9,175 @@ use std::{
     ops::{Deref, DerefMut},
 };
 
+/// How a failing widget is handled, see
+/// [on_widget_error](crate::statusbar::StatusBarBuilder::on_widget_error)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OnError {
+    /// swap the widget for a static crash message
+    #[default]
+    Replace,
+    /// keep the widget, overlay a warning badge on its last
+    /// successful render and let it keep retrying
+    Badge,
+}
+
 #[derive(Debug)]
-pub struct ReplaceableWidget(Box<dyn Widget>);
+pub struct ReplaceableWidget {
+    wd: Box<dyn Widget>,
+    on_error: OnError,
+    // a call on the widget failed, draws the badge until an update succeeds
+    failing: bool,
+    last_size: Option<Size>,
+}
 
 impl Deref for ReplaceableWidget {
     type Target = dyn Widget;
 
     fn deref(&self) -> &Self::Target {
-        self.0.as_ref()
+        self.wd.as_ref()
     }
 }
 
 impl DerefMut for ReplaceableWidget {
     fn deref_mut(&mut self) -> &mut Self::Target {
-        self.0.as_mut()
+        self.wd.as_mut()
     }
 }
 
 impl fmt::Display for ReplaceableWidget {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        std::fmt::Display::fmt(&self.0, f)
+        std::fmt::Display::fmt(&self.wd, f)
     }
 }
 
 impl ReplaceableWidget {
     pub fn new(wd: Box<dyn Widget>) -> Self {
-        Self(wd)
+        Self {
+            wd,
+            on_error: OnError::default(),
+            failing: false,
+            last_size: None,
+        }
+    }
+
+    pub fn with_on_error(mut self, on_error: OnError) -> Self {
+        self.on_error = on_error;
+        self
     }
 
     pub async fn draw_or_replace(&mut self, context: Context, rectangle: &Rectangle) {
-        if let Err(e) = self.0.draw(context, rectangle) {
-            self.replace(e).await;
-            // we need to recompute the size before we draw again
+        match self.wd.draw(context.clone(), rectangle) {
+            Ok(()) => {}
+            Err(e) if self.on_error == OnError::Badge => {
+                error!("`{}` draw failed: {e}", self.wd);
+                self.failing = true;
+            }
+            Err(e) => {
+                self.replace(e).await;
+                // we need to recompute the size before we draw again
+                return;
+            }
+        }
+        if self.failing {
+            if let Err(e) = draw_badge(&context, rectangle) {
+                error!("failed to draw the warning badge: {e}");
+            }
         }
     }
 
     pub async fn size_or_replace(&mut self, context: &Context) -> Size {
-        match self.0.size(context) {
-            Ok(s) => s,
+        match self.wd.size(context) {
+            Ok(s) => {
+                self.last_size = Some(s);
+                s
+            }
+            Err(e) if self.on_error == OnError::Badge => {
+                error!("`{}` size failed: {e}", self.wd);
+                self.failing = true;
+                // keep the last region so the layout stays put
+                self.last_size.unwrap_or(Size::Static(0))
+            }
             Err(e) => {
                 self.replace(e).await;
-                self.0.size(context).unwrap()
+                self.wd.size(context).unwrap()
             }
         }
     }
 
     pub async fn setup_or_replace(&mut self, info: &StatusBarInfo) {
-        match self.0.setup(info).await {
-            Ok(s) => s,
+        match self.wd.setup(info).await {
+            Ok(()) => {}
+            Err(e) if self.on_error == OnError::Badge => {
+                error!("`{}` setup failed: {e}", self.wd);
+                self.failing = true;
+            }
             Err(e) => {
                 self.replace(e).await;
-                self.0.setup(info).await.unwrap();
+                self.wd.setup(info).await.unwrap();
             }
         }
     }
+
     pub async fn update_or_replace(&mut self) {
-        if let Err(e) = self.0.update().await {
-            self.replace(e).await;
-            self.0.update().await.unwrap();
+        match self.wd.update().await {
+            Ok(()) => self.failing = false,
+            Err(e) if self.on_error == OnError::Badge => {
+                error!("`{}` update failed: {e}", self.wd);
+                self.failing = true;
+            }
+            Err(e) => {
+                self.replace(e).await;
+                self.wd.update().await.unwrap();
+            }
         }
     }
 
     pub async fn hook_or_replace(&mut self, sender: HookSender, pool: &mut TimedHooks) {
-        if let Err(e) = self.0.hook(sender.clone(), pool).await {
-            self.replace(e).await;
-            self.0.hook(sender, pool).await.unwrap();
+        match self.wd.hook(sender.clone(), pool).await {
+            Ok(()) => {}
+            Err(e) if self.on_error == OnError::Badge => {
+                error!("`{}` hook failed: {e}", self.wd);
+                self.failing = true;
+            }
+            Err(e) => {
+                self.replace(e).await;
+                self.wd.hook(sender, pool).await.unwrap();
+            }
+        }
+    }
+
+    pub async fn on_click_or_replace(&mut self, event: ClickEvent) {
+        match self.wd.on_click(event).await {
+            Ok(()) => {}
+            Err(e) if self.on_error == OnError::Badge => {
+                error!("`{}` on_click failed: {e}", self.wd);
+                self.failing = true;
+            }
+            Err(e) => self.replace(e).await,
+        }
+    }
+
+    pub async fn teardown_or_log(&mut self) {
+        if let Err(e) = self.wd.teardown().await {
+            error!("`{}` teardown failed: {e}", self.wd);
         }
     }
 
     async fn replace(&mut self, e: WidgetError) {
         error!("{e}");
-        error!("Replacing `{}` with default", self.0);
-        self.0 = Text::new("Widget Crashed 🙃", &WidgetConfig::default()).await;
+        error!("Replacing `{}` with default", self.wd);
+        self.wd = Text::new("Widget Crashed 🙃", &WidgetConfig::default()).await;
     }
 }
+
+/// A small amber triangle in the top right corner of the region
+fn draw_badge(context: &Context, rectangle: &Rectangle) -> std::result::Result<(), cairo::Error> {
+    let size = (f64::from(rectangle.height) * 0.4).min(10.0);
+    let x = f64::from(rectangle.width) - size;
+    set_source_rgba(context, Color::new(1.0, 0.7, 0.0, 1.0));
+    context.move_to(x + size / 2.0, 0.0);
+    context.line_to(x + size, size);
+    context.line_to(x, size);
+    context.close_path();
+    context.fill()
+}
@@ -0,0 +1,180 @@
+use crate::{
+    utils::{http_client, HookSender, TimedHooks},
+    widget_default,
+    widgets::{ClickEvent, MouseButton, Result, Text, Widget, WidgetConfig},
+    xdg_cache,
+};
+use async_trait::async_trait;
+use log::{debug, error};
+use std::{
+    collections::HashSet,
+    fmt::Display,
+    fs,
+    path::PathBuf,
+    time::Duration,
+};
+use tokio::time::sleep;
+
+#[derive(Debug, Clone)]
+struct FeedEntry {
+    id: String,
+    title: String,
+    link: Option<String>,
+}
+
+/// Displays the unread count and latest headline of RSS/Atom feeds
+///
+/// Left click opens the latest unread article in the browser
+/// and marks it as read
+pub struct Rss {
+    format: String,
+    feeds: Vec<String>,
+    entries: Vec<FeedEntry>,
+    // ids of the already seen entries, persisted in [xdg_cache]
+    seen: HashSet<String>,
+    seen_file: PathBuf,
+    interval: Duration,
+    inner: Text,
+}
+
+impl std::fmt::Debug for Rss {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "feeds: {:?}, inner: {:?}", self.feeds, self.inner)
+    }
+}
+
+impl Rss {
+    ///* `format`
+    ///  * *%c* will be replaced with the unread count
+    ///  * *%h* will be replaced with the latest unread headline
+    ///* `feeds` urls of the RSS/Atom feeds to poll
+    ///* `interval` how often to refetch
+    ///* `config` a [&WidgetConfig]
+    pub async fn new(
+        format: impl ToString,
+        feeds: Vec<String>,
+        interval: Duration,
+        config: &WidgetConfig,
+    ) -> Result<Box<Self>> {
+        let seen_file = xdg_cache().map_err(Error::from)?.join("rss_seen");
+        let seen = fs::read_to_string(&seen_file)
+            .map(|s| s.lines().map(String::from).collect())
+            .unwrap_or_default();
+        Ok(Box::new(Self {
+            format: format.to_string(),
+            feeds,
+            entries: Vec::new(),
+            seen,
+            seen_file,
+            interval,
+            inner: *Text::new("", config).await,
+        }))
+    }
+
+    async fn fetch_entries(&self) -> Vec<FeedEntry> {
+        if http_client().is_offline() {
+            return Vec::new();
+        }
+        let mut entries = Vec::new();
+        for url in &self.feeds {
+            let body = match http_client().get_text(url).await {
+                Ok(body) => body,
+                Err(e) => {
+                    error!("failed to fetch feed {url}: {e}");
+                    continue;
+                }
+            };
+            let feed = match feed_rs::parser::parse(body.as_bytes()) {
+                Ok(feed) => feed,
+                Err(e) => {
+                    error!("failed to parse feed {url}: {e}");
+                    continue;
+                }
+            };
+            for entry in feed.entries {
+                entries.push(FeedEntry {
+                    id: entry.id,
+                    title: entry.title.map(|t| t.content).unwrap_or_default(),
+                    link: entry.links.first().map(|l| l.href.clone()),
+                });
+            }
+        }
+        entries
+    }
+
+    fn unread(&self) -> impl Iterator<Item = &FeedEntry> {
+        self.entries.iter().filter(|e| !self.seen.contains(&e.id))
+    }
+
+    fn persist_seen(&self) {
+        let content = self.seen.iter().cloned().collect::<Vec<_>>().join("\n");
+        if let Err(e) = fs::write(&self.seen_file, content) {
+            error!("failed to persist seen entries: {e}");
+        }
+    }
+}
+
+#[async_trait]
+impl Widget for Rss {
+    async fn update(&mut self) -> Result<()> {
+        debug!("updating rss");
+        self.entries = self.fetch_entries().await;
+        let unread_count = self.unread().count();
+        let headline = self
+            .unread()
+            .next()
+            .map(|e| e.title.clone())
+            .unwrap_or_default();
+        let text = self
+            .format
+            .replace("%c", &unread_count.to_string())
+            .replace("%h", &headline);
+        self.inner.set_text(text);
+        Ok(())
+    }
+
+    async fn on_click(&mut self, event: ClickEvent) -> Result<()> {
+        if event.button != MouseButton::Left {
+            return Ok(());
+        }
+        let Some(entry) = self.unread().next().cloned() else {
+            return Ok(());
+        };
+        if let Some(link) = &entry.link {
+            if let Err(e) = tokio::process::Command::new("xdg-open").arg(link).spawn() {
+                error!("failed to open {link}: {e}");
+            }
+        }
+        self.seen.insert(entry.id);
+        self.persist_seen();
+        Ok(())
+    }
+
+    async fn hook(&mut self, sender: HookSender, _timed_hooks: &mut TimedHooks) -> Result<()> {
+        let interval = self.interval;
+        tokio::spawn(async move {
+            loop {
+                if sender.send().await.is_err() {
+                    error!("breaking rss hook");
+                    break;
+                }
+                sleep(interval).await;
+            }
+        });
+        Ok(())
+    }
+
+    widget_default!(draw, size, padding);
+}
+
+impl Display for Rss {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        String::from("Rss").fmt(f)
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error(transparent)]
+pub enum Error {
+    IO(#[from] std::io::Error),
+}
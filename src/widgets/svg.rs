@@ -1,5 +1,5 @@
 use crate::{
-    utils::{HookSender, OwnedImageSurface, TimedHooks},
+    utils::{Color, HookSender, OwnedImageSurface, TimedHooks},
     widgets::{Rectangle, Result, Size, Widget, WidgetConfig},
 };
 use async_trait::async_trait;
@@ -14,17 +14,64 @@ pub struct Svg {
     surface: OwnedImageSurface,
     padding: u32,
     width: u32,
+    path: PathBuf,
+    fg_color: Option<Color>,
 }
 
 impl Debug for Svg {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "padding: {:?}, width: {:?}", self.padding, self.width)
+        write!(
+            f,
+            "padding: {:?}, width: {:?}, path: {:?}",
+            self.padding, self.width, self.path
+        )
     }
 }
 
 impl Svg {
     pub fn new(path: PathBuf, width: u32, config: &WidgetConfig) -> Result<Box<Self>> {
-        let handle = rsvg::Loader::new().read_path(path).map_err(Error::from)?;
+        Ok(Box::new(Self {
+            surface: Self::render_surface(&path, width, None)?,
+            padding: config.padding,
+            width,
+            path,
+            fg_color: None,
+        }))
+    }
+
+    /// Recolors monochrome SVGs using `currentColor` so the icon
+    /// follows the bar theme
+    pub fn recolor(mut self: Box<Self>, color: Color) -> Result<Box<Self>> {
+        self.fg_color = Some(color);
+        self.surface = Self::render_surface(&self.path, self.width, self.fg_color)?;
+        Ok(self)
+    }
+
+    /// Swaps the displayed image at runtime
+    pub fn set_path(&mut self, path: impl Into<PathBuf>) -> Result<()> {
+        self.path = path.into();
+        self.surface = Self::render_surface(&self.path, self.width, self.fg_color)?;
+        Ok(())
+    }
+
+    fn render_surface(
+        path: &PathBuf,
+        width: u32,
+        fg_color: Option<Color>,
+    ) -> Result<OwnedImageSurface> {
+        let mut handle = rsvg::Loader::new().read_path(path).map_err(Error::from)?;
+
+        if let Some(color) = fg_color {
+            // currentColor resolves to the CSS color property
+            let css = format!(
+                "* {{ color: rgba({}, {}, {}, {}); }}",
+                (color.r * 255.0) as u8,
+                (color.g * 255.0) as u8,
+                (color.b * 255.0) as u8,
+                color.a,
+            );
+            handle.set_stylesheet(&css).map_err(Error::from)?;
+        }
 
         let surface =
             ImageSurface::create(Format::ARgb32, width as _, width as _).map_err(Error::from)?;
@@ -36,11 +83,7 @@ impl Svg {
             .map_err(Error::from)?;
         drop(context);
 
-        Ok(Box::new(Self {
-            surface: OwnedImageSurface::new(surface).map_err(Error::from)?,
-            padding: config.padding,
-            width,
-        }))
+        OwnedImageSurface::new(surface).map_err(|e| Error::from(e).into())
     }
 }
 
@@ -0,0 +1,129 @@
+use crate::{
+    utils::{HookSender, TimedHooks},
+    widget_default,
+    widgets::{ClickEvent, MouseButton, Result, Text, Widget, WidgetConfig},
+};
+use async_trait::async_trait;
+use log::{debug, error};
+use std::fmt::Display;
+use zbus::zvariant::OwnedObjectPath;
+
+const SYSTEMD_DEST: &str = "org.freedesktop.systemd1";
+const SYSTEMD_PATH: &str = "/org/freedesktop/systemd1";
+
+/// Displays how many of the watched systemd units have failed
+///
+/// Left click restarts the failed units
+pub struct Systemd {
+    format: String,
+    units: Vec<String>,
+    failed: Vec<String>,
+    inner: Text,
+    connection: zbus::Connection,
+}
+
+impl std::fmt::Debug for Systemd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "units: {:?}, failed: {:?}", self.units, self.failed)
+    }
+}
+
+impl Systemd {
+    ///* `format`
+    ///  * *%f* will be replaced with the number of failed units
+    ///  * *%t* will be replaced with the number of watched units
+    ///* `units` full unit names to watch (e.g. `sshd.service`)
+    ///* `config` a [&WidgetConfig]
+    pub async fn new(
+        format: impl ToString,
+        units: Vec<String>,
+        config: &WidgetConfig,
+    ) -> Result<Box<Self>> {
+        let connection = zbus::Connection::system().await.map_err(Error::from)?;
+        Ok(Box::new(Self {
+            format: format.to_string(),
+            units,
+            failed: Vec::new(),
+            inner: *Text::new("", config).await,
+            connection,
+        }))
+    }
+
+    async fn manager(&self) -> zbus::Result<zbus::Proxy<'_>> {
+        zbus::Proxy::new(
+            &self.connection,
+            SYSTEMD_DEST,
+            SYSTEMD_PATH,
+            "org.freedesktop.systemd1.Manager",
+        )
+        .await
+    }
+
+    async fn unit_state(&self, unit: &str) -> zbus::Result<String> {
+        let path: OwnedObjectPath = self.manager().await?.call("LoadUnit", &(unit)).await?;
+        let proxy = zbus::Proxy::new(
+            &self.connection,
+            SYSTEMD_DEST,
+            path,
+            "org.freedesktop.systemd1.Unit",
+        )
+        .await?;
+        proxy.get_property("ActiveState").await
+    }
+
+    async fn restart_failed(&self) -> zbus::Result<()> {
+        let manager = self.manager().await?;
+        for unit in &self.failed {
+            debug!("restarting {unit}");
+            let _: OwnedObjectPath = manager.call("RestartUnit", &(unit, "replace")).await?;
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Widget for Systemd {
+    async fn update(&mut self) -> Result<()> {
+        debug!("updating systemd");
+        self.failed.clear();
+        for unit in &self.units {
+            match self.unit_state(unit).await {
+                Ok(state) if state == "failed" => self.failed.push(unit.clone()),
+                Ok(_) => {}
+                Err(e) => error!("failed to query {unit}: {e}"),
+            }
+        }
+        let text = self
+            .format
+            .replace("%f", &self.failed.len().to_string())
+            .replace("%t", &self.units.len().to_string());
+        self.inner.set_text(text);
+        Ok(())
+    }
+
+    async fn on_click(&mut self, event: ClickEvent) -> Result<()> {
+        if event.button == MouseButton::Left {
+            self.restart_failed().await.map_err(Error::from)?;
+        }
+        Ok(())
+    }
+
+    async fn hook(&mut self, sender: HookSender, timed_hooks: &mut TimedHooks) -> Result<()> {
+        timed_hooks.subscribe(sender);
+        Ok(())
+    }
+
+    widget_default!(draw, size, padding);
+}
+
+impl Display for Systemd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        String::from("Systemd").fmt(f)
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error(transparent)]
+pub enum Error {
+    Zbus(#[from] zbus::Error),
+}
@@ -1,13 +1,16 @@
 use crate::{
     statusbar::set_window_title,
-    utils::{screen_true_height, Atoms, HookSender, Position, StatusBarInfo, TimedHooks},
+    utils::{
+        atoms::intern_atom, notify, screen_true_height, Atoms, HookSender, Position,
+        StatusBarInfo, TimedHooks,
+    },
     widgets::{Rectangle, Result, Size, Widget, WidgetConfig},
 };
 use async_channel::{bounded, Receiver};
 use async_trait::async_trait;
 use cairo::Context;
 use log::{debug, error};
-use std::{fmt::Display, sync::Arc, thread};
+use std::{collections::HashMap, fmt::Display, sync::Arc, thread};
 use xcb::{
     x::{
         ChangeProperty, ChangeWindowAttributes, ClientMessageData, ClientMessageEvent, Colormap,
@@ -20,6 +23,16 @@ use xcb::{
 };
 
 const SYSTEM_TRAY_REQUEST_DOCK: u32 = 0;
+const SYSTEM_TRAY_BEGIN_MESSAGE: u32 = 1;
+const SYSTEM_TRAY_CANCEL_MESSAGE: u32 = 2;
+
+/// A balloon message being received in 20 byte
+/// _NET_SYSTEM_TRAY_MESSAGE_DATA chunks
+#[derive(Debug)]
+struct Balloon {
+    remaining: usize,
+    text: Vec<u8>,
+}
 
 /// Displays a system tray
 pub struct Systray {
@@ -32,6 +45,10 @@ pub struct Systray {
     event_receiver: Option<Receiver<SystrayEvent>>,
     icon_size: u32,
     context: Option<Gcontext>,
+    // in-flight balloon messages, keyed by (icon window, message id)
+    balloons: HashMap<(u32, u32), Balloon>,
+    // widths of icons that requested a non-square size, by window id
+    widths: HashMap<u32, u32>,
 }
 
 impl std::fmt::Debug for Systray {
@@ -59,6 +76,8 @@ impl Systray {
             internal_padding,
             icon_size: 0,
             context: None,
+            balloons: HashMap::new(),
+            widths: HashMap::new(),
         }))
     }
 
@@ -104,6 +123,7 @@ impl Systray {
             return Ok(());
         }
         self.children.retain(|child| *child != window);
+        self.widths.remove(&window.resource_id());
 
         self.connection.send_request(&ChangeWindowAttributes {
             window,
@@ -180,7 +200,14 @@ impl Systray {
                 value_list: &[
                     Cw::BackPixmap(Pixmap::none()),
                     Cw::BorderPixel(screen.black_pixel()),
-                    Cw::EventMask(EventMask::PROPERTY_CHANGE | EventMask::STRUCTURE_NOTIFY),
+                    // substructure redirect intercepts the children's
+                    // map and resize requests so icons cannot pick
+                    // their own geometry
+                    Cw::EventMask(
+                        EventMask::PROPERTY_CHANGE
+                            | EventMask::STRUCTURE_NOTIFY
+                            | EventMask::SUBSTRUCTURE_REDIRECT,
+                    ),
                     Cw::Colormap(colormap),
                 ],
             })
@@ -306,23 +333,148 @@ impl Systray {
     }
 
     fn handle_client_message(&mut self, event: ClientMessageEvent) -> Result<()> {
+        let atoms = Atoms::new(&self.connection).map_err(Error::from)?;
+        let message_data =
+            intern_atom(&self.connection, "_NET_SYSTEM_TRAY_MESSAGE_DATA").map_err(Error::from)?;
+        if event.r#type() == message_data {
+            self.handle_balloon_chunk(&event);
+            return Ok(());
+        }
+        if event.r#type() != atoms._NET_SYSTEM_TRAY_OPCODE {
+            return Ok(());
+        }
         let ClientMessageData::Data32(data) = event.data() else {
             return Ok(());
         };
-        let opcode = data[1];
-        let window = data[2];
-        if SYSTEM_TRAY_REQUEST_DOCK == opcode {
-            debug!("systray request dock message");
+        match data[1] {
+            SYSTEM_TRAY_REQUEST_DOCK => {
+                debug!("systray request dock message");
 
-            let window = unsafe { Window::new(window) };
+                let window = unsafe { Window::new(data[2]) };
 
-            if self.adopt(window).is_err() {
-                self.forget(window)?;
+                if self.adopt(window).is_err() {
+                    self.forget(window)?;
+                }
             }
-        };
+            // [time, opcode, timeout, length, id]
+            SYSTEM_TRAY_BEGIN_MESSAGE => {
+                let length = data[3] as usize;
+                let id = data[4];
+                debug!("systray balloon message of {length} bytes");
+                if length > 0 {
+                    self.balloons.insert(
+                        (event.window().resource_id(), id),
+                        Balloon {
+                            remaining: length,
+                            text: Vec::with_capacity(length),
+                        },
+                    );
+                }
+            }
+            SYSTEM_TRAY_CANCEL_MESSAGE => {
+                self.balloons
+                    .remove(&(event.window().resource_id(), data[2]));
+            }
+            opcode => debug!("unknown systray opcode {opcode}"),
+        }
         Ok(())
     }
 
+    /// Collects one chunk of a balloon message, showing it as a
+    /// notification once the announced length has arrived
+    fn handle_balloon_chunk(&mut self, event: &ClientMessageEvent) {
+        let ClientMessageData::Data8(data) = event.data() else {
+            return;
+        };
+        let window = event.window().resource_id();
+        // chunks carry no id, they belong to the pending message of the icon
+        let Some(key) = self.balloons.keys().find(|(w, _)| *w == window).copied() else {
+            return;
+        };
+        let balloon = self.balloons.get_mut(&key).expect("key comes from the map");
+        let take = balloon.remaining.min(data.len());
+        balloon.text.extend_from_slice(&data[..take]);
+        balloon.remaining -= take;
+        if balloon.remaining == 0 {
+            let balloon = self.balloons.remove(&key).expect("key comes from the map");
+            let text = String::from_utf8_lossy(&balloon.text);
+            notify::notify("Systray", &text, libnotify::Urgency::Normal);
+        }
+    }
+
+    /// Reparents all the adopted icons back to the root window
+    /// and destroys the tray window
+    fn release_children(&mut self) {
+        let setup = self.connection.get_setup();
+        let screen = setup.roots().nth(self.screen_id as _).unwrap();
+        let root = screen.root();
+
+        self.widths.clear();
+        for window in self.children.drain(..) {
+            self.connection
+                .send_and_check_request(&ChangeWindowAttributes {
+                    window,
+                    value_list: &[Cw::EventMask(EventMask::NO_EVENT)],
+                })
+                .ok();
+            self.connection
+                .send_and_check_request(&UnmapWindow { window })
+                .ok();
+            self.connection
+                .send_and_check_request(
+                    &(ReparentWindow {
+                        window,
+                        parent: root,
+                        x: 0,
+                        y: 0,
+                    }),
+                )
+                .ok();
+        }
+
+        if let Some(window) = self.window.take() {
+            self.connection
+                .send_and_check_request(&ChangeWindowAttributes {
+                    window,
+                    value_list: &[Cw::EventMask(EventMask::STRUCTURE_NOTIFY)],
+                })
+                .ok();
+            self.connection
+                .send_and_check_request(&DestroyWindow { window })
+                .ok();
+        }
+        self.connection.flush().ok();
+    }
+
+    /// The width an icon takes, `icon_size` unless it asked
+    /// for a different aspect ratio
+    fn child_width(&self, window: Window) -> u32 {
+        self.widths
+            .get(&window.resource_id())
+            .copied()
+            .unwrap_or(self.icon_size)
+    }
+
+    /// Clamps a child's resize request to the bar height,
+    /// preserving the requested aspect ratio
+    fn resize_child(&mut self, window: Window, width: u16, height: u16) {
+        if !self.children.contains(&window) {
+            return;
+        }
+        let width = u32::from(width.max(1));
+        let height = u32::from(height.max(1));
+        let scaled = (width * self.icon_size / height).max(1);
+        debug!(
+            "systray icon {:?} asked for {width}x{height}, giving it {scaled}x{}",
+            window, self.icon_size
+        );
+        if scaled == self.icon_size {
+            self.widths.remove(&window.resource_id());
+        } else {
+            self.widths.insert(window.resource_id(), scaled);
+        }
+    }
+
     fn handle_event(&mut self, event: SystrayEvent) -> Result<()> {
         match event {
             SystrayEvent::ClientMessage(event) => {
@@ -335,6 +487,15 @@ impl Systray {
                     self.forget(window)?;
                 }
             }
+            SystrayEvent::ConfigureRequest((window, width, height)) => {
+                self.resize_child(window, width, height);
+            }
+            SystrayEvent::MapRequest(window) => {
+                // the redirect swallows the request, map it ourselves
+                self.connection
+                    .send_and_check_request(&MapWindow { window })
+                    .map_err(Error::from)?;
+            }
             _ => (),
         }
         Ok(())
@@ -404,6 +565,7 @@ impl Widget for Systray {
             self.connection
                 .send_and_check_request(&MapWindow { window: *child })
                 .ok();
+            let width = self.child_width(*child);
             self.connection
                 .send_and_check_request(
                     &(ConfigureWindow {
@@ -411,13 +573,13 @@ impl Widget for Systray {
                         value_list: &[
                             ConfigWindow::X(offset as _),
                             ConfigWindow::Y(1),
-                            ConfigWindow::Width(self.icon_size as _),
+                            ConfigWindow::Width(width as _),
                             ConfigWindow::Height(self.icon_size as _),
                         ],
                     }),
                 )
                 .ok();
-            offset += self.icon_size + self.internal_padding;
+            offset += width + self.internal_padding;
         }
 
         Ok(())
@@ -457,6 +619,11 @@ impl Widget for Systray {
         Ok(())
     }
 
+    async fn teardown(&mut self) -> Result<()> {
+        self.release_children();
+        Ok(())
+    }
+
     async fn hook(&mut self, sender: HookSender, _timed_hooks: &mut TimedHooks) -> Result<()> {
         let connection = self.connection.clone();
         let (tx, rx) = bounded(10);
@@ -469,7 +636,20 @@ impl Widget for Systray {
                 None
             };
             if let Some(event) = event {
-                if tx.send_blocking(event).is_err() || sender.send_blocking().is_err() {
+                // docking, undocking and resizes change the tray width
+                let size_changed = matches!(
+                    event,
+                    SystrayEvent::ClientMessage(_)
+                        | SystrayEvent::ConfigureRequest(_)
+                        | SystrayEvent::DestroyNotify(_)
+                        | SystrayEvent::ReparentNotify(_)
+                );
+                let notify = if size_changed {
+                    sender.send_size_changed_blocking()
+                } else {
+                    sender.send_blocking()
+                };
+                if tx.send_blocking(event).is_err() || notify.is_err() {
                     error!("breaking systray hook loop");
                     break;
                 }
@@ -483,8 +663,13 @@ impl Widget for Systray {
             return Ok(Size::Static(1));
         }
         let children_len = self.children.len() as u32;
+        let icons: u32 = self
+            .children
+            .iter()
+            .map(|child| self.child_width(*child))
+            .sum();
         Ok(Size::Static(
-            children_len * self.icon_size + (children_len - 1) * self.internal_padding + 2,
+            icons + (children_len - 1) * self.internal_padding + 2,
         ))
     }
 
@@ -495,45 +680,7 @@ impl Widget for Systray {
 
 impl Drop for Systray {
     fn drop(&mut self) {
-        let setup = self.connection.get_setup();
-        let screen = setup.roots().nth(self.screen_id as _).unwrap();
-        let root = screen.root();
-
-        for window in &self.children {
-            let window = *window;
-            self.connection
-                .send_and_check_request(&ChangeWindowAttributes {
-                    window,
-                    value_list: &[Cw::EventMask(EventMask::NO_EVENT)],
-                })
-                .ok();
-            self.connection
-                .send_and_check_request(&UnmapWindow { window })
-                .ok();
-            self.connection
-                .send_and_check_request(
-                    &(ReparentWindow {
-                        window,
-                        parent: root,
-                        x: 0,
-                        y: 0,
-                    }),
-                )
-                .ok();
-        }
-
-        if let Some(window) = self.window {
-            self.connection
-                .send_and_check_request(&ChangeWindowAttributes {
-                    window,
-                    value_list: &[Cw::EventMask(EventMask::STRUCTURE_NOTIFY)],
-                })
-                .ok();
-            self.connection
-                .send_and_check_request(&DestroyWindow { window })
-                .ok();
-        }
-        self.connection.flush().ok();
+        self.release_children();
     }
 }
 
@@ -546,7 +693,9 @@ impl Display for Systray {
 #[derive(Debug)]
 enum SystrayEvent {
     ClientMessage(ClientMessageEvent),
+    ConfigureRequest((Window, u16, u16)),
     DestroyNotify(Window),
+    MapRequest(Window),
     PropertyNotify,
     ReparentNotify((Window, Window)),
     SelectionClear,
@@ -557,7 +706,11 @@ impl From<xcb::x::Event> for SystrayEvent {
     fn from(value: xcb::x::Event) -> Self {
         match value {
             xcb::x::Event::ClientMessage(event) => Self::ClientMessage(event),
+            xcb::x::Event::ConfigureRequest(event) => {
+                Self::ConfigureRequest((event.window(), event.width(), event.height()))
+            }
             xcb::x::Event::DestroyNotify(event) => Self::DestroyNotify(event.window()),
+            xcb::x::Event::MapRequest(event) => Self::MapRequest(event.window()),
             xcb::x::Event::PropertyNotify(_) => Self::PropertyNotify,
             xcb::x::Event::ReparentNotify(event) => {
                 Self::ReparentNotify((event.parent(), event.window()))
@@ -0,0 +1,327 @@
+use crate::{
+    utils::{set_source_rgba, shared_connection, Atoms, Color, HookSender, TimedHooks},
+    widgets::{
+        workspaces::get_current_desktop, ClickEvent, MouseButton, Rectangle, Result, Size, Widget,
+        WidgetConfig,
+    },
+};
+use async_trait::async_trait;
+use cairo::Context;
+use log::{debug, error};
+use pango::{FontDescription, Layout};
+use pangocairo::functions::{create_context, show_layout};
+use std::{
+    fmt::Display,
+    sync::{Arc, Mutex},
+    thread,
+};
+use xcb::{Connection, XidNew};
+
+#[derive(Debug)]
+struct Task {
+    window: u32,
+    title: String,
+    active: bool,
+}
+
+/// Displays the windows on the current workspace
+///
+/// Left click focuses a window, middle click closes it
+pub struct TaskList {
+    padding: u32,
+    fg_color: Color,
+    active_window_color: Color,
+    font: String,
+    font_size: f64,
+    internal_padding: u32,
+    max_title_len: usize,
+    tasks: Vec<Task>,
+    // pixel width of each rendered title, filled in during draw
+    // so clicks can be mapped back to a window
+    extents: Mutex<Vec<u32>>,
+    connection: Arc<Connection>,
+}
+
+impl std::fmt::Debug for TaskList {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "tasks: {:?}", self.tasks)
+    }
+}
+
+impl TaskList {
+    ///* `active_window_color` color of the focused window title
+    ///* `internal_padding` space to leave between window titles
+    ///* `max_title_len` longer titles are truncated with an ellipsis
+    ///* `config` a [&WidgetConfig]
+    pub async fn new(
+        active_window_color: Color,
+        internal_padding: u32,
+        max_title_len: usize,
+        config: &WidgetConfig,
+    ) -> Result<Box<Self>> {
+        let (connection, _) = shared_connection().map_err(Error::from)?;
+        Ok(Box::new(Self {
+            padding: config.padding,
+            fg_color: config.fg_color,
+            active_window_color,
+            font: config.font.to_owned(),
+            font_size: config.font_size,
+            internal_padding,
+            max_title_len,
+            tasks: Vec::new(),
+            extents: Mutex::new(Vec::new()),
+            connection,
+        }))
+    }
+
+    fn get_layout(&self, context: &Context) -> Layout {
+        let pango_context = create_context(context);
+        let layout = Layout::new(&pango_context);
+        let mut font = FontDescription::from_string(&self.font);
+        font.set_absolute_size(self.font_size * f64::from(pango::SCALE));
+        layout.set_font_description(Some(&font));
+        layout
+    }
+
+    fn truncate(&self, title: &str) -> String {
+        if title.chars().count() <= self.max_title_len {
+            return title.to_string();
+        }
+        let mut out: String = title.chars().take(self.max_title_len).collect();
+        out.push('…');
+        out
+    }
+
+    fn window_property(&self, window: xcb::x::Window, property: xcb::x::Atom) -> Option<u32> {
+        let cookie = self.connection.send_request(&xcb::x::GetProperty {
+            delete: false,
+            window,
+            property,
+            r#type: xcb::x::ATOM_CARDINAL,
+            long_offset: 0,
+            long_length: 1,
+        });
+        let reply = self.connection.wait_for_reply(cookie).ok()?;
+        reply.value::<u32>().first().cloned()
+    }
+
+    fn window_title(&self, window: xcb::x::Window, atoms: &Atoms) -> Option<String> {
+        let cookie = self.connection.send_request(&xcb::x::GetProperty {
+            delete: false,
+            window,
+            property: atoms._NET_WM_NAME,
+            r#type: atoms.UTF8_STRING,
+            long_offset: 0,
+            long_length: u32::MAX,
+        });
+        let reply = self.connection.wait_for_reply(cookie).ok()?;
+        String::from_utf8(reply.value::<u8>().into()).ok()
+    }
+
+    fn read_tasks(&self) -> Result<Vec<Task>> {
+        let atoms = Atoms::new(&self.connection).map_err(Error::from)?;
+        let root = self.connection.get_setup().roots().next().unwrap().root();
+        let current_desktop = get_current_desktop(&self.connection)?;
+
+        let cookie = self.connection.send_request(&xcb::x::GetProperty {
+            delete: false,
+            window: root,
+            property: atoms._NET_ACTIVE_WINDOW,
+            r#type: xcb::x::ATOM_WINDOW,
+            long_offset: 0,
+            long_length: 1,
+        });
+        let reply = self.connection.wait_for_reply(cookie).map_err(Error::Xcb)?;
+        let active_window = reply.value::<u32>().first().cloned().unwrap_or(0);
+
+        let cookie = self.connection.send_request(&xcb::x::GetProperty {
+            delete: false,
+            window: root,
+            property: atoms._NET_CLIENT_LIST,
+            r#type: xcb::x::ATOM_WINDOW,
+            long_offset: 0,
+            long_length: u32::MAX,
+        });
+        let reply = self.connection.wait_for_reply(cookie).map_err(Error::Xcb)?;
+
+        let mut tasks = Vec::new();
+        for window_id in reply.value::<u32>() {
+            let window = unsafe { xcb::x::Window::new(*window_id) };
+            if self.window_property(window, atoms._NET_WM_DESKTOP) != Some(current_desktop) {
+                continue;
+            }
+            let Some(title) = self.window_title(window, atoms) else {
+                continue;
+            };
+            tasks.push(Task {
+                window: *window_id,
+                title: self.truncate(&title),
+                active: *window_id == active_window,
+            });
+        }
+        Ok(tasks)
+    }
+
+    fn send_window_message(&self, window: u32, property: xcb::x::Atom) -> Result<()> {
+        let atoms = Atoms::new(&self.connection).map_err(Error::from)?;
+        let root = self.connection.get_setup().roots().next().unwrap().root();
+        // both messages carry source indication 2 (a pager) and a timestamp,
+        // but _NET_CLOSE_WINDOW wants them in the opposite order
+        let data = if property == atoms._NET_CLOSE_WINDOW {
+            [xcb::x::CURRENT_TIME, 2, 0, 0, 0]
+        } else {
+            [2, xcb::x::CURRENT_TIME, 0, 0, 0]
+        };
+        let event = xcb::x::ClientMessageEvent::new(
+            unsafe { xcb::x::Window::new(window) },
+            property,
+            xcb::x::ClientMessageData::Data32(data),
+        );
+        self.connection
+            .send_and_check_request(&xcb::x::SendEvent {
+                propagate: false,
+                destination: xcb::x::SendEventDest::Window(root),
+                event_mask: xcb::x::EventMask::SUBSTRUCTURE_NOTIFY
+                    | xcb::x::EventMask::SUBSTRUCTURE_REDIRECT,
+                event: &event,
+            })
+            .map_err(Error::from)?;
+        self.connection.flush().map_err(Error::from)?;
+        Ok(())
+    }
+
+    fn task_at(&self, x: u32) -> Option<u32> {
+        let extents = self.extents.lock().unwrap();
+        let mut start = self.padding;
+        for (task, width) in self.tasks.iter().zip(extents.iter()) {
+            if (start..start + width).contains(&x) {
+                return Some(task.window);
+            }
+            start += width + self.internal_padding;
+        }
+        None
+    }
+}
+
+#[async_trait]
+impl Widget for TaskList {
+    fn draw(&self, context: Context, rectangle: &Rectangle) -> Result<()> {
+        context.move_to(f64::from(self.padding), 0.0);
+        let layout = self.get_layout(&context);
+        let mut extents = Vec::with_capacity(self.tasks.len());
+        let mut first = true;
+        for task in &self.tasks {
+            let color = if task.active {
+                self.active_window_color
+            } else {
+                self.fg_color
+            };
+            set_source_rgba(&context, color);
+            layout.set_text(&task.title);
+            if first {
+                first = false;
+                context.rel_move_to(
+                    0.0,
+                    f64::from((rectangle.height - layout.pixel_size().1 as u32) / 2),
+                );
+            }
+            show_layout(&context, &layout);
+            let width = layout.pixel_size().0 as u32;
+            extents.push(width);
+            context.rel_move_to(f64::from(self.internal_padding) + f64::from(width), 0.0);
+        }
+        *self.extents.lock().unwrap() = extents;
+        Ok(())
+    }
+
+    async fn update(&mut self) -> Result<()> {
+        debug!("updating task_list");
+        self.tasks = self.read_tasks()?;
+        Ok(())
+    }
+
+    async fn on_click(&mut self, event: ClickEvent) -> Result<()> {
+        let Some(window) = self.task_at(event.x) else {
+            return Ok(());
+        };
+        let atoms = Atoms::new(&self.connection).map_err(Error::from)?;
+        match event.button {
+            MouseButton::Left => self.send_window_message(window, atoms._NET_ACTIVE_WINDOW),
+            MouseButton::Middle => self.send_window_message(window, atoms._NET_CLOSE_WINDOW),
+            _ => Ok(()),
+        }
+    }
+
+    async fn hook(&mut self, sender: HookSender, _timed_hooks: &mut TimedHooks) -> Result<()> {
+        let (connection, screen_id) = Connection::connect(None).unwrap();
+        let root_window = connection
+            .get_setup()
+            .roots()
+            .nth(screen_id as usize)
+            .unwrap()
+            .root();
+        connection
+            .send_and_check_request(&xcb::x::ChangeWindowAttributes {
+                window: root_window,
+                value_list: &[xcb::x::Cw::EventMask(xcb::x::EventMask::PROPERTY_CHANGE)],
+            })
+            .map_err(Error::from)?;
+        connection.flush().map_err(Error::from)?;
+        thread::spawn(move || loop {
+            if matches!(
+                connection.wait_for_event(),
+                Ok(xcb::Event::X(xcb::x::Event::PropertyNotify(_)))
+            ) && sender.send_blocking().is_err()
+            {
+                error!("breaking task_list hook");
+                break;
+            }
+        });
+        Ok(())
+    }
+
+    fn size(&self, context: &Context) -> Result<Size> {
+        if self.tasks.is_empty() {
+            return Ok(Size::Static(2 * self.padding));
+        }
+        let layout = self.get_layout(context);
+        let big_string = self
+            .tasks
+            .iter()
+            .map(|t| t.title.clone())
+            .collect::<String>();
+        layout.set_text(&big_string);
+        let text_size = layout.pixel_size().0 as u32;
+        Ok(Size::Static(
+            text_size + (2 * self.padding) + (self.tasks.len() as u32 * self.internal_padding),
+        ))
+    }
+
+    fn padding(&self) -> u32 {
+        self.padding
+    }
+}
+
+impl Display for TaskList {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        String::from("TaskList").fmt(f)
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error(transparent)]
+pub enum Error {
+    Xcb(#[from] xcb::Error),
+}
+
+impl From<xcb::ConnError> for Error {
+    fn from(e: xcb::ConnError) -> Self {
+        Error::Xcb(xcb::Error::Connection(e))
+    }
+}
+
+impl From<xcb::ProtocolError> for Error {
+    fn from(e: xcb::ProtocolError) -> Self {
+        Error::Xcb(xcb::Error::Protocol(e))
+    }
+}
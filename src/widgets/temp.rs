@@ -1,4 +1,4 @@
-use crate::utils::{HookSender, TimedHooks};
+use crate::utils::{metrics, HookSender, TimedHooks};
 use crate::{
     widget_default,
     widgets::{Result, Text, Widget, WidgetConfig},
@@ -37,6 +37,7 @@ impl Widget for Temperatures {
             temp += elem.current().celsius();
             count += 1.0;
         }
+        metrics::gauge("temperature_celsius", temp / count);
         let text = self.format.replace("%t", &format!("{:.1}", temp / count));
         self.inner.set_text(text);
         Ok(())
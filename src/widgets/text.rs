@@ -1,22 +1,84 @@
 use crate::{
-    utils::{set_source_rgba, Color},
-    widgets::{Rectangle, Result, Size, Widget, WidgetConfig},
+    utils::{format, set_source_rgba, Background, Color, FormatValue, HookSender, Position, TimedHooks},
+    widgets::{Accent, Rectangle, Result, Size, Widget, WidgetConfig},
 };
 use async_trait::async_trait;
 use cairo::Context;
-use pango::{FontDescription, Layout};
-use pangocairo::functions::{create_context, show_layout};
-use std::fmt::Display;
+use pango::{EllipsizeMode, FontDescription, Layout, WrapMode};
+use pangocairo::functions::{create_context, show_layout, update_layout};
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    fmt::Display,
+    sync::atomic::{AtomicBool, AtomicU32, Ordering},
+};
+
+thread_local! {
+    /// Layouts shared by every [Text] on this thread, keyed by font,
+    /// so draw does not rebuild pango state on every frame
+    static LAYOUT_CACHE: RefCell<HashMap<(String, u64, bool), Layout>> = RefCell::new(HashMap::new());
+}
+
+/// Makes a [Text] scroll horizontally when it exceeds `max_width`
+#[derive(Debug, Clone)]
+pub struct Marquee {
+    /// pixels scrolled per frame
+    pub speed: u32,
+    /// widget width while scrolling
+    pub max_width: u32,
+    /// frames to pause at both ends
+    pub pause: u32,
+}
+
+impl Default for Marquee {
+    fn default() -> Self {
+        Self {
+            speed: 2,
+            max_width: 300,
+            pause: 10,
+        }
+    }
+}
+
+/// Horizontal alignment of a [Text] inside its region
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TextAlign {
+    #[default]
+    Left,
+    Center,
+    Right,
+}
 
 /// Displays custom text
 #[derive(Debug)]
 pub struct Text {
     text: String,
+    // reused by set_format so ticks with an unchanged value allocate nothing
+    scratch: String,
     padding: u32,
     fg_color: Color,
+    background: Option<Background>,
     font: String,
     font_size: f64,
     flex: bool,
+    flex_weight: u32,
+    width_percent: Option<f64>,
+    priority: u32,
+    align: TextAlign,
+    min_width: Option<u32>,
+    max_width: Option<u32>,
+    tabular_figures: bool,
+    ellipsize: EllipsizeMode,
+    wrap: Option<WrapMode>,
+    single_line: bool,
+    accent: Option<Accent>,
+    marquee: Option<Marquee>,
+    offset: u32,
+    pause_left: u32,
+    // pixel width of the full text, re-measured when the text changes,
+    // atomics so size can cache through &self without making Text !Sync
+    text_width: AtomicU32,
+    measured: AtomicBool,
 }
 
 impl Text {
@@ -25,53 +87,307 @@ impl Text {
     pub async fn new(text: impl ToString, config: &WidgetConfig) -> Box<Self> {
         Box::new(Self {
             text: text.to_string(),
+            scratch: String::new(),
             padding: config.padding,
             fg_color: config.fg_color,
+            background: config.background.clone(),
             font: config.font.clone(),
             font_size: config.font_size,
             flex: config.flex,
+            flex_weight: 1,
+            width_percent: None,
+            priority: config.priority,
+            align: TextAlign::default(),
+            min_width: None,
+            max_width: None,
+            tabular_figures: false,
+            ellipsize: config.ellipsize,
+            wrap: config.wrap,
+            single_line: config.single_line,
+            accent: config.accent,
+            marquee: None,
+            offset: 0,
+            pause_left: 0,
+            text_width: AtomicU32::new(0),
+            measured: AtomicBool::new(false),
         })
     }
 
-    pub fn set_text(&mut self, text: impl ToString) {
-        self.text = text.to_string();
+    /// Scrolls the text horizontally when it is wider than the widget
+    pub fn with_marquee(mut self: Box<Self>, marquee: Marquee) -> Box<Self> {
+        self.set_marquee(marquee);
+        self
+    }
+
+    /// Aligns the text inside its region, visible when the
+    /// widget is flex and has extra space
+    pub fn with_align(mut self: Box<Self>, align: TextAlign) -> Box<Self> {
+        self.set_align(align);
+        self
+    }
+
+    pub fn set_align(&mut self, align: TextAlign) {
+        self.align = align;
+    }
+
+    /// Keeps the widget at least this wide so neighbours do not
+    /// shift when the text changes (e.g. `7%` vs `77%`)
+    pub fn with_min_width(mut self: Box<Self>, min_width: u32) -> Box<Self> {
+        self.min_width = Some(min_width);
+        self
+    }
+
+    /// Caps how wide the widget can grow, mostly useful to
+    /// bound a flex widget
+    pub fn with_max_width(mut self: Box<Self>, max_width: u32) -> Box<Self> {
+        self.max_width = Some(max_width);
+        self
+    }
+
+    /// Makes the widget flex with this share of the leftover
+    /// space, a plain flex widget has weight 1
+    pub fn with_flex_weight(mut self: Box<Self>, weight: u32) -> Box<Self> {
+        self.flex = true;
+        self.flex_weight = weight;
+        self
+    }
+
+    /// Reserves a fixed fraction (`0.0..=1.0`) of the space left
+    /// over by the static widgets
+    pub fn with_width_percent(mut self: Box<Self>, percent: f64) -> Box<Self> {
+        self.flex = true;
+        self.width_percent = Some(percent);
+        self
+    }
+
+    /// Larger priorities survive longer when the bar overflows,
+    /// see [OverflowPolicy](crate::statusbar::OverflowPolicy)
+    pub fn with_priority(mut self: Box<Self>, priority: u32) -> Box<Self> {
+        self.priority = priority;
+        self
+    }
+
+    /// Renders digits at a fixed width (tabular figures),
+    /// stopping numeric widgets from jittering
+    pub fn with_tabular_figures(mut self: Box<Self>) -> Box<Self> {
+        self.tabular_figures = true;
+        self.measured.store(false, Ordering::Relaxed);
+        self
+    }
+
+    pub fn set_marquee(&mut self, marquee: Marquee) {
+        self.pause_left = marquee.pause;
+        self.marquee = Some(marquee);
+    }
+
+    pub fn set_text(&mut self, text: impl AsRef<str>) {
+        let text = text.as_ref();
+        if text == self.text {
+            return;
+        }
+        self.text.clear();
+        self.text.push_str(text);
+        self.text_changed();
+    }
+
+    /// Renders `template` with [format](crate::utils::format::format)
+    /// into a buffer reused across calls, skipping all the layout
+    /// work when the result is unchanged
+    pub fn set_format(&mut self, template: &str, values: &[(char, FormatValue)]) {
+        self.scratch.clear();
+        format::format_into(template, values, &mut self.scratch);
+        if self.scratch == self.text {
+            return;
+        }
+        std::mem::swap(&mut self.text, &mut self.scratch);
+        self.text_changed();
+    }
+
+    fn text_changed(&mut self) {
+        self.offset = 0;
+        self.pause_left = self.marquee.as_ref().map(|m| m.pause).unwrap_or(0);
+        self.measured.store(false, Ordering::Relaxed);
     }
 
     pub fn clear(&mut self) {
         self.text.clear();
     }
 
+    pub fn set_fg_color(&mut self, color: Color) {
+        self.fg_color = color;
+    }
+
+    /// The configured font, used by the glyph coverage check in
+    /// [icons](crate::utils::icons)
+    pub fn font(&self) -> &str {
+        &self.font
+    }
+
     fn get_layout(&self, context: &Context) -> Result<Layout> {
-        let pango_context = create_context(context);
-        let layout = Layout::new(&pango_context);
-        let mut font = FontDescription::from_string(&self.font);
-        font.set_absolute_size(self.font_size * f64::from(pango::SCALE));
-        layout.set_font_description(Some(&font));
-        Ok(layout)
+        LAYOUT_CACHE.with(|cache| {
+            let mut cache = cache.borrow_mut();
+            let key = (
+                self.font.clone(),
+                self.font_size.to_bits(),
+                self.tabular_figures,
+            );
+            if let Some(layout) = cache.get(&key) {
+                // reattach the cached layout to the current cairo context
+                update_layout(context, layout);
+                return Ok(layout.clone());
+            }
+            let pango_context = create_context(context);
+            let layout = Layout::new(&pango_context);
+            let mut font = FontDescription::from_string(&self.font);
+            font.set_absolute_size(self.font_size * f64::from(pango::SCALE));
+            layout.set_font_description(Some(&font));
+            if self.tabular_figures {
+                let attributes = pango::AttrList::new();
+                attributes.insert(pango::AttrFontFeatures::new("tnum=1"));
+                layout.set_attributes(Some(&attributes));
+            }
+            cache.insert(key, layout.clone());
+            Ok(layout)
+        })
+    }
+
+    /// how far the text can scroll before the end is visible
+    fn max_offset(&self) -> u32 {
+        let Some(marquee) = &self.marquee else {
+            return 0;
+        };
+        self.text_width
+            .load(Ordering::Relaxed)
+            .saturating_sub(marquee.max_width)
     }
 }
 
 #[async_trait]
 impl Widget for Text {
     fn draw(&self, context: Context, rectangle: &Rectangle) -> Result<()> {
+        if let Some(background) = &self.background {
+            background
+                .apply(&context, rectangle.width as f64, rectangle.height as f64)
+                .map_err(Error::from)?;
+        }
         set_source_rgba(&context, self.fg_color);
         let layout = self.get_layout(&context)?;
+        // the layout is shared, restore these on every draw
+        layout.set_single_paragraph_mode(self.single_line);
+        layout.set_ellipsize(self.ellipsize);
+        if let Some(wrap) = self.wrap {
+            layout.set_wrap(wrap);
+        }
+        if self.ellipsize != EllipsizeMode::None || self.wrap.is_some() {
+            layout.set_width(rectangle.width as i32 * pango::SCALE);
+        } else {
+            layout.set_width(-1);
+        }
+        // only re-shape when the text actually changed
+        if layout.text().as_str() != self.text {
+            layout.set_text(&self.text);
+        }
+        let (text_width, text_height) = layout.pixel_size();
+        if self.max_offset() > 0 {
+            context.rectangle(
+                0.0,
+                0.0,
+                f64::from(rectangle.width),
+                f64::from(rectangle.height),
+            );
+            context.clip();
+        }
+        let free_space = (f64::from(rectangle.width) - f64::from(text_width)).max(0.0);
+        let x = match self.align {
+            TextAlign::Left => 0.0,
+            TextAlign::Center => free_space / 2.0,
+            TextAlign::Right => free_space,
+        };
         context.move_to(
-            0.,
-            f64::from((rectangle.height - layout.pixel_size().1 as u32) / 2),
+            x - f64::from(self.offset),
+            f64::from((rectangle.height - text_height as u32) / 2),
         );
-        layout.set_text(&self.text);
         show_layout(&context, &layout);
+        if let Some(accent) = &self.accent {
+            set_source_rgba(&context, accent.color);
+            let y = match accent.position {
+                Position::Top => 0.0,
+                Position::Bottom => f64::from(rectangle.height) - f64::from(accent.thickness),
+            };
+            context.rectangle(0.0, y, f64::from(rectangle.width), f64::from(accent.thickness));
+            context.fill().map_err(Error::from)?;
+        }
+        Ok(())
+    }
+
+    async fn update(&mut self) -> Result<()> {
+        let Some(marquee) = &self.marquee else {
+            return Ok(());
+        };
+        let max_offset = self.max_offset();
+        if max_offset == 0 {
+            self.offset = 0;
+            return Ok(());
+        }
+        if self.pause_left > 0 {
+            self.pause_left -= 1;
+            return Ok(());
+        }
+        if self.offset >= max_offset {
+            self.offset = 0;
+        } else {
+            self.offset = (self.offset + marquee.speed).min(max_offset);
+        }
+        if self.offset == 0 || self.offset == max_offset {
+            self.pause_left = marquee.pause;
+        }
+        Ok(())
+    }
+
+    async fn hook(&mut self, sender: HookSender, pool: &mut TimedHooks) -> Result<()> {
+        if self.marquee.is_some() {
+            pool.subscribe(sender);
+        }
         Ok(())
     }
 
     fn size(&self, context: &Context) -> Result<Size> {
         if self.flex {
+            if let Some(percent) = self.width_percent {
+                return Ok(Size::Percent(percent));
+            }
+            if self.flex_weight != 1 || self.min_width.is_some() || self.max_width.is_some() {
+                return Ok(Size::Weighted {
+                    weight: self.flex_weight,
+                    min: self.min_width,
+                    max: self.max_width,
+                });
+            }
             return Ok(Size::Flex);
         }
-        let layout = self.get_layout(context)?;
-        layout.set_text(&self.text);
-        let size = layout.pixel_size().0 as u32;
+        if !self.measured.load(Ordering::Relaxed) {
+            let layout = self.get_layout(context)?;
+            // measure the natural width, ellipsization only applies in draw
+            layout.set_width(-1);
+            layout.set_ellipsize(EllipsizeMode::None);
+            layout.set_single_paragraph_mode(self.single_line);
+            if layout.text().as_str() != self.text {
+                layout.set_text(&self.text);
+            }
+            self.text_width
+                .store(layout.pixel_size().0 as u32, Ordering::Relaxed);
+            self.measured.store(true, Ordering::Relaxed);
+        }
+        let mut size = self.text_width.load(Ordering::Relaxed);
+        if let Some(min_width) = self.min_width {
+            size = size.max(min_width);
+        }
+        if let Some(max_width) = self.max_width {
+            size = size.min(max_width);
+        }
+        if let Some(marquee) = &self.marquee {
+            return Ok(Size::Static(size.min(marquee.max_width)));
+        }
         Ok(Size::Static(size))
     }
 
@@ -82,6 +398,10 @@ impl Widget for Text {
             self.padding
         }
     }
+
+    fn priority(&self) -> u32 {
+        self.priority
+    }
 }
 
 impl Display for Text {
@@ -91,4 +411,7 @@ impl Display for Text {
 }
 
 #[derive(thiserror::Error, Debug)]
-pub enum Error {}
+#[error(transparent)]
+pub enum Error {
+    Cairo(#[from] cairo::Error),
+}
@@ -0,0 +1,139 @@
+use crate::{
+    utils::{HookSender, TimedHooks},
+    widget_default,
+    widgets::{Result, Text, Widget, WidgetConfig},
+};
+use async_trait::async_trait;
+use log::debug;
+use std::{fmt::Display, fs, time::Instant};
+
+const RAPL_PATH: &str = "/sys/class/powercap/intel-rapl:0";
+
+/// Sum of the thermal throttle event counters of every cpu
+fn throttle_count() -> u64 {
+    let Ok(cpus) = fs::read_dir("/sys/devices/system/cpu") else {
+        return 0;
+    };
+    let mut total = 0;
+    for cpu in cpus.flatten() {
+        let path = cpu.path().join("thermal_throttle");
+        for counter in ["core_throttle_count", "package_throttle_count"] {
+            if let Ok(value) = fs::read_to_string(path.join(counter)) {
+                total += value.trim().parse::<u64>().unwrap_or(0);
+            }
+        }
+    }
+    total
+}
+
+/// Package power limit in microwatt from RAPL
+fn power_limit() -> Option<u64> {
+    let limit = fs::read_to_string(format!("{RAPL_PATH}/constraint_0_power_limit_uw")).ok()?;
+    limit.trim().parse().ok()
+}
+
+/// Package energy counter in microjoule from RAPL
+fn energy() -> Option<u64> {
+    let energy = fs::read_to_string(format!("{RAPL_PATH}/energy_uj")).ok()?;
+    energy.trim().parse().ok()
+}
+
+/// Icons used by [Throttle]
+#[derive(Debug)]
+pub struct ThrottleIcons {
+    ///displayed while the cpu is thermal throttling
+    pub thermal: String,
+    ///displayed while the package sits at its power limit
+    pub power: String,
+}
+
+impl Default for ThrottleIcons {
+    fn default() -> Self {
+        Self {
+            thermal: String::from("🔥"),
+            power: String::from("⚡"),
+        }
+    }
+}
+
+/// Warns when the cpu is thermal throttling or capped
+/// at its RAPL power limit, stays empty otherwise
+#[derive(Debug)]
+pub struct Throttle {
+    format: String,
+    icons: ThrottleIcons,
+    previous_count: Option<u64>,
+    previous_energy: Option<(u64, Instant)>,
+    inner: Text,
+}
+
+impl Throttle {
+    ///* `format`
+    ///  * *%i* will be replaced with the active warning icons
+    ///* `icons` sets a custom [ThrottleIcons]
+    ///* `config` a [&WidgetConfig]
+    pub async fn new(
+        format: impl ToString,
+        icons: Option<ThrottleIcons>,
+        config: &WidgetConfig,
+    ) -> Box<Self> {
+        Box::new(Self {
+            format: format.to_string(),
+            icons: icons.unwrap_or_default(),
+            previous_count: None,
+            previous_energy: None,
+            inner: *Text::new("", config).await,
+        })
+    }
+}
+
+#[async_trait]
+impl Widget for Throttle {
+    async fn update(&mut self) -> Result<()> {
+        debug!("updating throttle");
+        let count = throttle_count();
+        let thermal = matches!(self.previous_count, Some(previous) if count > previous);
+        self.previous_count = Some(count);
+
+        let mut power = false;
+        if let (Some(limit), Some(energy)) = (power_limit(), energy()) {
+            let now = Instant::now();
+            if let Some((previous, at)) = self.previous_energy {
+                let elapsed = now.duration_since(at).as_secs_f64();
+                if elapsed > 0.0 {
+                    let draw = energy.saturating_sub(previous) as f64 / elapsed;
+                    // sitting right at the limit means the package is capped
+                    power = draw >= limit as f64 * 0.95;
+                }
+            }
+            self.previous_energy = Some((energy, now));
+        }
+
+        if !thermal && !power {
+            self.inner.clear();
+            return Ok(());
+        }
+        let mut icons = String::new();
+        if thermal {
+            icons.push_str(&self.icons.thermal);
+        }
+        if power {
+            icons.push_str(&self.icons.power);
+        }
+        self.inner.set_text(self.format.replace("%i", &icons));
+        Ok(())
+    }
+
+    async fn hook(&mut self, sender: HookSender, timed_hooks: &mut TimedHooks) -> Result<()> {
+        timed_hooks.subscribe(sender);
+        Ok(())
+    }
+
+    widget_default!(draw, size, padding);
+}
+
+impl Display for Throttle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        String::from("Throttle").fmt(f)
+    }
+}
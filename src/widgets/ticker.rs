@@ -0,0 +1,148 @@
+use crate::{
+    utils::{http_client, Color, HookSender, TimedHooks},
+    widget_default,
+    widgets::{Result, Text, Widget, WidgetConfig},
+};
+use async_trait::async_trait;
+use log::{debug, error};
+use std::{fmt::Display, time::Duration};
+use tokio::time::sleep;
+
+#[async_trait]
+pub trait TickerProvider: std::fmt::Debug + Send {
+    /// returns the price and the 24h change percentage of a symbol
+    async fn price(&self, symbol: &str) -> Option<(f64, f64)>;
+}
+
+/// Fetches prices from the CoinGecko public api
+///
+/// Symbols are CoinGecko ids (e.g. `bitcoin`), prices are in the
+/// given vs currency (e.g. `usd`)
+#[derive(Debug)]
+pub struct CoinGeckoProvider {
+    vs_currency: String,
+}
+
+impl CoinGeckoProvider {
+    pub fn new(vs_currency: impl ToString) -> Box<Self> {
+        Box::new(Self {
+            vs_currency: vs_currency.to_string(),
+        })
+    }
+}
+
+#[async_trait]
+impl TickerProvider for CoinGeckoProvider {
+    async fn price(&self, symbol: &str) -> Option<(f64, f64)> {
+        let url = format!(
+            "https://api.coingecko.com/api/v3/simple/price?ids={}&vs_currencies={}&include_24hr_change=true",
+            symbol, self.vs_currency,
+        );
+        let response = http_client().get_json(&url).await.ok()??;
+        let coin = response.get(symbol)?;
+        let price = coin.get(&self.vs_currency)?.as_f64()?;
+        let change = coin
+            .get(format!("{}_24h_change", self.vs_currency))?
+            .as_f64()?;
+        Some((price, change))
+    }
+}
+
+/// Displays the price of a rotating list of symbols
+#[derive(Debug)]
+pub struct Ticker {
+    format: String,
+    symbols: Vec<String>,
+    current: usize,
+    interval: Duration,
+    provider: Box<dyn TickerProvider>,
+    up_color: Color,
+    down_color: Color,
+    inner: Text,
+}
+
+impl Ticker {
+    ///* `format`
+    ///  * *%s* will be replaced with the symbol
+    ///  * *%p* will be replaced with the price
+    ///  * *%c* will be replaced with the 24h change percentage
+    ///* `symbols` shown one at a time, advancing every `interval`
+    ///* `provider` implements [TickerProvider]
+    ///* `config` a [&WidgetConfig]
+    pub async fn new(
+        format: impl ToString,
+        symbols: Vec<String>,
+        interval: Duration,
+        provider: Box<impl TickerProvider + 'static>,
+        config: &WidgetConfig,
+    ) -> Box<Self> {
+        Box::new(Self {
+            format: format.to_string(),
+            symbols,
+            current: 0,
+            interval,
+            provider,
+            up_color: Color::new(0.0, 0.8, 0.0, 1.0),
+            down_color: Color::new(0.8, 0.0, 0.0, 1.0),
+            inner: *Text::new("", config).await,
+        })
+    }
+
+    /// Sets the colors used for positive and negative 24h changes
+    pub fn with_colors(mut self: Box<Self>, up: Color, down: Color) -> Box<Self> {
+        self.up_color = up;
+        self.down_color = down;
+        self
+    }
+}
+
+#[async_trait]
+impl Widget for Ticker {
+    async fn update(&mut self) -> Result<()> {
+        debug!("updating ticker");
+        if self.symbols.is_empty() {
+            return Ok(());
+        }
+        let symbol = self.symbols[self.current].clone();
+        // advance after picking so a fresh bar starts from the first symbol,
+        // even when the price fetch below fails
+        self.current = (self.current + 1) % self.symbols.len();
+        let Some((price, change)) = self.provider.price(&symbol).await else {
+            return Ok(());
+        };
+        let text = self
+            .format
+            .replace("%s", &symbol)
+            .replace("%p", &format!("{price:.2}"))
+            .replace("%c", &format!("{change:+.1}"));
+        self.inner.set_fg_color(if change < 0.0 {
+            self.down_color
+        } else {
+            self.up_color
+        });
+        self.inner.set_text(text);
+        Ok(())
+    }
+
+    async fn hook(&mut self, sender: HookSender, _timed_hooks: &mut TimedHooks) -> Result<()> {
+        let interval = self.interval;
+        tokio::spawn(async move {
+            loop {
+                if sender.send().await.is_err() {
+                    error!("breaking ticker hook");
+                    break;
+                }
+                sleep(interval).await;
+            }
+        });
+        Ok(())
+    }
+
+    widget_default!(draw, size, padding);
+}
+
+impl Display for Ticker {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        String::from("Ticker").fmt(f)
+    }
+}
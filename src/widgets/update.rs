@@ -1,22 +1,83 @@
 use crate::{
-    utils::{HookSender, TimedHooks},
+    utils::{connectivity, spawn, Color, HookSender, Popup, StatusBarInfo, TimedHooks},
     widget_default,
-    widgets::{Result, Text, Widget, WidgetConfig},
+    widgets::{ClickEvent, MouseButton, Result, Text, Widget, WidgetConfig},
 };
 use async_trait::async_trait;
 use log::error;
-use std::{fmt::Display, process::Stdio, time::Duration};
+use std::{fmt::Display, path::Path, process::Stdio, thread, time::Duration};
 use tokio::{
     io::{AsyncBufReadExt, BufReader},
     process::Command,
-    task,
+    select, task,
     time::sleep,
 };
 
+const POPUP_HEIGHT: u16 = 300;
+
+/// True when the distro marks a reboot as required
+async fn reboot_required() -> bool {
+    if Path::new("/var/run/reboot-required").exists() {
+        return true;
+    }
+    // needs-restarting exits non-zero when a reboot is needed
+    Command::new("needs-restarting")
+        .arg("-r")
+        .output()
+        .await
+        .is_ok_and(|out| !out.status.success())
+}
+
+/// Runs the popup until the user clicks, clicking the upgrade
+/// button launches `command` in a terminal
+fn run_update_popup(
+    mut popup: Popup,
+    lines: Vec<String>,
+    button_index: Option<usize>,
+    terminal: String,
+    command: Option<String>,
+) {
+    if let Err(e) = popup.set_lines(lines) {
+        error!("failed to draw update popup: {e}");
+        return;
+    }
+    if let Err(e) = popup.show() {
+        error!("failed to show update popup: {e}");
+        return;
+    }
+    loop {
+        let Ok(event) = popup.next_event() else {
+            break;
+        };
+        let xcb::Event::X(xcb::x::Event::ButtonPress(press)) = event else {
+            continue;
+        };
+        if press.detail() == 1
+            && popup.line_at(f64::from(press.event_y())) == button_index
+            && button_index.is_some()
+        {
+            if let Some(command) = &command {
+                let mut upgrade = std::process::Command::new(&terminal);
+                upgrade.args(["-e", "sh", "-c", command]);
+                spawn::spawn_command(upgrade, command.clone());
+            }
+        }
+        break;
+    }
+    popup.hide().ok();
+}
+
 #[derive(Debug)]
 pub struct Update {
     inner: Text,
     sources: Vec<Box<dyn UpdateSource>>,
+    /// pending updates of every source, refreshed on update
+    updates: Vec<(String, Vec<String>)>,
+    reboot_icon: String,
+    upgrade_command: Option<String>,
+    terminal: String,
+    config: WidgetConfig,
+    popup_y: i16,
 }
 
 impl Update {
@@ -24,30 +85,110 @@ impl Update {
         Box::new(Self {
             inner: *Text::new("", config).await,
             sources,
+            updates: Vec::new(),
+            reboot_icon: String::from("⟳"),
+            upgrade_command: None,
+            terminal: String::from("xterm"),
+            config: config.clone(),
+            popup_y: 0,
         })
     }
+
+    /// Icon shown while the distro requires a reboot (default "⟳")
+    pub fn reboot_icon(mut self: Box<Self>, icon: impl ToString) -> Box<Self> {
+        self.reboot_icon = icon.to_string();
+        self
+    }
+
+    /// Command the popup upgrade button runs in a terminal
+    pub fn upgrade_command(mut self: Box<Self>, command: impl ToString) -> Box<Self> {
+        self.upgrade_command = Some(command.to_string());
+        self
+    }
+
+    /// Terminal emulator used for the upgrade command (default "xterm")
+    pub fn terminal(mut self: Box<Self>, terminal: impl ToString) -> Box<Self> {
+        self.terminal = terminal.to_string();
+        self
+    }
 }
 
 #[async_trait]
 impl Widget for Update {
+    async fn setup(&mut self, info: &StatusBarInfo) -> Result<()> {
+        self.popup_y = info.popup_y(POPUP_HEIGHT);
+        Ok(())
+    }
+
+    async fn on_click(&mut self, event: ClickEvent) -> Result<()> {
+        if event.button != MouseButton::Left {
+            return Ok(());
+        }
+        let mut lines = Vec::new();
+        for (name, updates) in &self.updates {
+            if updates.is_empty() {
+                continue;
+            }
+            lines.push(format!("{name}:"));
+            lines.extend(updates.iter().map(|update| format!("  {update}")));
+        }
+        if lines.is_empty() {
+            return Ok(());
+        }
+        let button_index = self.upgrade_command.is_some().then(|| {
+            lines.push(String::from("[ upgrade ]"));
+            lines.len() - 1
+        });
+        let config = self.config.clone();
+        let popup_y = self.popup_y;
+        let terminal = self.terminal.clone();
+        let command = self.upgrade_command.clone();
+        // the popup runs its own event loop, keep it off the bar one
+        thread::spawn(move || {
+            match Popup::new(
+                0,
+                popup_y,
+                400,
+                POPUP_HEIGHT,
+                Color::new(0.0, 0.0, 0.0, 0.9),
+                &config,
+            ) {
+                Ok(popup) => run_update_popup(popup, lines, button_index, terminal, command),
+                Err(e) => error!("failed to open update popup: {e}"),
+            }
+        });
+        Ok(())
+    }
+
     async fn update(&mut self) -> Result<()> {
-        let mut all_updates = Vec::new();
+        self.updates.clear();
+        let mut parts = Vec::new();
         for source in &mut self.sources {
-            if source.update_available().await? {
-                all_updates.push(source.message());
+            let updates = source.updates().await?;
+            if !updates.is_empty() {
+                parts.push(format!("{} {}", source.name(), updates.len()));
             }
+            self.updates.push((source.name(), updates));
         }
-        self.inner.set_text(all_updates.join(" - "));
+        if reboot_required().await {
+            parts.push(self.reboot_icon.clone());
+        }
+        self.inner.set_text(parts.join(" - "));
         Ok(())
     }
 
     async fn hook(&mut self, sender: HookSender, _pool: &mut TimedHooks) -> Result<()> {
         task::spawn(async move {
             loop {
+                connectivity().wait_until_online().await;
                 if let Err(e) = sender.send().await {
                     error!("error sending update hook: {}", e);
                 }
-                sleep(Duration::from_secs(60)).await;
+                select!(
+                    _ = sleep(Duration::from_secs(60)) => {}
+                    // refetch as soon as the connection returns
+                    _ = connectivity().wait_for_reconnect() => {}
+                );
             }
         });
         Ok(())
@@ -64,8 +205,9 @@ impl Display for Update {
 
 #[async_trait]
 pub trait UpdateSource: std::fmt::Debug + Send {
-    async fn update_available(&mut self) -> Result<bool>;
-    fn message(&self) -> String;
+    /// Pending updates as "package version" lines
+    async fn updates(&mut self) -> Result<Vec<String>>;
+    fn name(&self) -> String;
 }
 
 #[derive(Debug)]
@@ -79,7 +221,7 @@ impl Apt {
 
 #[async_trait]
 impl UpdateSource for Apt {
-    async fn update_available(&mut self) -> Result<bool> {
+    async fn updates(&mut self) -> Result<Vec<String>> {
         let mut child = Command::new("apt")
             .args(["list", "--upgradable"])
             .stdout(Stdio::piped())
@@ -90,17 +232,55 @@ impl UpdateSource for Apt {
         child.wait().await.unwrap();
         let stdout = child.stdout.take().unwrap();
         let mut lines = BufReader::new(stdout).lines();
+        // skip the "Listing..." header
         let _ = lines.next_line().await;
-        let line = lines.next_line().await.map_err(Error::from)?;
-
-        Ok(line.is_some())
+        let mut updates = Vec::new();
+        while let Some(line) = lines.next_line().await.map_err(Error::from)? {
+            // "package/suite version arch [upgradable from: old]"
+            let mut parts = line.split_whitespace();
+            let Some(package) = parts.next().and_then(|p| p.split('/').next()) else {
+                continue;
+            };
+            let version = parts.next().unwrap_or_default();
+            updates.push(format!("{package} {version}"));
+        }
+        Ok(updates)
     }
 
-    fn message(&self) -> String {
+    fn name(&self) -> String {
         "apt".to_string()
     }
 }
 
+#[derive(Debug)]
+pub struct Flatpak {}
+
+impl Flatpak {
+    pub fn new() -> Box<Self> {
+        Box::new(Self {})
+    }
+}
+
+#[async_trait]
+impl UpdateSource for Flatpak {
+    async fn updates(&mut self) -> Result<Vec<String>> {
+        let output = Command::new("flatpak")
+            .args(["remote-ls", "--updates", "--columns=name,version"])
+            .output()
+            .await
+            .map_err(Error::from)?;
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+            .collect())
+    }
+
+    fn name(&self) -> String {
+        "flatpak".to_string()
+    }
+}
+
 #[derive(Debug, thiserror::Error)]
 #[error(transparent)]
 pub enum Error {
@@ -1,11 +1,18 @@
 use crate::{
-    utils::{percentage_to_index, HookSender, ResettableTimer, TimedHooks},
+    utils::{
+        icons, percentage_to_index, set_source_rgba, Color, HookSender, IconSet, IconTheme, Popup,
+        ResettableTimer, StatusBarInfo, TimedHooks,
+    },
     widget_default,
-    widgets::{Result, Text, Widget, WidgetConfig},
+    widgets::{ClickEvent, MouseButton, OsdBar, Rectangle, Result, Size, Text, Widget, WidgetConfig},
 };
+use async_channel::{bounded, Receiver, Sender};
 use async_trait::async_trait;
-use log::debug;
-use std::{fmt::Display, marker::Send};
+use cairo::Context;
+use log::{debug, error};
+use std::{fmt::Display, marker::Send, thread};
+
+const POPUP_HEIGHT: u16 = 200;
 
 /// Icons used by [Volume]
 #[derive(Debug)]
@@ -24,7 +31,66 @@ impl Default for VolumeIcons {
         }
     }
 }
+
+impl IconSet for VolumeIcons {
+    fn glyphs(&self) -> Vec<&str> {
+        self.percentages
+            .iter()
+            .map(String::as_str)
+            .chain([self.muted.as_str()])
+            .collect()
+    }
+
+    fn for_theme(theme: IconTheme) -> Self {
+        match theme {
+            IconTheme::NerdFont => Self::default(),
+            IconTheme::Emoji => Self {
+                percentages: ["🔈", "🔉", "🔊"].map(String::from).to_vec(),
+                muted: String::from("🔇"),
+            },
+            IconTheme::Ascii => Self {
+                percentages: ["vol .", "vol o", "vol O"].map(String::from).to_vec(),
+                muted: String::from("vol x"),
+            },
+        }
+    }
+}
+/// Runs the sink popup until the user picks a device or closes it
+fn run_sink_picker(
+    mut popup: Popup,
+    lines: Vec<String>,
+    names: Vec<String>,
+    selection: Sender<String>,
+) {
+    if let Err(e) = popup.set_lines(lines) {
+        error!("failed to draw sink popup: {e}");
+        return;
+    }
+    if let Err(e) = popup.show() {
+        error!("failed to show sink popup: {e}");
+        return;
+    }
+    loop {
+        let Ok(event) = popup.next_event() else {
+            break;
+        };
+        let xcb::Event::X(xcb::x::Event::ButtonPress(press)) = event else {
+            continue;
+        };
+        if press.detail() == 1 {
+            if let Some(index) = popup.line_at(f64::from(press.event_y())) {
+                let _ = selection.send_blocking(names[index].clone());
+            }
+        }
+        break;
+    }
+    popup.hide().ok();
+}
+
 /// Displays status and volume of the audio device
+///
+/// Right click opens a popup listing the output devices,
+/// clicking one makes it the default sink
 #[derive(Debug)]
 pub struct Volume {
     format: String,
@@ -34,6 +100,13 @@ pub struct Volume {
     previous_volume: f64,
     previous_muted: bool,
     show_counter: ResettableTimer,
+    fg_color: Color,
+    osd: Option<OsdBar>,
+    osd_visible: bool,
+    current: f64,
+    config: WidgetConfig,
+    popup_y: i16,
+    sink_switch: Option<Receiver<String>>,
 }
 
 impl Volume {
@@ -57,10 +130,23 @@ impl Volume {
             previous_volume: 0.0,
             previous_muted: false,
             show_counter: ResettableTimer::new(config.hide_timeout),
+            fg_color: config.fg_color,
+            osd: None,
+            osd_visible: false,
+            current: 0.0,
+            config: config.clone(),
+            popup_y: 0,
+            sink_switch: None,
             inner: *Text::new("", config).await,
         })
     }
 
+    /// Shows an [OsdBar] while the volume is changing
+    pub fn with_osd(mut self: Box<Self>, osd: OsdBar) -> Box<Self> {
+        self.osd = Some(osd);
+        self
+    }
+
     fn build_string(&mut self, volume: f64, muted: bool) -> String {
         if muted {
             return self.icons.muted.clone();
@@ -75,8 +161,24 @@ impl Volume {
 
 #[async_trait]
 impl Widget for Volume {
+    async fn setup(&mut self, info: &StatusBarInfo) -> Result<()> {
+        icons::warn_missing_glyphs(&self.icons, self.inner.font(), "Volume");
+        // open the popup just below (or above) the bar
+        self.popup_y = info.popup_y(POPUP_HEIGHT);
+        Ok(())
+    }
+
     async fn update(&mut self) -> Result<()> {
         debug!("updating volume");
+        if let Some(receiver) = &self.sink_switch {
+            if let Ok(name) = receiver.try_recv() {
+                debug!("switching default sink to {name}");
+                if self.provider.set_default_sink(&name).await.is_none() {
+                    error!("failed to switch sink");
+                }
+                self.sink_switch = None;
+            }
+        }
         let f = self.provider.volume_and_muted();
         let (volume, muted) = f.await.unwrap_or((0.0, false));
 
@@ -85,12 +187,51 @@ impl Widget for Volume {
             self.previous_volume = volume;
             self.show_counter.reset();
         }
+        self.current = volume;
         if self.show_counter.is_done() {
             self.inner.clear();
         } else {
             let text = self.build_string(volume, muted);
             self.inner.set_text(text);
         }
+        self.osd_visible = self.osd.is_some() && !self.show_counter.is_done();
+        Ok(())
+    }
+
+    async fn on_click(&mut self, event: ClickEvent) -> Result<()> {
+        if event.button != MouseButton::Right {
+            return Ok(());
+        }
+        let sinks = self.provider.sinks().await;
+        if sinks.is_empty() {
+            return Ok(());
+        }
+        let lines: Vec<String> = sinks
+            .iter()
+            .map(|(_, description, default)| {
+                let marker = if *default { "> " } else { "  " };
+                format!("{marker}{description}")
+            })
+            .collect();
+        let names: Vec<String> = sinks.into_iter().map(|(name, ..)| name).collect();
+        let (tx, rx) = bounded(1);
+        self.sink_switch = Some(rx);
+        let config = self.config.clone();
+        let popup_y = self.popup_y;
+        // the popup runs its own event loop, keep it off the bar one
+        thread::spawn(move || {
+            match Popup::new(
+                0,
+                popup_y,
+                400,
+                POPUP_HEIGHT,
+                Color::new(0.0, 0.0, 0.0, 0.9),
+                &config,
+            ) {
+                Ok(popup) => run_sink_picker(popup, lines, names, tx),
+                Err(e) => error!("failed to open sink popup: {e}"),
+            }
+        });
         Ok(())
     }
 
@@ -99,7 +240,44 @@ impl Widget for Volume {
         Ok(())
     }
 
-    widget_default!(draw, size, padding);
+    fn draw(&self, context: Context, rectangle: &Rectangle) -> Result<()> {
+        self.inner.draw(context.clone(), rectangle)?;
+        let Some(osd) = &self.osd else {
+            return Ok(());
+        };
+        if !self.osd_visible {
+            return Ok(());
+        }
+        // progress bar to the right of the icon text
+        let start = f64::from(rectangle.width).min(30.0);
+        let available = (f64::from(rectangle.width) - start - 5.0).max(0.0);
+        let y = (f64::from(rectangle.height) - f64::from(osd.thickness)) / 2.0;
+        let mut track = self.fg_color;
+        track.a *= 0.3;
+        set_source_rgba(&context, track);
+        context.rectangle(start, y, available, f64::from(osd.thickness));
+        context.fill().map_err(Error::from)?;
+        set_source_rgba(&context, self.fg_color);
+        context.rectangle(
+            start,
+            y,
+            available * self.current.clamp(0.0, 100.0) / 100.0,
+            f64::from(osd.thickness),
+        );
+        context.fill().map_err(Error::from)?;
+        Ok(())
+    }
+
+    fn size(&self, context: &Context) -> Result<Size> {
+        if self.osd_visible {
+            if let Some(osd) = &self.osd {
+                return Ok(Size::Static(osd.width));
+            }
+        }
+        self.inner.size(context)
+    }
+
+    widget_default!(padding);
 }
 
 impl Display for Volume {
@@ -109,10 +287,18 @@ impl Display for Volume {
 }
 
 #[async_trait]
-pub trait VolumeProvider: std::fmt::Debug + Send {
+pub trait VolumeProvider: std::fmt::Debug + Send + Sync {
     async fn volume(&self) -> Option<f64>;
     async fn muted(&self) -> Option<bool>;
     async fn volume_and_muted(&self) -> Option<(f64, bool)>;
+    /// Available output devices as (name, description, is_default)
+    async fn sinks(&self) -> Vec<(String, String, bool)> {
+        Vec::new()
+    }
+    /// Makes `name` the default output and moves the active streams to it
+    async fn set_default_sink(&mut self, _name: &str) -> Option<()> {
+        None
+    }
 }
 
 #[cfg(feature = "pulseaudio")]
@@ -123,7 +309,7 @@ pub mod pulseaudio {
     use async_channel::{bounded, Receiver, Sender};
     use async_trait::async_trait;
     use libpulse_binding::volume::{ChannelVolumes, Volume as PaVolume};
-    use pulsectl::controllers::DeviceControl;
+    use pulsectl::controllers::{AppControl, DeviceControl};
 
     fn volume_to_percent(volume: ChannelVolumes) -> f64 {
         let avg = volume.avg().0;
@@ -133,9 +319,21 @@ pub mod pulseaudio {
         (avg - PaVolume::MUTED.0) as f64 / base_delta
     }
 
+    enum Request {
+        Status,
+        Sinks,
+        SetDefault(String),
+    }
+
+    enum Response {
+        Status(Option<(f64, bool)>),
+        Sinks(Vec<(String, String, bool)>),
+        Done(bool),
+    }
+
     pub struct PulseaudioProvider {
-        request: Sender<()>,
-        data: Receiver<Option<(f64, bool)>>,
+        request: Sender<Request>,
+        data: Receiver<Response>,
     }
 
     impl PulseaudioProvider {
@@ -144,17 +342,61 @@ pub mod pulseaudio {
             let (data_tx, data_rx) = bounded(10);
             thread::spawn(move || {
                 let mut controller = pulsectl::controllers::SinkController::create().unwrap();
-                while request_rx.recv_blocking().is_ok() {
-                    let data = if let Ok(default_device) = controller.get_default_device() {
-                        Some((
-                            volume_to_percent(default_device.volume),
-                            default_device.mute,
-                        ))
-                    } else {
-                        None
+                while let Ok(request) = request_rx.recv_blocking() {
+                    let response = match request {
+                        Request::Status => {
+                            let data = if let Ok(default_device) = controller.get_default_device()
+                            {
+                                Some((
+                                    volume_to_percent(default_device.volume),
+                                    default_device.mute,
+                                ))
+                            } else {
+                                None
+                            };
+                            Response::Status(data)
+                        }
+                        Request::Sinks => {
+                            let default_name = controller
+                                .get_default_device()
+                                .ok()
+                                .and_then(|device| device.name);
+                            let sinks = controller
+                                .list_devices()
+                                .map(|devices| {
+                                    devices
+                                        .into_iter()
+                                        .filter_map(|device| {
+                                            let name = device.name?;
+                                            let description = device
+                                                .description
+                                                .unwrap_or_else(|| name.clone());
+                                            let default =
+                                                Some(&name) == default_name.as_ref();
+                                            Some((name, description, default))
+                                        })
+                                        .collect()
+                                })
+                                .unwrap_or_default();
+                            Response::Sinks(sinks)
+                        }
+                        Request::SetDefault(name) => {
+                            let mut ok = controller.set_default_device(&name).unwrap_or(false);
+                            // move the running streams along
+                            if ok {
+                                if let Ok(apps) = controller.list_applications() {
+                                    for app in apps {
+                                        if controller.move_app_by_name(app.index, &name).is_err()
+                                        {
+                                            ok = false;
+                                        }
+                                    }
+                                }
+                            }
+                            Response::Done(ok)
+                        }
                     };
-
-                    data_tx.send_blocking(data).unwrap();
+                    data_tx.send_blocking(response).unwrap();
                 }
             });
             Ok(Self {
@@ -173,22 +415,46 @@ pub mod pulseaudio {
     #[async_trait]
     impl VolumeProvider for PulseaudioProvider {
         async fn volume(&self) -> Option<f64> {
-            self.request.send(()).await.ok()?;
-            self.data.recv().await.ok()?.map(|(v, _)| v)
+            self.volume_and_muted().await.map(|(v, _)| v)
         }
 
         async fn muted(&self) -> Option<bool> {
-            self.request.send(()).await.ok()?;
-            self.data.recv().await.ok()?.map(|(_, m)| m)
+            self.volume_and_muted().await.map(|(_, m)| m)
         }
 
         async fn volume_and_muted(&self) -> Option<(f64, bool)> {
-            self.request.send(()).await.ok()?;
-            self.data.recv().await.ok()?
+            self.request.send(Request::Status).await.ok()?;
+            match self.data.recv().await.ok()? {
+                Response::Status(data) => data,
+                _ => None,
+            }
+        }
+
+        async fn sinks(&self) -> Vec<(String, String, bool)> {
+            if self.request.send(Request::Sinks).await.is_err() {
+                return Vec::new();
+            }
+            match self.data.recv().await {
+                Ok(Response::Sinks(sinks)) => sinks,
+                _ => Vec::new(),
+            }
+        }
+
+        async fn set_default_sink(&mut self, name: &str) -> Option<()> {
+            self.request
+                .send(Request::SetDefault(name.to_string()))
+                .await
+                .ok()?;
+            match self.data.recv().await.ok()? {
+                Response::Done(true) => Some(()),
+                _ => None,
+            }
         }
     }
 }
 
 #[derive(thiserror::Error, Debug)]
 #[error(transparent)]
-pub enum Error {}
+pub enum Error {
+    Cairo(#[from] cairo::Error),
+}
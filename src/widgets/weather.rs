@@ -1,42 +1,103 @@
 use crate::{
-    utils::{HookSender, TimedHooks},
+    utils::{connectivity, icons, HookSender, IconSet, IconTheme, StatusBarInfo, TimedHooks},
     widget_default,
     widgets::{Result, Text, Widget, WidgetConfig},
 };
 use async_trait::async_trait;
 use log::{debug, warn};
-use std::{fmt::Debug, time::Duration};
-use tokio::time::sleep;
+use std::{
+    fmt::Debug,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use tokio::{select, time::sleep};
 
-#[derive(Debug)]
+/// Unit of every temperature in a [Meteo]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TemperatureUnit {
+    Celsius,
+    Fahrenheit,
+}
+
+impl std::fmt::Display for TemperatureUnit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Celsius => f.write_str("°C"),
+            Self::Fahrenheit => f.write_str("°F"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct Meteo {
     pub code: f32,
     pub city: String,
-    pub current: String,
-    pub max: String,
-    pub min: String,
+    pub current: f32,
+    pub max: f32,
+    pub min: f32,
+    pub unit: TemperatureUnit,
+    /// local time of today's sunrise (e.g. `07:32`), empty when
+    /// the provider does not report it
+    pub sunrise: String,
+    /// local time of today's sunset
+    pub sunset: String,
+    /// temperature forecast for the coming hours, empty when the
+    /// provider does not report one
+    pub hourly: Vec<f32>,
+    /// max temperature of the coming days
+    pub daily_max: Vec<f32>,
+    /// min temperature of the coming days
+    pub daily_min: Vec<f32>,
+}
+
+impl Meteo {
+    /// A temperature followed by the unit (e.g. `21.3°C`)
+    pub fn display(&self, temperature: f32) -> String {
+        format!("{}{}", temperature, self.unit)
+    }
 }
 
 #[cfg(feature = "openmeteo")]
 pub mod openmeteo {
-    use super::{Error, Meteo, Result, WeatherProvider};
+    use super::{Error, Meteo, Result, TemperatureUnit, WeatherProvider};
+    use crate::utils::LocationProvider;
     use async_trait::async_trait;
     use ipgeolocate::{Locator, Service};
     use log::debug;
     use open_meteo_api::models::TimeZone;
 
     #[derive(Debug)]
-    pub struct OpenMeteoProvider;
+    pub struct OpenMeteoProvider {
+        location: Option<Box<dyn LocationProvider>>,
+    }
 
     impl OpenMeteoProvider {
         pub fn new() -> Box<Self> {
-            Box::new(Self)
+            Box::new(Self { location: None })
         }
-    }
 
-    #[async_trait]
-    impl WeatherProvider for OpenMeteoProvider {
-        async fn get_current_meteo(&self) -> Result<Meteo> {
+        /// Reads the coordinates from `provider` (e.g. geoclue)
+        /// instead of geolocating the machine's public ip
+        pub fn with_location_provider(
+            mut self: Box<Self>,
+            provider: Box<dyn LocationProvider>,
+        ) -> Box<Self> {
+            self.location = Some(provider);
+            self
+        }
+
+        /// Coordinates and place name from the configured source
+        async fn coordinates(&self) -> Result<(f32, f32, String)> {
+            if let Some(provider) = &self.location {
+                let location = provider
+                    .location()
+                    .await
+                    .ok_or(Error::MissingData("location"))?;
+                return Ok((
+                    location.latitude as f32,
+                    location.longitude as f32,
+                    location.city.unwrap_or_default(),
+                ));
+            }
             let addr = public_ip::addr_v4()
                 .await
                 .ok_or(Error::MissingData("public ip"))?;
@@ -45,17 +106,28 @@ pub mod openmeteo {
                 .await
                 .map_err(Box::new)
                 .map_err(|e| Error::ProviderError(e))?;
+            Ok((
+                loc_info.latitude.parse::<f32>().unwrap(),
+                loc_info.longitude.parse::<f32>().unwrap(),
+                loc_info.city,
+            ))
+        }
+    }
+
+    #[async_trait]
+    impl WeatherProvider for OpenMeteoProvider {
+        async fn get_current_meteo(&self) -> Result<Meteo> {
+            let (latitude, longitude, city) = self.coordinates().await?;
 
             let data = open_meteo_api::query::OpenMeteo::new()
-                .coordinates(
-                    loc_info.latitude.parse::<f32>().unwrap(),
-                    loc_info.longitude.parse::<f32>().unwrap(),
-                )
+                .coordinates(latitude, longitude)
                 .expect("why is this error not Send???")
                 .current_weather()
                 .expect("why is this error not Send???")
                 .time_zone(TimeZone::Auto)
                 .expect("why is this error not Send???")
+                .hourly()
+                .expect("why is this error not Send???")
                 .daily()
                 .expect("why is this error not Send???")
                 .query()
@@ -68,35 +140,53 @@ pub mod openmeteo {
             let daily = data.daily.ok_or(Error::MissingData("daily"))?;
             let daily_units = data.daily_units.ok_or(Error::MissingData("daily_units"))?;
 
-            let max = format!(
-                "{}{}",
-                daily
-                    .temperature_2m_max
-                    .first()
-                    .ok_or(Error::MissingData("max_temperature"))?
-                    .ok_or(Error::MissingData("max_temperature"))?,
-                daily_units.temperature_2m_max
-            );
-            let min = format!(
-                "{}{}",
-                daily
-                    .temperature_2m_min
-                    .first()
-                    .ok_or(Error::MissingData("min_temperature"))?
-                    .ok_or(Error::MissingData("min_temperature"))?,
-                daily_units.temperature_2m_min
-            );
-            let current = format!(
-                "{}{}",
-                current_weather.temperature, daily_units.temperature_2m_min
-            );
+            let max = daily
+                .temperature_2m_max
+                .first()
+                .ok_or(Error::MissingData("max_temperature"))?
+                .ok_or(Error::MissingData("max_temperature"))?;
+            let min = daily
+                .temperature_2m_min
+                .first()
+                .ok_or(Error::MissingData("min_temperature"))?
+                .ok_or(Error::MissingData("min_temperature"))?;
+            let unit = if daily_units.temperature_2m_max.contains('F') {
+                TemperatureUnit::Fahrenheit
+            } else {
+                TemperatureUnit::Celsius
+            };
+            let hourly = data
+                .hourly
+                .map(|hourly| hourly.temperature_2m.into_iter().flatten().collect())
+                .unwrap_or_default();
+            let daily_max = daily.temperature_2m_max.iter().flatten().copied().collect();
+            let daily_min = daily.temperature_2m_min.iter().flatten().copied().collect();
+            // iso timestamps like `2023-01-06T07:32`, keep only the time
+            let sunrise = daily
+                .sunrise
+                .first()
+                .and_then(|time| time.split('T').next_back())
+                .unwrap_or_default()
+                .to_string();
+            let sunset = daily
+                .sunset
+                .first()
+                .and_then(|time| time.split('T').next_back())
+                .unwrap_or_default()
+                .to_string();
 
             let out = Meteo {
                 code: current_weather.weathercode,
-                city: loc_info.city,
-                current,
+                city,
+                current: current_weather.temperature,
                 max,
                 min,
+                unit,
+                sunrise,
+                sunset,
+                hourly,
+                daily_max,
+                daily_min,
             };
             Ok(out)
         }
@@ -118,6 +208,9 @@ pub struct MeteoIcons {
     pub light_snow: String,
     pub thunderstorm: String,
     pub unknown: String,
+    /// the eight lunar phases starting from the new moon,
+    /// drawn by the `%moon` specifier
+    pub moon_phases: Vec<String>,
 }
 
 impl Default for MeteoIcons {
@@ -135,10 +228,99 @@ impl Default for MeteoIcons {
             snow: "󰼶".to_string(),
             thunderstorm: "".to_string(),
             unknown: "".to_string(),
+            moon_phases: ["🌑", "🌒", "🌓", "🌔", "🌕", "🌖", "🌗", "🌘"]
+                .map(String::from)
+                .to_vec(),
         }
     }
 }
 
+impl IconSet for MeteoIcons {
+    fn for_theme(theme: IconTheme) -> Self {
+        match theme {
+            IconTheme::NerdFont => Self::default(),
+            IconTheme::Emoji => Self {
+                clear: "☀️".to_string(),
+                cloudy: "☁️".to_string(),
+                drizzle: "🌦️".to_string(),
+                fog: "🌫️".to_string(),
+                freezing_drizzle: "🌨️".to_string(),
+                freezing_rain: "🌨️".to_string(),
+                hail: "⛈️".to_string(),
+                light_snow: "🌨️".to_string(),
+                rain: "🌧️".to_string(),
+                snow: "❄️".to_string(),
+                thunderstorm: "🌩️".to_string(),
+                unknown: "❓".to_string(),
+                moon_phases: ["🌑", "🌒", "🌓", "🌔", "🌕", "🌖", "🌗", "🌘"]
+                    .map(String::from)
+                    .to_vec(),
+            },
+            IconTheme::Ascii => Self {
+                clear: "clear".to_string(),
+                cloudy: "cloudy".to_string(),
+                drizzle: "drizzle".to_string(),
+                fog: "fog".to_string(),
+                freezing_drizzle: "frz drizzle".to_string(),
+                freezing_rain: "frz rain".to_string(),
+                hail: "hail".to_string(),
+                light_snow: "light snow".to_string(),
+                rain: "rain".to_string(),
+                snow: "snow".to_string(),
+                thunderstorm: "storm".to_string(),
+                unknown: "?".to_string(),
+                moon_phases: [
+                    "new moon",
+                    "waxing crescent",
+                    "first quarter",
+                    "waxing gibbous",
+                    "full moon",
+                    "waning gibbous",
+                    "last quarter",
+                    "waning crescent",
+                ]
+                .map(String::from)
+                .to_vec(),
+            },
+        }
+    }
+
+    fn glyphs(&self) -> Vec<&str> {
+        vec![
+            self.clear.as_str(),
+            self.cloudy.as_str(),
+            self.fog.as_str(),
+            self.freezing_rain.as_str(),
+            self.freezing_drizzle.as_str(),
+            self.hail.as_str(),
+            self.rain.as_str(),
+            self.snow.as_str(),
+            self.drizzle.as_str(),
+            self.light_snow.as_str(),
+            self.thunderstorm.as_str(),
+            self.unknown.as_str(),
+        ]
+        .into_iter()
+        .chain(self.moon_phases.iter().map(String::as_str))
+        .collect()
+    }
+}
+
+/// Index into [MeteoIcons::moon_phases] for today, from the days
+/// elapsed since a known new moon
+fn moon_phase_index(phases: usize) -> usize {
+    // average length of a lunation, in days
+    const SYNODIC_MONTH: f64 = 29.530_588_853;
+    // unix timestamp of the new moon of 2000-01-06 18:14 UTC
+    const REFERENCE: f64 = 947_182_440.0;
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs_f64())
+        .unwrap_or(REFERENCE);
+    let age = ((now - REFERENCE) / 86_400.0).rem_euclid(SYNODIC_MONTH);
+    (age / SYNODIC_MONTH * phases as f64).round() as usize % phases
+}
+
 impl MeteoIcons {
     /// Convert meteo code to icon
     fn translate_code(&self, value: u8) -> &str {
@@ -185,6 +367,9 @@ impl Weather {
     ///  * `%cur` will be replaced with the current temperature
     ///  * `%max` will be replaced with the max temperature
     ///  * `%min` will be replaced with the min temperature
+    ///  * `%sunrise` will be replaced with the sunrise time
+    ///  * `%sunset` will be replaced with the sunset time
+    ///  * `%moon` will be replaced with the current moon phase
     ///* `icons` a [&MeteoIcons]
     ///* `config` a [&WidgetConfig]
     pub async fn new(
@@ -204,6 +389,11 @@ impl Weather {
 
 #[async_trait]
 impl Widget for Weather {
+    async fn setup(&mut self, _info: &StatusBarInfo) -> Result<()> {
+        icons::warn_missing_glyphs(&self.icons, self.inner.font(), "Weather");
+        Ok(())
+    }
+
     async fn update(&mut self) -> Result<()> {
         debug!("updating meteo");
         let meteo = self.provider.get_current_meteo().await?;
@@ -211,9 +401,17 @@ impl Widget for Weather {
             .format
             .replace("%city", &meteo.city.to_string())
             .replace("%icon", self.icons.translate_code(meteo.code as _))
-            .replace("%cur", &meteo.current)
-            .replace("%max", &meteo.max)
-            .replace("%min", &meteo.min);
+            .replace("%cur", &meteo.display(meteo.current))
+            .replace("%max", &meteo.display(meteo.max))
+            .replace("%min", &meteo.display(meteo.min))
+            .replace("%sunrise", &meteo.sunrise)
+            .replace("%sunset", &meteo.sunset);
+        let text_str = if text_str.contains("%moon") {
+            let index = moon_phase_index(self.icons.moon_phases.len());
+            text_str.replace("%moon", &self.icons.moon_phases[index])
+        } else {
+            text_str
+        };
         self.inner.set_text(text_str);
         Ok(())
     }
@@ -222,11 +420,16 @@ impl Widget for Weather {
         // 1 hour
         tokio::spawn(async move {
             loop {
+                connectivity().wait_until_online().await;
                 if let Err(e) = sender.send().await {
                     debug!("breaking thread loop: {}", e);
                     break;
                 }
-                sleep(Duration::from_secs(3600)).await;
+                select!(
+                    _ = sleep(Duration::from_secs(3600)) => {}
+                    // refetch as soon as the connection returns
+                    _ = connectivity().wait_for_reconnect() => {}
+                );
             }
         });
         Ok(())
@@ -1,14 +1,19 @@
 use crate::{
-    utils::{set_source_rgba, Atoms, Color, HookSender, TimedHooks},
-    widgets::{Rectangle, Result, Size, Widget, WidgetConfig},
+    utils::{set_source_rgba, shared_connection, Atoms, Color, HookSender, TimedHooks},
+    widgets::{ClickEvent, MouseButton, Rectangle, Result, Size, Widget, WidgetConfig},
 };
 use async_trait::async_trait;
 use cairo::Context;
 use log::{debug, error};
 use pango::{FontDescription, Layout};
 use pangocairo::functions::{create_context, show_layout};
-use std::{collections::HashSet, fmt::Display, thread};
-use xcb::Connection;
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Display,
+    sync::Arc,
+    thread,
+};
+use xcb::{Connection, XidNew};
 
 pub fn get_desktops_names(connection: &Connection) -> Result<Vec<String>> {
     let atoms = Atoms::new(connection).map_err(Error::from)?;
@@ -28,6 +33,49 @@ pub fn get_desktops_names(connection: &Connection) -> Result<Vec<String>> {
         .collect::<Vec<String>>())
 }
 
+/// Number of windows on every desktop, from the EWMH client list
+pub fn get_window_counts(connection: &Connection) -> Result<HashMap<u32, usize>> {
+    let atoms = Atoms::new(connection).map_err(Error::from)?;
+    let root = connection.get_setup().roots().next().unwrap().root();
+    let cookie = connection.send_request(&xcb::x::GetProperty {
+        delete: false,
+        window: root,
+        property: atoms._NET_CLIENT_LIST,
+        r#type: xcb::x::ATOM_WINDOW,
+        long_offset: 0,
+        long_length: u32::MAX,
+    });
+    let reply = connection.wait_for_reply(cookie).map_err(Error::Xcb)?;
+    let mut counts = HashMap::new();
+    for window in reply.value::<xcb::x::Window>() {
+        let cookie = connection.send_request(&xcb::x::GetProperty {
+            delete: false,
+            window: *window,
+            property: atoms._NET_WM_DESKTOP,
+            r#type: xcb::x::ATOM_CARDINAL,
+            long_offset: 0,
+            long_length: 1,
+        });
+        let Ok(reply) = connection.wait_for_reply(cookie) else {
+            continue;
+        };
+        if let Some(desktop) = reply.value::<u32>().first() {
+            *counts.entry(*desktop).or_insert(0) += 1;
+        }
+    }
+    Ok(counts)
+}
+
+/// 3 -> "³", rendered after the workspace label
+fn superscript(count: usize) -> String {
+    const DIGITS: [char; 10] = ['⁰', '¹', '²', '³', '⁴', '⁵', '⁶', '⁷', '⁸', '⁹'];
+    count
+        .to_string()
+        .chars()
+        .map(|c| DIGITS[c as usize - '0' as usize])
+        .collect()
+}
+
 #[derive(Debug, Eq, PartialEq, Clone, Copy)]
 pub enum WorkspaceStatus {
     Active,
@@ -35,6 +83,73 @@ pub enum WorkspaceStatus {
     Empty,
 }
 
+/// A rule matching a workspace name
+#[derive(Debug)]
+pub enum WorkspaceRule {
+    Exact(String),
+    Prefix(String),
+    Contains(String),
+}
+
+impl WorkspaceRule {
+    fn matches(&self, name: &str) -> bool {
+        match self {
+            WorkspaceRule::Exact(s) => name == s,
+            WorkspaceRule::Prefix(s) => name.starts_with(s),
+            WorkspaceRule::Contains(s) => name.contains(s),
+        }
+    }
+}
+
+/// Rewrites workspace names into custom labels or icons
+///
+/// Rules are applied in insertion order, the first match wins.
+/// *%n* in the label is replaced with the original name.
+#[derive(Debug, Default)]
+pub struct WorkspaceRenamer {
+    rules: Vec<(WorkspaceRule, String)>,
+}
+
+impl WorkspaceRenamer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn exact(mut self, name: impl ToString, label: impl ToString) -> Self {
+        self.rules
+            .push((WorkspaceRule::Exact(name.to_string()), label.to_string()));
+        self
+    }
+
+    pub fn prefix(mut self, prefix: impl ToString, label: impl ToString) -> Self {
+        self.rules
+            .push((WorkspaceRule::Prefix(prefix.to_string()), label.to_string()));
+        self
+    }
+
+    pub fn contains(mut self, pattern: impl ToString, label: impl ToString) -> Self {
+        self.rules
+            .push((WorkspaceRule::Contains(pattern.to_string()), label.to_string()));
+        self
+    }
+
+    fn rename(&self, name: &str) -> String {
+        for (rule, label) in &self.rules {
+            if rule.matches(name) {
+                return label.replace("%n", name);
+            }
+        }
+        name.to_string()
+    }
+}
+
+#[derive(Debug)]
+struct Workspace {
+    name: String,
+    label: String,
+    status: WorkspaceStatus,
+}
+
 /// Displays informations about the active workspaces
 #[derive(Debug)]
 pub struct Workspaces {
@@ -46,7 +161,10 @@ pub struct Workspaces {
     active_workspace_color: Color,
     policy: Box<dyn WorkspaceHider>,
     status_provider: Box<dyn WorkspaceStatusProvider>,
-    workspaces: Vec<(String, WorkspaceStatus)>,
+    renamer: WorkspaceRenamer,
+    wrap_scroll: bool,
+    show_window_counts: bool,
+    workspaces: Vec<Workspace>,
 }
 
 impl Workspaces {
@@ -70,9 +188,32 @@ impl Workspaces {
             font_size: config.font_size,
             policy: Box::new(policy),
             status_provider: Box::new(status_provider),
+            renamer: WorkspaceRenamer::default(),
+            wrap_scroll: false,
+            show_window_counts: false,
         })
     }
 
+    /// Makes scrolling past the first or last workspace wrap around
+    pub fn with_scroll_wrap(mut self: Box<Self>, wrap: bool) -> Box<Self> {
+        self.wrap_scroll = wrap;
+        self
+    }
+
+    /// Sets a [WorkspaceRenamer] used to display icons or labels
+    /// instead of the raw workspace names
+    pub fn with_renamer(mut self: Box<Self>, renamer: WorkspaceRenamer) -> Box<Self> {
+        self.renamer = renamer;
+        self
+    }
+
+    /// Renders the number of windows on each workspace as a
+    /// superscript after its label
+    pub fn with_window_counts(mut self: Box<Self>) -> Box<Self> {
+        self.show_window_counts = true;
+        self
+    }
+
     fn get_layout(&self, context: &Context) -> Result<Layout> {
         let pango_context = create_context(context);
         let layout = Layout::new(&pango_context);
@@ -89,17 +230,17 @@ impl Widget for Workspaces {
         context.move_to(f64::from(self.padding), 0.0);
         let layout = self.get_layout(&context)?;
         let mut first = true;
-        for (workspace, active) in &self.workspaces {
-            let color = match active {
+        for workspace in &self.workspaces {
+            let color = match workspace.status {
                 WorkspaceStatus::Active => self.active_workspace_color,
                 WorkspaceStatus::Used => self.fg_color,
                 WorkspaceStatus::Empty => Color::new(0.4, 0.4, 0.4, 1.0),
             };
-            if self.policy.should_hide(workspace, active) {
+            if self.policy.should_hide(&workspace.name, &workspace.status) {
                 continue;
             }
             set_source_rgba(&context, color);
-            layout.set_text(workspace);
+            layout.set_text(&workspace.label);
             if first {
                 first = false;
                 context.rel_move_to(
@@ -118,23 +259,63 @@ impl Widget for Workspaces {
 
     async fn update(&mut self) -> Result<()> {
         debug!("updating workspaces");
-        let (connection, _) = Connection::connect(None).map_err(Error::from)?;
+        let (connection, _) = shared_connection().map_err(Error::from)?;
         let Ok(workspaces) = get_desktops_names(&connection) else {
             return Ok(());
         };
 
         self.workspaces.clear();
 
+        let counts = if self.show_window_counts {
+            get_window_counts(&connection).unwrap_or_default()
+        } else {
+            HashMap::new()
+        };
+
         self.status_provider.update().await?;
         for (i, workspace) in workspaces.into_iter().enumerate() {
             let f = self.status_provider.status(&workspace, i);
             let new_status = f.await;
-            self.workspaces.push((workspace, new_status));
+            let mut label = self.renamer.rename(&workspace);
+            if let Some(count) = counts.get(&(i as u32)).copied().filter(|c| *c > 0) {
+                label.push_str(&superscript(count));
+            }
+            self.workspaces.push(Workspace {
+                label,
+                name: workspace,
+                status: new_status,
+            });
         }
 
         Ok(())
     }
 
+    async fn on_click(&mut self, event: ClickEvent) -> Result<()> {
+        let delta: i64 = match event.button {
+            MouseButton::ScrollUp => 1,
+            MouseButton::ScrollDown => -1,
+            _ => return Ok(()),
+        };
+        let Some(active) = self
+            .workspaces
+            .iter()
+            .position(|w| w.status == WorkspaceStatus::Active)
+        else {
+            return Ok(());
+        };
+        let len = self.workspaces.len() as i64;
+        let target = active as i64 + delta;
+        let target = if self.wrap_scroll {
+            target.rem_euclid(len)
+        } else if (0..len).contains(&target) {
+            target
+        } else {
+            return Ok(());
+        };
+        let (connection, _) = shared_connection().map_err(Error::from)?;
+        switch_to_desktop(&connection, target as u32)
+    }
+
     async fn hook(&mut self, sender: HookSender, _timed_hooks: &mut TimedHooks) -> Result<()> {
         let (connection, screen_id) = Connection::connect(None).unwrap();
         let root_window = connection
@@ -167,16 +348,16 @@ impl Widget for Workspaces {
         let hidden_workspaces: HashSet<_> = self
             .workspaces
             .iter()
-            .filter(|(w, status)| self.policy.should_hide(w, status))
-            .map(|(w, _)| w)
+            .filter(|w| self.policy.should_hide(&w.name, &w.status))
+            .map(|w| &w.name)
             .collect();
 
         let layout = self.get_layout(context)?;
         let big_string = self
             .workspaces
             .iter()
-            .filter(|(w, _)| !hidden_workspaces.contains(w))
-            .map(|(text, _)| text.clone())
+            .filter(|w| !hidden_workspaces.contains(&w.name))
+            .map(|w| w.label.clone())
             .collect::<String>();
 
         layout.set_text(&big_string);
@@ -219,13 +400,13 @@ pub trait WorkspaceStatusProvider: std::fmt::Debug + Send {
 }
 
 pub struct ActiveProvider {
-    connection: Connection,
+    connection: Arc<Connection>,
     active_index: usize,
 }
 
 impl ActiveProvider {
     pub fn new() -> Result<Self> {
-        let (connection, _) = Connection::connect(None).map_err(Error::from)?;
+        let (connection, _) = shared_connection().map_err(Error::from)?;
         Ok(Self {
             connection,
             active_index: 0,
@@ -254,6 +435,241 @@ impl WorkspaceStatusProvider for ActiveProvider {
     }
 }
 
+/// Asks the window manager to switch to the given desktop
+pub fn switch_to_desktop(connection: &Connection, index: u32) -> Result<()> {
+    let atoms = Atoms::new(connection).map_err(Error::from)?;
+    let root = connection.get_setup().roots().next().unwrap().root();
+    let event = xcb::x::ClientMessageEvent::new(
+        root,
+        atoms._NET_CURRENT_DESKTOP,
+        xcb::x::ClientMessageData::Data32([index, xcb::x::CURRENT_TIME, 0, 0, 0]),
+    );
+    connection
+        .send_and_check_request(&xcb::x::SendEvent {
+            propagate: false,
+            destination: xcb::x::SendEventDest::Window(root),
+            event_mask: xcb::x::EventMask::SUBSTRUCTURE_NOTIFY
+                | xcb::x::EventMask::SUBSTRUCTURE_REDIRECT,
+            event: &event,
+        })
+        .map_err(Error::from)?;
+    connection.flush().map_err(Error::from)?;
+    Ok(())
+}
+
+/// Marks a workspace as used when any client window lives on it,
+/// using the EWMH _NET_CLIENT_LIST and _NET_WM_DESKTOP properties
+pub struct OccupiedProvider {
+    connection: Arc<Connection>,
+    active_index: usize,
+    occupied: HashSet<u32>,
+}
+
+impl OccupiedProvider {
+    pub fn new() -> Result<Self> {
+        let (connection, _) = shared_connection().map_err(Error::from)?;
+        Ok(Self {
+            connection,
+            active_index: 0,
+            occupied: HashSet::new(),
+        })
+    }
+
+    fn read_occupied(&self) -> Result<HashSet<u32>> {
+        let atoms = Atoms::new(&self.connection).map_err(Error::from)?;
+        let root = self.connection.get_setup().roots().next().unwrap().root();
+        let cookie = self.connection.send_request(&xcb::x::GetProperty {
+            delete: false,
+            window: root,
+            property: atoms._NET_CLIENT_LIST,
+            r#type: xcb::x::ATOM_WINDOW,
+            long_offset: 0,
+            long_length: u32::MAX,
+        });
+        let reply = self.connection.wait_for_reply(cookie).map_err(Error::Xcb)?;
+        let mut occupied = HashSet::new();
+        for window_id in reply.value::<u32>() {
+            let window = unsafe { xcb::x::Window::new(*window_id) };
+            let cookie = self.connection.send_request(&xcb::x::GetProperty {
+                delete: false,
+                window,
+                property: atoms._NET_WM_DESKTOP,
+                r#type: xcb::x::ATOM_CARDINAL,
+                long_offset: 0,
+                long_length: 1,
+            });
+            if let Ok(reply) = self.connection.wait_for_reply(cookie) {
+                if let Some(desktop) = reply.value::<u32>().first() {
+                    occupied.insert(*desktop);
+                }
+            }
+        }
+        Ok(occupied)
+    }
+}
+
+impl std::fmt::Debug for OccupiedProvider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Display::fmt("OccupiedProvider", f)
+    }
+}
+
+#[async_trait]
+impl WorkspaceStatusProvider for OccupiedProvider {
+    async fn update(&mut self) -> Result<()> {
+        self.active_index = get_current_desktop(&self.connection)? as usize;
+        self.occupied = self.read_occupied()?;
+        Ok(())
+    }
+
+    async fn status(&self, _workspace: &str, index: usize) -> WorkspaceStatus {
+        if index == self.active_index {
+            WorkspaceStatus::Active
+        } else if self.occupied.contains(&(index as u32)) {
+            WorkspaceStatus::Used
+        } else {
+            WorkspaceStatus::Empty
+        }
+    }
+}
+
+#[cfg(feature = "qtile")]
+pub mod qtile {
+    use super::{ActiveProvider, Error, Result, WorkspaceStatus, WorkspaceStatusProvider};
+    use async_trait::async_trait;
+    use log::error;
+    use pyo3::{types::PyModule, Py, PyResult, Python};
+    use std::collections::HashMap;
+
+    const CLIENT_CODE: &str = r#"from collections import Counter
+from libqtile.command.client import CommandClient
+import signal
+
+signal.signal(signal.SIGINT, signal.SIG_DFL)
+c = CommandClient()
+def windows():
+    windows = c.call("windows")
+    return dict(Counter([(w["group"]) for w in windows if w["group"]]))"#;
+
+    fn load_module() -> PyResult<Py<PyModule>> {
+        Python::with_gil(|py| Ok(PyModule::from_code_bound(py, CLIENT_CODE, "", "")?.into()))
+    }
+
+    /// Counts the windows in each qtile group over the qtile IPC socket
+    pub struct QtileStatusProvider {
+        python_module: Py<PyModule>,
+        active_provider: ActiveProvider,
+        group_count: HashMap<String, usize>,
+    }
+
+    impl QtileStatusProvider {
+        pub async fn new() -> Result<Self> {
+            Ok(Self {
+                python_module: load_module().map_err(Error::from)?,
+                active_provider: ActiveProvider::new()?,
+                group_count: HashMap::new(),
+            })
+        }
+
+        fn windows(&self) -> PyResult<HashMap<String, usize>> {
+            Python::with_gil(|py| {
+                self.python_module
+                    .getattr(py, "windows")?
+                    .call0(py)?
+                    .extract::<HashMap<String, usize>>(py)
+            })
+        }
+    }
+
+    impl std::fmt::Debug for QtileStatusProvider {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            std::fmt::Display::fmt("QtileStatusProvider", f)
+        }
+    }
+
+    #[async_trait]
+    impl WorkspaceStatusProvider for QtileStatusProvider {
+        async fn update(&mut self) -> Result<()> {
+            self.active_provider.update().await?;
+            let group_count = match self.windows() {
+                Ok(group_count) => group_count,
+                Err(e) => {
+                    // the socket path changes when qtile restarts:
+                    // reconnect with a fresh CommandClient and retry once
+                    error!("qtile ipc call failed ({e}), reconnecting");
+                    self.python_module = load_module().map_err(Error::from)?;
+                    let Ok(group_count) = self.windows() else {
+                        error!("Failed to get group count");
+                        return Ok(());
+                    };
+                    group_count
+                }
+            };
+            self.group_count = group_count;
+            Ok(())
+        }
+
+        async fn status(&self, workspace: &str, index: usize) -> WorkspaceStatus {
+            let status = self.active_provider.status(workspace, index).await;
+            if status == WorkspaceStatus::Active {
+                status
+            } else if self.group_count.get(workspace).is_some_and(|c| *c > 0) {
+                WorkspaceStatus::Used
+            } else {
+                WorkspaceStatus::Empty
+            }
+        }
+    }
+}
+
+/// Reads workspace status from herbstluftwm via `herbstclient tag_status`
+#[derive(Debug, Default)]
+pub struct HerbstluftwmProvider {
+    tags: Vec<(String, WorkspaceStatus)>,
+}
+
+impl HerbstluftwmProvider {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl WorkspaceStatusProvider for HerbstluftwmProvider {
+    async fn update(&mut self) -> Result<()> {
+        let output = tokio::process::Command::new("herbstclient")
+            .arg("tag_status")
+            .output()
+            .await
+            .map_err(Error::from)?;
+        self.tags = String::from_utf8_lossy(&output.stdout)
+            .split_whitespace()
+            .filter(|tag| !tag.is_empty())
+            .map(|tag| {
+                let (prefix, name) = tag.split_at(1);
+                let status = match prefix {
+                    "#" => WorkspaceStatus::Active,
+                    "." => WorkspaceStatus::Empty,
+                    // occupied, urgent or shown on another monitor
+                    _ => WorkspaceStatus::Used,
+                };
+                (name.to_string(), status)
+            })
+            .collect();
+        Ok(())
+    }
+
+    async fn status(&self, workspace: &str, index: usize) -> WorkspaceStatus {
+        if let Some((_, status)) = self.tags.iter().find(|(name, _)| name == workspace) {
+            return *status;
+        }
+        self.tags
+            .get(index)
+            .map(|(_, status)| *status)
+            .unwrap_or(WorkspaceStatus::Empty)
+    }
+}
+
 pub fn get_current_desktop(connection: &Connection) -> Result<u32> {
     let atoms = Atoms::new(connection).map_err(Error::from)?;
     let cookie = connection.send_request(&xcb::x::GetProperty {
@@ -279,6 +695,9 @@ pub enum Error {
     Ewmh,
     #[error("Pango")]
     Pango,
+    Io(#[from] std::io::Error),
+    #[cfg(feature = "qtile")]
+    Py(#[from] pyo3::PyErr),
     Xcb(#[from] xcb::Error),
 }
 